@@ -0,0 +1,114 @@
+// Copyright (C) 2023 Tristan Gerritsen <tristan@thewoosh.org>
+// All Rights Reserved.
+
+//! Measures the common operations of a frame with criterion, so regressions
+//! in the rasterizer show up against the saved baselines: clearing, drawing
+//! grids of triangles of different sizes, filling single triangles with the
+//! bounding box algorithm and presenting to a `MemorySurface`, which keeps
+//! the benchmarks headless.
+
+use std::hint::black_box;
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use raggio::{
+    math::Vector2f,
+    platform::MemorySurface,
+    shader::{FragmentShader2D, VertexShader2D},
+    swap_chain::{Extent, FillAlgorithm, SwapChain},
+    Pixel, TriangleVertices,
+};
+
+const WIDTH: usize = 1280;
+const HEIGHT: usize = 720;
+const PIXELS: u64 = WIDTH as u64 * HEIGHT as u64;
+
+struct Shader;
+
+impl VertexShader2D for Shader {
+    fn run(&self, position: Vector2f) -> Vector2f {
+        position
+    }
+}
+
+impl FragmentShader2D for Shader {
+    fn run(&self) -> Pixel {
+        Pixel::WHITE
+    }
+}
+
+fn swap_chain() -> SwapChain {
+    SwapChain::with_extent(Extent { width: WIDTH, height: HEIGHT })
+}
+
+/// Creates a grid of triangles of which every triangle spans the given size
+/// in normalized device coordinates, so every size covers roughly half of
+/// the swap chain.
+fn triangles(size: f32) -> Vec<TriangleVertices> {
+    let count = (2.0 / size) as usize;
+    (0..count * count)
+        .map(|index| {
+            let x = -1.0 + (index % count) as f32 * size;
+            let y = -1.0 + (index / count) as f32 * size;
+            TriangleVertices::new(
+                Vector2f::new(x, y),
+                Vector2f::new(x + size, y),
+                Vector2f::new(x, y + size),
+            )
+        })
+        .collect()
+}
+
+fn clear(c: &mut Criterion) {
+    let mut swap_chain = swap_chain();
+    let mut group = c.benchmark_group("clear");
+    group.throughput(Throughput::Elements(PIXELS));
+    group.bench_function("opaque", |b| b.iter(|| swap_chain.clear(black_box(Pixel::BLACK))));
+    group.finish();
+}
+
+fn draw_rasterized(c: &mut Criterion) {
+    let mut swap_chain = swap_chain();
+    let mut group = c.benchmark_group("draw_rasterized");
+    group.throughput(Throughput::Elements(PIXELS / 2));
+    for (name, size) in [("small", 0.01), ("medium", 0.1), ("large", 1.0)] {
+        let vertices = triangles(size);
+        group.bench_with_input(BenchmarkId::from_parameter(name), &vertices, |b, vertices| {
+            b.iter(|| swap_chain.draw_rasterized(black_box(vertices), &Shader, &Shader));
+        });
+    }
+    group.finish();
+}
+
+/// Fills a single triangle, which covers half of its square bounding box,
+/// with the edge functions tested at every pixel of the bounding box.
+fn bounding_box_fill(c: &mut Criterion) {
+    let mut swap_chain = swap_chain();
+    swap_chain.set_fill_algorithm(FillAlgorithm::HalfSpace);
+    let mut group = c.benchmark_group("bounding_box_fill");
+    for (name, size) in [("small", 0.05), ("medium", 0.5), ("large", 2.0)] {
+        let vertices = [TriangleVertices::new(
+            Vector2f::new(-1.0, -1.0),
+            Vector2f::new(-1.0 + size, -1.0),
+            Vector2f::new(-1.0, -1.0 + size),
+        )];
+        let bounding_box = (size / 2.0) * (size / 2.0) * PIXELS as f32;
+        group.throughput(Throughput::Elements(bounding_box as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(name), &vertices, |b, vertices| {
+            b.iter(|| swap_chain.draw_rasterized(black_box(vertices), &Shader, &Shader));
+        });
+    }
+    group.finish();
+}
+
+fn present(c: &mut Criterion) {
+    let swap_chain = swap_chain();
+    let mut surface = MemorySurface::new();
+    let mut group = c.benchmark_group("present");
+    group.throughput(Throughput::Elements(PIXELS));
+    group.bench_function("memory", |b| b.iter(|| swap_chain.present(&mut surface).unwrap()));
+    group.finish();
+    black_box(surface.pixels());
+}
+
+criterion_group!(benches, clear, draw_rasterized, bounding_box_fill, present);
+criterion_main!(benches);
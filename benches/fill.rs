@@ -0,0 +1,78 @@
+// Copyright (C) 2023 Tristan Gerritsen <tristan@thewoosh.org>
+// All Rights Reserved.
+
+//! Compares the fill throughput of the edge-function rasterizer used by
+//! `SwapChain::draw_rasterized` with hit testing every pixel of the bounding
+//! box using `Triangle2D::hit_test`.
+
+use std::{hint::black_box, time::Instant};
+
+use raggio::{
+    math::{Triangle2D, Vector2, Vector2f},
+    shader::{FragmentShader2D, VertexShader2D},
+    swap_chain::{Extent, SwapChain},
+    Pixel, TriangleVertices,
+};
+
+const WIDTH: usize = 1024;
+const HEIGHT: usize = 1024;
+const ITERATIONS: u32 = 20;
+
+struct Shader;
+
+impl VertexShader2D for Shader {
+    fn run(&self, position: Vector2f) -> Vector2f {
+        position
+    }
+}
+
+impl FragmentShader2D for Shader {
+    fn run(&self) -> Pixel {
+        Pixel::WHITE
+    }
+}
+
+fn report(name: &str, iterations: u32, elapsed_seconds: f64, pixels: u64) {
+    let pixels_per_second = pixels as f64 * iterations as f64 / elapsed_seconds;
+    println!("{name:>20}: {:8.3} ms/iter, {:8.1} Mpixels/s",
+        elapsed_seconds * 1000.0 / iterations as f64, pixels_per_second / 1_000_000.0);
+}
+
+fn main() {
+    let vertices = [
+        TriangleVertices::new(
+            Vector2f::new(-0.9, -0.9),
+            Vector2f::new(0.9, -0.7),
+            Vector2f::new(-0.2, 0.9),
+        )
+    ];
+
+    let mut swap_chain = SwapChain::with_extent(Extent { width: WIDTH, height: HEIGHT });
+    let begin = Instant::now();
+    for _ in 0..ITERATIONS {
+        swap_chain.draw_rasterized(black_box(&vertices), &Shader, &Shader);
+    }
+    let bounding_box_pixels = (0.9 * WIDTH as f64) * (0.9 * HEIGHT as f64);
+    report("edge functions", ITERATIONS, begin.elapsed().as_secs_f64(), bounding_box_pixels as u64);
+
+    let to_pixel = |vertex: Vector2f| Vector2::new(
+        ((vertex.x + 1.0) / 2.0 * WIDTH as f32).round() as i32,
+        ((vertex.y + 1.0) / 2.0 * HEIGHT as f32).round() as i32,
+    );
+    let triangle = Triangle2D(to_pixel(vertices[0].a), to_pixel(vertices[0].b), to_pixel(vertices[0].c));
+    let bounds = triangle.encapsulating_rectangle();
+
+    let mut covered = 0u64;
+    let begin = Instant::now();
+    for _ in 0..ITERATIONS {
+        for y in bounds.y_range() {
+            for x in bounds.x_range() {
+                if black_box(&triangle).hit_test(Vector2::new(x, y)) {
+                    covered += 1;
+                }
+            }
+        }
+    }
+    black_box(covered);
+    report("area hit testing", ITERATIONS, begin.elapsed().as_secs_f64(), bounding_box_pixels as u64);
+}
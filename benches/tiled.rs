@@ -0,0 +1,78 @@
+// Copyright (C) 2023 Tristan Gerritsen <tristan@thewoosh.org>
+// All Rights Reserved.
+
+//! Compares `SwapChain::draw_rasterized` with the `TiledRasterizer` on a scene
+//! of many small, overlapping triangles.
+
+use std::{hint::black_box, time::Instant};
+
+use raggio::{
+    blend::BlendMode,
+    math::Vector2f,
+    shader::{FragmentShader2D, VertexShader2D},
+    swap_chain::{Extent, SwapChain},
+    tiled::TiledRasterizer,
+    Pixel, TriangleVertices,
+};
+
+const WIDTH: usize = 1920;
+const HEIGHT: usize = 1080;
+const TRIANGLES: usize = 50_000;
+const ITERATIONS: u32 = 20;
+
+struct Shader;
+
+impl VertexShader2D for Shader {
+    fn run(&self, position: Vector2f) -> Vector2f {
+        position
+    }
+}
+
+impl FragmentShader2D for Shader {
+    fn run(&self) -> Pixel {
+        Pixel::new(0xFF, 0xFF, 0xFF, 0x20)
+    }
+}
+
+fn small_triangles() -> Vec<TriangleVertices> {
+    // A simple linear congruential generator, so every run draws the same scene.
+    let mut state = 0x6C078965u32;
+    let mut random = || {
+        state = state.wrapping_mul(1664525).wrapping_add(1013904223);
+        (state >> 8) as f32 / (1 << 24) as f32 * 2.0 - 1.0
+    };
+
+    (0..TRIANGLES)
+        .map(|_| {
+            let center = Vector2f::new(random(), random());
+            let mut corner = || Vector2f::new(center.x + random() * 0.02, center.y + random() * 0.02);
+            TriangleVertices::new(corner(), corner(), corner())
+        })
+        .collect()
+}
+
+fn report(name: &str, iterations: u32, elapsed_seconds: f64) {
+    println!("{name:>20}: {:8.3} ms/iter, {:8.1} Mtriangles/s",
+        elapsed_seconds * 1000.0 / iterations as f64,
+        TRIANGLES as f64 * iterations as f64 / elapsed_seconds / 1_000_000.0);
+}
+
+fn main() {
+    let vertices = small_triangles();
+
+    let mut swap_chain = SwapChain::with_extent(Extent { width: WIDTH, height: HEIGHT });
+    swap_chain.set_blend_mode(BlendMode::AlphaBlend);
+
+    let begin = Instant::now();
+    for _ in 0..ITERATIONS {
+        swap_chain.draw_rasterized(black_box(&vertices), &Shader, &Shader);
+    }
+    report("draw_rasterized", ITERATIONS, begin.elapsed().as_secs_f64());
+
+    let mut rasterizer = TiledRasterizer::new();
+    let begin = Instant::now();
+    for _ in 0..ITERATIONS {
+        rasterizer.draw(&mut swap_chain, black_box(&vertices), &Shader, &Shader);
+    }
+    report("tiled", ITERATIONS, begin.elapsed().as_secs_f64());
+}
@@ -28,12 +28,14 @@ impl FragmentShader2D for Shader {
     }
 }
 
-fn main() {
+fn main() -> Result<(), raggio::Error> {
     let event_loop = EventLoop::new();
     let window = WindowBuilder::new().build(&event_loop).unwrap();
 
-    let mut surface = Surface::new(&window).unwrap();
-    let mut swap_chain = SwapChain::new(window.inner_size().to_logical(1.0));
+    let mut surface = Surface::new(&window)?;
+    let scale_factor = window.scale_factor();
+    let size = window.inner_size().to_logical(scale_factor);
+    let mut swap_chain: SwapChain = SwapChain::try_with_scale_factor(size, scale_factor)?;
 
     let shader = Shader{};
 
@@ -58,7 +60,8 @@ fn main() {
                 control_flow.set_exit();
             },
             Event::WindowEvent { event: WindowEvent::Resized(size), .. } => {
-                swap_chain.resize_with_clear_color(size.to_logical(1.0), Pixel::BLACK);
+                let scale_factor = window.scale_factor();
+                swap_chain.resize_with_scale_factor(size.to_logical(scale_factor), scale_factor, Pixel::BLACK);
             },
             Event::RedrawRequested(_) => {
                 println!("[Redraw]");
@@ -72,7 +75,10 @@ fn main() {
                 println!("  Timing of Draw: {} ms", begin.elapsed().as_micros() as f32 / 1000.0);
 
                 let begin = Instant::now();
-                swap_chain.present(&mut surface).unwrap();
+                if let Err(error) = swap_chain.present(&mut surface) {
+                    eprintln!("{}", raggio::Error::from(error));
+                    control_flow.set_exit();
+                }
                 println!("  Timing of Present: {} ms", begin.elapsed().as_micros() as f32 / 1000.0);
             },
             _ => ()
@@ -0,0 +1,158 @@
+// Copyright (C) 2023 Tristan Gerritsen <tristan@thewoosh.org>
+// All Rights Reserved.
+
+//! Batches of triangles with a color per group of triangles, which are
+//! accumulated over a frame and submitted to the swap chain at once.
+
+use crate::{
+    format::PixelFormat,
+    shader::{SolidColor, VertexShader},
+    swap_chain::SwapChain,
+    Pixel, TriangleVertices,
+};
+
+/// Collects triangles with their colors, to draw them with a single
+/// [`DrawBatch::submit`] instead of a call to
+/// [`SwapChain::draw_rasterized`] per color.
+///
+/// The batch keeps its buffers around when it's [cleared](DrawBatch::clear),
+/// so reusing it avoids allocating them every frame.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct DrawBatch {
+    vertices: Vec<TriangleVertices>,
+    colors: Vec<Pixel>,
+}
+
+impl DrawBatch {
+
+    /// Creates an empty batch.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Checks whether the batch has no triangles.
+    pub fn is_empty(&self) -> bool {
+        self.vertices.is_empty()
+    }
+
+    /// Gets the amount of triangles in the batch.
+    pub fn len(&self) -> usize {
+        self.vertices.len()
+    }
+
+    /// Gets the triangles in the order they were pushed, together with their
+    /// colors.
+    pub fn triangles(&self) -> impl Iterator<Item = (&TriangleVertices, Pixel)> {
+        self.vertices.iter().zip(self.colors.iter().copied())
+    }
+
+    /// Removes all of the triangles from the batch.
+    pub fn clear(&mut self) {
+        self.vertices.clear();
+        self.colors.clear();
+    }
+
+    /// Adds the triangles to the batch, which are filled with the color
+    /// specified with straight alpha.
+    pub fn push(&mut self, vertices: &[TriangleVertices], color: Pixel) -> &mut Self {
+        self.vertices.extend_from_slice(vertices);
+        self.colors.resize(self.vertices.len(), color);
+        self
+    }
+
+    /// Draws the triangles of the batch to the swap chain, in the order they
+    /// were pushed, using the state of the swap chain. The output is identical
+    /// to drawing every group of triangles with its own call to
+    /// [`SwapChain::draw_rasterized`], except that the triangles are numbered
+    /// across the whole batch for [picking](SwapChain::set_picking).
+    pub fn submit<P: PixelFormat>(&self, swap_chain: &mut SwapChain<P>, vertex_shader: &dyn VertexShader) {
+        let Some(clip_rectangle) = swap_chain.clip_rectangle() else {
+            return;
+        };
+
+        for (index, (vertex_triple, color)) in self.triangles().enumerate() {
+            let triangles = swap_chain.assemble_triangles(vertex_triple, index, vertex_shader, &(),
+                &clip_rectangle);
+            for triangle in triangles {
+                if let Some(bounds) = swap_chain.rasterization_bounds(&triangle.triangle) {
+                    swap_chain.rasterize_triangle_bounded(&triangle, &bounds, &SolidColor, &color);
+                }
+            }
+        }
+    }
+
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        blend::BlendMode,
+        math::Vector2f,
+        shader::{FragmentShader2D, VertexShader2D},
+        swap_chain::Extent,
+    };
+
+    struct Identity;
+
+    impl VertexShader2D for Identity {
+        fn run(&self, position: Vector2f) -> Vector2f {
+            position
+        }
+    }
+
+    struct Solid(Pixel);
+
+    impl FragmentShader2D for Solid {
+        fn run(&self) -> Pixel {
+            self.0
+        }
+    }
+
+    #[test]
+    fn batch_matches_separate_draws() {
+        let triangles = [
+            TriangleVertices::new(
+                Vector2f::new(-1.0, -1.0),
+                Vector2f::new(0.5, -1.0),
+                Vector2f::new(-1.0, 0.5),
+            ),
+            TriangleVertices::new(
+                Vector2f::new(-0.5, -0.5),
+                Vector2f::new(1.0, -0.2),
+                Vector2f::new(0.0, 1.0),
+            ),
+            TriangleVertices::new(
+                Vector2f::new(1.0, 1.0),
+                Vector2f::new(-1.0, 0.8),
+                Vector2f::new(0.2, -0.9),
+            ),
+        ];
+        let colors = [
+            Pixel::new(0xFF, 0x00, 0x00, 0xC0),
+            Pixel::new(0x00, 0xFF, 0x00, 0x80),
+            Pixel::new(0x00, 0x00, 0xFF, 0x40),
+        ];
+
+        let mut expected = SwapChain::with_extent(Extent { width: 24, height: 16 });
+        expected.set_blend_mode(BlendMode::AlphaBlend);
+        for (triangle, color) in triangles.iter().zip(colors) {
+            expected.draw_rasterized(std::slice::from_ref(triangle), &Identity, &Solid(color));
+        }
+
+        let mut batch = DrawBatch::new();
+        for (triangle, color) in triangles.iter().zip(colors) {
+            batch.push(std::slice::from_ref(triangle), color);
+        }
+        assert_eq!(batch.len(), 3);
+
+        let mut swap_chain = SwapChain::with_extent(Extent { width: 24, height: 16 });
+        swap_chain.set_blend_mode(BlendMode::AlphaBlend);
+        batch.submit(&mut swap_chain, &Identity);
+        assert_eq!(swap_chain.as_slice(), expected.as_slice());
+        assert!(swap_chain.as_slice().iter().any(|pixel| *pixel != Pixel::BLACK));
+
+        batch.clear();
+        assert!(batch.is_empty());
+    }
+}
@@ -0,0 +1,247 @@
+// Copyright (C) 2023 Tristan Gerritsen <tristan@thewoosh.org>
+// All Rights Reserved.
+
+use crate::{Pixel, pixel::ColorSpace};
+
+/// Describes how the output of the fragment shader is combined with the
+/// pixel that is already present in the swap chain.
+///
+/// Blending operates on straight (non-premultiplied) alpha, unless
+/// [`BlendMode::blend_premultiplied`] is used. By default it is
+/// performed in the gamma-encoded space the pixels are stored in, but see
+/// [`BlendMode::blend_in`] for blending in linear space.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub enum BlendMode {
+
+    /// The source pixel replaces the destination pixel, alpha is ignored.
+    #[default]
+    Opaque,
+
+    /// Source-over compositing: the source is placed on top of the
+    /// destination, weighted by the alpha of the source.
+    AlphaBlend,
+
+    /// The source color, weighted by its alpha, is added to the destination.
+    /// Every channel saturates at `0xFF`.
+    Additive,
+
+}
+
+/// Divides the value by 255, rounding to the nearest integer.
+#[inline]
+fn div_255(value: u32) -> u32 {
+    (value + 127) / 255
+}
+
+impl BlendMode {
+
+    /// Combines the `source` pixel with the `destination` pixel.
+    pub fn blend(&self, source: Pixel, destination: Pixel) -> Pixel {
+        match self {
+            BlendMode::Opaque => source,
+            BlendMode::AlphaBlend => blend_source_over(source, destination),
+            BlendMode::Additive => blend_additive(source, destination),
+        }
+    }
+
+    /// Combines the `source` pixel with the `destination` pixel, both of which
+    /// have premultiplied alpha.
+    pub fn blend_premultiplied(&self, source: Pixel, destination: Pixel) -> Pixel {
+        match self {
+            BlendMode::Opaque => source,
+            BlendMode::AlphaBlend => {
+                let inverse_alpha = 255 - source.alpha() as u32;
+                let channel = |source: u8, destination: u8| {
+                    (source as u32 + div_255(destination as u32 * inverse_alpha)) as u8
+                };
+                Pixel::new(
+                    channel(source.red(), destination.red()),
+                    channel(source.green(), destination.green()),
+                    channel(source.blue(), destination.blue()),
+                    channel(source.alpha(), destination.alpha()),
+                )
+            }
+            BlendMode::Additive => Pixel::new(
+                destination.red().saturating_add(source.red()),
+                destination.green().saturating_add(source.green()),
+                destination.blue().saturating_add(source.blue()),
+                destination.alpha().saturating_add(source.alpha()),
+            ),
+        }
+    }
+
+    /// Combines the `source` pixel with the `destination` pixel in the given
+    /// color space.
+    pub fn blend_in(&self, color_space: ColorSpace, source: Pixel, destination: Pixel) -> Pixel {
+        match (self, color_space) {
+            (BlendMode::Opaque, _) => source,
+            (_, ColorSpace::Srgb) => self.blend(source, destination),
+            (_, ColorSpace::Linear) => Pixel::from_linear(self.blend_f32(source.to_linear(),
+                destination.to_linear())),
+        }
+    }
+
+    /// Combines the `source` color with the `destination` color, both given
+    /// as normalized `[red, green, blue, alpha]` values with straight alpha,
+    /// without rounding to 8 bits. Either color space works, as long as both
+    /// colors are in the same one.
+    pub fn blend_f32(&self, source: [f32; 4], destination: [f32; 4]) -> [f32; 4] {
+        match self {
+            BlendMode::Opaque => source,
+            BlendMode::AlphaBlend => blend_source_over_f32(source, destination),
+            BlendMode::Additive => blend_additive_f32(source, destination),
+        }
+    }
+
+}
+
+fn blend_source_over(source: Pixel, destination: Pixel) -> Pixel {
+    let source_alpha = source.alpha() as u32;
+    let destination_alpha = destination.alpha() as u32;
+    let inverse_alpha = 255 - source_alpha;
+
+    // Both weights are scaled by 255, so the division of the channels below
+    // doesn't need any rescaling.
+    let source_weight = source_alpha * 255;
+    let destination_weight = destination_alpha * inverse_alpha;
+    let total_weight = source_weight + destination_weight;
+
+    if total_weight == 0 {
+        return Pixel::TRANSPARENT;
+    }
+
+    let channel = |source: u8, destination: u8| {
+        let value = source as u32 * source_weight + destination as u32 * destination_weight;
+        ((value + total_weight / 2) / total_weight) as u8
+    };
+
+    Pixel::new(
+        channel(source.red(), destination.red()),
+        channel(source.green(), destination.green()),
+        channel(source.blue(), destination.blue()),
+        (source_alpha + div_255(destination_alpha * inverse_alpha)) as u8,
+    )
+}
+
+fn blend_additive(source: Pixel, destination: Pixel) -> Pixel {
+    let source_alpha = source.alpha() as u32;
+
+    let channel = |source: u8, destination: u8| {
+        (destination as u32 + div_255(source as u32 * source_alpha)).min(0xFF) as u8
+    };
+
+    Pixel::new(
+        channel(source.red(), destination.red()),
+        channel(source.green(), destination.green()),
+        channel(source.blue(), destination.blue()),
+        destination.alpha().saturating_add(source.alpha()),
+    )
+}
+
+fn blend_source_over_f32(source: [f32; 4], destination: [f32; 4]) -> [f32; 4] {
+    let source_weight = source[3];
+    let destination_weight = destination[3] * (1.0 - source[3]);
+    let alpha = source_weight + destination_weight;
+
+    if alpha == 0.0 {
+        return [0.0; 4];
+    }
+
+    let channel = |i: usize| (source[i] * source_weight + destination[i] * destination_weight) / alpha;
+    [channel(0), channel(1), channel(2), alpha]
+}
+
+fn blend_additive_f32(source: [f32; 4], destination: [f32; 4]) -> [f32; 4] {
+    let channel = |i: usize| (destination[i] + source[i] * source[3]).min(1.0);
+    [channel(0), channel(1), channel(2), (destination[3] + source[3]).min(1.0)]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn opaque_replaces_destination() {
+        let source = Pixel::new(0x10, 0x20, 0x30, 0x00);
+        let destination = Pixel::new(0xFF, 0xFF, 0xFF, 0xFF);
+        assert_eq!(BlendMode::Opaque.blend(source, destination), source);
+    }
+
+    #[test]
+    fn alpha_blend_half_white_over_black() {
+        let source = Pixel::new(0xFF, 0xFF, 0xFF, 0x80);
+        let destination = Pixel::BLACK;
+        assert_eq!(BlendMode::AlphaBlend.blend(source, destination), Pixel::new(0x80, 0x80, 0x80, 0xFF));
+    }
+
+    #[test]
+    fn alpha_blend_extremes() {
+        let source = Pixel::new(0x12, 0x34, 0x56, 0xFF);
+        let destination = Pixel::new(0xAB, 0xCD, 0xEF, 0xFF);
+        assert_eq!(BlendMode::AlphaBlend.blend(source, destination), source);
+
+        let source = Pixel::new(0x12, 0x34, 0x56, 0x00);
+        assert_eq!(BlendMode::AlphaBlend.blend(source, destination), destination);
+
+        assert_eq!(BlendMode::AlphaBlend.blend(Pixel::TRANSPARENT, Pixel::TRANSPARENT), Pixel::TRANSPARENT);
+    }
+
+    #[test]
+    fn additive_saturates() {
+        let source = Pixel::new(0xFF, 0x80, 0x00, 0xFF);
+        let destination = Pixel::new(0x80, 0x80, 0x80, 0xFF);
+        assert_eq!(BlendMode::Additive.blend(source, destination), Pixel::new(0xFF, 0xFF, 0x80, 0xFF));
+
+        let source = Pixel::new(0xFF, 0xFF, 0xFF, 0x80);
+        let destination = Pixel::BLACK;
+        assert_eq!(BlendMode::Additive.blend(source, destination), Pixel::new(0x80, 0x80, 0x80, 0xFF));
+    }
+
+    #[test]
+    fn alpha_blend_in_linear_space() {
+        let source = Pixel::new(0xFF, 0xFF, 0xFF, 0x80);
+        let blended = BlendMode::AlphaBlend.blend_in(ColorSpace::Linear, source, Pixel::BLACK);
+        assert_eq!(blended, Pixel::new(0xBC, 0xBC, 0xBC, 0xFF));
+
+        let blended = BlendMode::AlphaBlend.blend_in(ColorSpace::Srgb, source, Pixel::BLACK);
+        assert_eq!(blended, BlendMode::AlphaBlend.blend(source, Pixel::BLACK));
+    }
+
+    #[test]
+    fn blend_f32_keeps_precision() {
+        let source = [1.0, 1.0, 1.0, 0.01];
+        let mut destination = [0.0, 0.0, 0.0, 1.0];
+        for _ in 0..10 {
+            destination = BlendMode::AlphaBlend.blend_f32(source, destination);
+        }
+        let expected = 1.0 - 0.99f32.powi(10);
+        assert!((destination[0] - expected).abs() < 1e-6, "{destination:?}");
+        assert_eq!(destination[3], 1.0);
+
+        let added = BlendMode::Additive.blend_f32([0.5, 0.25, 1.0, 0.5], [0.75, 0.0, 0.0, 1.0]);
+        assert_eq!(added, [1.0, 0.125, 0.5, 1.0]);
+        assert_eq!(BlendMode::Opaque.blend_f32(source, destination), source);
+    }
+
+    #[test]
+    fn additive_in_linear_space() {
+        let source = Pixel::new(0xFF, 0x00, 0x00, 0xFF);
+        let destination = Pixel::new(0x80, 0x80, 0x80, 0xFF);
+        let blended = BlendMode::Additive.blend_in(ColorSpace::Linear, source, destination);
+        assert_eq!(blended, Pixel::new(0xFF, 0x80, 0x80, 0xFF));
+    }
+
+    #[test]
+    fn blend_premultiplied() {
+        let source = Pixel::new(0xFF, 0xFF, 0xFF, 0x80).premultiplied();
+        let gray = Pixel::new(0x80, 0x80, 0x80, 0xFF);
+        assert_eq!(BlendMode::AlphaBlend.blend_premultiplied(source, Pixel::BLACK), gray);
+        assert_eq!(BlendMode::Additive.blend_premultiplied(source, Pixel::BLACK), gray);
+        assert_eq!(BlendMode::Opaque.blend_premultiplied(source, Pixel::BLACK), source);
+
+        let source = Pixel::new(0xFF, 0x80, 0x00, 0xFF);
+        let destination = Pixel::new(0x80, 0x80, 0x80, 0xFF);
+        let blended = BlendMode::Additive.blend_premultiplied(source, destination);
+        assert_eq!(blended, Pixel::new(0xFF, 0xFF, 0x80, 0xFF));
+    }
+}
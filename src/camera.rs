@@ -0,0 +1,164 @@
+// Copyright (C) 2023 Tristan Gerritsen <tristan@thewoosh.org>
+// All Rights Reserved.
+
+use crate::math::{Matrix3, Vector2f, Vector3};
+
+/// A camera looking at a 2D world, which can be moved, zoomed and rotated. Its
+/// view matrix transforms world coordinates to normalized device coordinates,
+/// which makes it suitable as a uniform of a vertex shader.
+///
+/// Without zoom, one unit in the world is one unit in normalized device
+/// coordinates, i.e. half of the width or height of the swap chain.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Camera2D {
+
+    /// The point in the world that is shown at the center of the view.
+    pub position: Vector2f,
+
+    /// The factor by which the world is magnified.
+    pub zoom: f32,
+
+    /// The counter-clockwise rotation of the camera in radians. The world
+    /// appears rotated in the opposite direction.
+    pub rotation: f32,
+
+}
+
+impl Camera2D {
+
+    /// Creates a camera looking at the given position, without zoom or
+    /// rotation.
+    pub fn new(position: Vector2f) -> Self {
+        Self {
+            position,
+            zoom: 1.0,
+            rotation: 0.0,
+        }
+    }
+
+    /// Gets the matrix transforming world coordinates to normalized device
+    /// coordinates.
+    pub fn view_matrix(&self) -> Matrix3 {
+        Matrix3::scaling(self.zoom, self.zoom)
+            * Matrix3::rotation(-self.rotation)
+            * Matrix3::translation(-self.position.x, -self.position.y)
+    }
+
+    /// Transforms the point in world coordinates to normalized device
+    /// coordinates.
+    pub fn world_to_ndc(&self, point: Vector2f) -> Vector2f {
+        self.view_matrix().transform_point(point)
+    }
+
+}
+
+impl Default for Camera2D {
+    fn default() -> Self {
+        Self::new(Vector2f::default())
+    }
+}
+
+/// A perspective camera in a 3D world, looking from its position towards its
+/// target, such as for the [`RayTracer`](crate::raytrace::RayTracer).
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Camera3D {
+
+    /// The point the camera looks from, where every ray starts.
+    pub position: Vector3,
+
+    /// The point in the world that is shown at the center of the view.
+    pub target: Vector3,
+
+    /// The direction in the world that is up in the view, which must not be
+    /// parallel to the direction the camera looks in.
+    pub up: Vector3,
+
+    /// The vertical field of view in radians.
+    pub fov_y: f32,
+
+}
+
+impl Camera3D {
+
+    /// Creates a camera looking from the position at the target, with the
+    /// positive Y axis up and a vertical field of view of 90 degrees.
+    pub fn new(position: Vector3, target: Vector3) -> Self {
+        Self {
+            position,
+            target,
+            up: Vector3::new(0.0, 1.0, 0.0),
+            fov_y: std::f32::consts::FRAC_PI_2,
+        }
+    }
+
+    /// Gets the unit direction of the ray from the camera through the point
+    /// of the view, where `(-1, -1)` is the bottom left and `(1, 1)` the top
+    /// right corner, given the ratio of the width to the height of the view.
+    pub fn ray_direction(&self, point: Vector2f, aspect_ratio: f32) -> Vector3 {
+        let scale = |vector: Vector3, factor: f32| Vector3::new(vector.x * factor, vector.y * factor,
+            vector.z * factor);
+
+        let forward = (self.target - self.position).normalized();
+        let right = forward.cross(self.up).normalized();
+        let up = right.cross(forward);
+
+        let half_height = (self.fov_y / 2.0).tan();
+        let offset = scale(right, point.x * half_height * aspect_ratio) + scale(up, point.y * half_height);
+        (forward + offset).normalized()
+    }
+
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(actual: Vector2f, expected: Vector2f) {
+        assert!(Vector2f::distance(actual, expected) < 0.0001, "{actual} isn't {expected}");
+    }
+
+    #[test]
+    fn default_camera_is_identity() {
+        let point = Vector2f::new(0.25, -0.5);
+        assert_eq!(Camera2D::default().view_matrix(), Matrix3::IDENTITY);
+        assert_eq!(Camera2D::default().world_to_ndc(point), point);
+    }
+
+    #[test]
+    fn panning_shifts_points() {
+        let mut camera = Camera2D::new(Vector2f::new(1.0, 0.0));
+        assert_close(camera.world_to_ndc(Vector2f::new(1.0, 0.0)), Vector2f::new(0.0, 0.0));
+        assert_close(camera.world_to_ndc(Vector2f::new(1.5, 0.5)), Vector2f::new(0.5, 0.5));
+
+        camera.position.y = -2.0;
+        assert_close(camera.world_to_ndc(Vector2f::new(1.5, 0.5)), Vector2f::new(0.5, 2.5));
+    }
+
+    #[test]
+    fn camera3d_rays() {
+        let camera = Camera3D::new(Vector3::new(0.0, 0.0, 2.0), Vector3::default());
+        let close = |actual: Vector3, expected: Vector3| (actual - expected).length() < 0.0001;
+
+        let center = camera.ray_direction(Vector2f::new(0.0, 0.0), 1.0);
+        assert!(close(center, Vector3::new(0.0, 0.0, -1.0)), "{center}");
+
+        // At 90 degrees, the corners of the view are at 45 degrees along
+        // both axes.
+        let top_right = camera.ray_direction(Vector2f::new(1.0, 1.0), 1.0);
+        assert!(close(top_right, Vector3::new(1.0, 1.0, -1.0).normalized()), "{top_right}");
+
+        let wide = camera.ray_direction(Vector2f::new(1.0, 0.0), 2.0);
+        assert!(close(wide, Vector3::new(2.0, 0.0, -1.0).normalized()), "{wide}");
+    }
+
+    #[test]
+    fn zoom_and_rotation() {
+        let mut camera = Camera2D::new(Vector2f::new(1.0, 1.0));
+        camera.zoom = 2.0;
+        assert_close(camera.world_to_ndc(Vector2f::new(1.5, 1.0)), Vector2f::new(1.0, 0.0));
+
+        // Rotating the camera counter-clockwise turns the world clockwise.
+        camera.rotation = std::f32::consts::FRAC_PI_2;
+        assert_close(camera.world_to_ndc(Vector2f::new(1.5, 1.0)), Vector2f::new(0.0, -1.0));
+    }
+}
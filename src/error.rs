@@ -0,0 +1,99 @@
+// Copyright (C) 2023 Tristan Gerritsen <tristan@thewoosh.org>
+// All Rights Reserved.
+
+use std::fmt;
+
+use crate::{
+    platform::{SurfaceCreationError, SurfacePresentationError},
+    swap_chain::SwapChainError,
+};
+
+/// Any of the errors of this crate, so applications can propagate failures to
+/// create or present to a window with a single error type.
+#[derive(Debug)]
+pub enum Error {
+
+    /// The surface for the window couldn't be created.
+    SurfaceCreation(SurfaceCreationError),
+
+    /// The swap chain couldn't be presented to the surface.
+    SurfacePresentation(SurfacePresentationError),
+
+    /// The swap chain couldn't be created.
+    SwapChain(SwapChainError),
+
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::SurfaceCreation(error) => write!(f, "failed to create surface: {error}"),
+            Error::SurfacePresentation(error) => write!(f, "failed to present: {error}"),
+            Error::SwapChain(error) => write!(f, "failed to create swap chain: {error}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::SurfaceCreation(error) => Some(error),
+            Error::SurfacePresentation(error) => Some(error),
+            Error::SwapChain(error) => Some(error),
+        }
+    }
+}
+
+impl From<SurfaceCreationError> for Error {
+    fn from(error: SurfaceCreationError) -> Self {
+        Error::SurfaceCreation(error)
+    }
+}
+
+impl From<SurfacePresentationError> for Error {
+    fn from(error: SurfacePresentationError) -> Self {
+        Error::SurfacePresentation(error)
+    }
+}
+
+impl From<SwapChainError> for Error {
+    fn from(error: SwapChainError) -> Self {
+        Error::SwapChain(error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::error::Error as _;
+
+    use super::*;
+
+    #[test]
+    fn converts_from_errors() {
+        assert!(matches!(Error::from(SurfaceCreationError::InvalidHandle),
+            Error::SurfaceCreation(SurfaceCreationError::InvalidHandle)));
+        assert!(matches!(Error::from(SurfacePresentationError::ImageTooLarge),
+            Error::SurfacePresentation(SurfacePresentationError::ImageTooLarge)));
+        assert!(matches!(Error::from(SwapChainError::ZeroExtent),
+            Error::SwapChain(SwapChainError::ZeroExtent)));
+
+        fn present() -> Result<(), Error> {
+            Err(SurfacePresentationError::BufferSizeMismatch)?
+        }
+        assert!(matches!(present(), Err(Error::SurfacePresentation(_))));
+    }
+
+    #[test]
+    fn display() {
+        let error = Error::from(SurfaceCreationError::UnsupportedPlatform);
+        assert_eq!(error.to_string(), "failed to create surface: the platform is not supported");
+        assert_eq!(error.source().unwrap().to_string(), "the platform is not supported");
+
+        let error = Error::from(SurfacePresentationError::BufferSizeMismatch);
+        assert_eq!(error.to_string(), "failed to present: the number of pixels doesn't match the extent");
+
+        let error = Error::from(SwapChainError::TooLarge);
+        assert_eq!(error.to_string(),
+            "failed to create swap chain: the pixel buffer doesn't fit in the address space");
+    }
+}
@@ -0,0 +1,93 @@
+// Copyright (C) 2023 Tristan Gerritsen <tristan@thewoosh.org>
+// All Rights Reserved.
+
+use crate::{math::Vector2f, swap_chain::Extent, Pixel};
+
+/// The way an image is sampled at positions that lie between its pixels.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Filter {
+
+    /// Take the pixel closest to the position.
+    #[default]
+    Nearest,
+
+    /// Interpolate linearly between the four pixels surrounding the
+    /// position.
+    Bilinear,
+
+}
+
+impl Filter {
+
+    /// Samples the image at the given position, in pixels, where the pixel
+    /// centers lie on integer coordinates. Positions outside of the image are
+    /// clamped to its edges.
+    ///
+    /// The image must have at least one pixel.
+    pub(crate) fn sample(&self, image: &[Pixel], extent: Extent, position: Vector2f) -> Pixel {
+        let max_x = extent.width as f32 - 1.0;
+        let max_y = extent.height as f32 - 1.0;
+        let x = position.x.clamp(0.0, max_x);
+        let y = position.y.clamp(0.0, max_y);
+        let pixel = |x: f32, y: f32| image[y as usize * extent.width + x as usize];
+
+        match self {
+            Filter::Nearest => pixel(x.round(), y.round()),
+            Filter::Bilinear => {
+                let (left, top) = (x.floor(), y.floor());
+                let (right, bottom) = ((left + 1.0).min(max_x), (top + 1.0).min(max_y));
+                let (t_x, t_y) = (x - left, y - top);
+
+                // Interpolating premultiplied colors keeps the colors of
+                // transparent pixels from bleeding into their neighbours.
+                let weighted = [
+                    (pixel(left, top), (1.0 - t_x) * (1.0 - t_y)),
+                    (pixel(right, top), t_x * (1.0 - t_y)),
+                    (pixel(left, bottom), (1.0 - t_x) * t_y),
+                    (pixel(right, bottom), t_x * t_y),
+                ];
+
+                let mut sums = [0.0f32; 4];
+                for (pixel, weight) in weighted {
+                    let pixel = pixel.premultiplied();
+                    sums[0] += pixel.red() as f32 * weight;
+                    sums[1] += pixel.green() as f32 * weight;
+                    sums[2] += pixel.blue() as f32 * weight;
+                    sums[3] += pixel.alpha() as f32 * weight;
+                }
+
+                let [red, green, blue, alpha] = sums.map(|sum| sum.round().clamp(0.0, 255.0) as u8);
+                Pixel::new(red, green, blue, alpha).unpremultiplied()
+            }
+        }
+    }
+
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sample_nearest_and_bilinear() {
+        let image = [Pixel::BLACK, Pixel::WHITE];
+        let extent = Extent { width: 2, height: 1 };
+
+        assert_eq!(Filter::Nearest.sample(&image, extent, Vector2f::new(0.4, 0.0)), Pixel::BLACK);
+        assert_eq!(Filter::Nearest.sample(&image, extent, Vector2f::new(0.6, 0.0)), Pixel::WHITE);
+        assert_eq!(Filter::Bilinear.sample(&image, extent, Vector2f::new(0.5, 0.0)),
+            Pixel::new(0x80, 0x80, 0x80, 0xFF));
+
+        // Positions outside of the image are clamped.
+        assert_eq!(Filter::Bilinear.sample(&image, extent, Vector2f::new(-3.0, 5.0)), Pixel::BLACK);
+        assert_eq!(Filter::Bilinear.sample(&image, extent, Vector2f::new(8.0, -1.0)), Pixel::WHITE);
+    }
+
+    #[test]
+    fn bilinear_ignores_color_of_transparent_pixels() {
+        let image = [Pixel::RED, Pixel::new(0x00, 0xFF, 0x00, 0x00)];
+        let extent = Extent { width: 2, height: 1 };
+        assert_eq!(Filter::Bilinear.sample(&image, extent, Vector2f::new(0.5, 0.0)),
+            Pixel::new(0xFF, 0x00, 0x00, 0x80));
+    }
+}
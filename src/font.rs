@@ -0,0 +1,128 @@
+// Copyright (C) 2023 Tristan Gerritsen <tristan@thewoosh.org>
+// All Rights Reserved.
+
+//! Fixed-width bitmap fonts, of which the glyphs are stored in an image,
+//! the atlas, for drawing basic text such as HUDs and debug output.
+
+use crate::{swap_chain::Extent, Pixel};
+
+/// A font of which every glyph has the same size, stored in an atlas of
+/// glyphs laid out in rows, from left to right and top to bottom. The glyphs
+/// are for consecutive characters, starting at the first character of the
+/// font.
+///
+/// The glyphs are usually white, of which the alpha gives their shape, since
+/// they are tinted by the color of the text.
+#[derive(Clone, Debug, PartialEq)]
+pub struct BitmapFont {
+    atlas: Vec<Pixel>,
+    atlas_extent: Extent,
+    glyph_extent: Extent,
+    first_character: char,
+}
+
+impl BitmapFont {
+
+    /// Creates the font from the atlas, specified with straight alpha, with
+    /// glyphs of the given extent. Any pixels to the right and bottom of the
+    /// atlas that don't make up a whole glyph are ignored.
+    ///
+    /// # Panics
+    /// Panics when the length of the atlas doesn't match its extent, or when
+    /// the glyphs are empty.
+    pub fn new(atlas: &[Pixel], atlas_extent: Extent, glyph_extent: Extent, first_character: char) -> Self {
+        assert_eq!(atlas.len(), atlas_extent.width * atlas_extent.height, "atlas doesn't match its extent");
+        assert!(!glyph_extent.is_empty(), "glyphs must have at least one pixel");
+
+        Self {
+            atlas: atlas.to_vec(),
+            atlas_extent,
+            glyph_extent,
+            first_character,
+        }
+    }
+
+    /// Gets the character of the first glyph in the atlas.
+    pub fn first_character(&self) -> char {
+        self.first_character
+    }
+
+    /// Gets the extent of every glyph, which is also how far the text
+    /// advances per character and per line.
+    pub fn glyph_extent(&self) -> Extent {
+        self.glyph_extent
+    }
+
+    /// Gets the amount of glyphs in the atlas.
+    pub fn glyph_count(&self) -> usize {
+        let columns = self.atlas_extent.width / self.glyph_extent.width;
+        columns * (self.atlas_extent.height / self.glyph_extent.height)
+    }
+
+    /// Checks whether the font has a glyph for the character.
+    pub fn has_glyph(&self, character: char) -> bool {
+        self.glyph_index(character).is_some()
+    }
+
+    /// Gets the pixel of the glyph of the character at the position in the
+    /// glyph, or `None` when the font has no glyph for the character.
+    ///
+    /// # Panics
+    /// Panics when the position is outside of the glyph.
+    pub fn glyph_pixel(&self, character: char, x: usize, y: usize) -> Option<Pixel> {
+        assert!(x < self.glyph_extent.width && y < self.glyph_extent.height,
+            "position is outside of the glyph");
+
+        let index = self.glyph_index(character)?;
+        let columns = self.atlas_extent.width / self.glyph_extent.width;
+        let atlas_x = index % columns * self.glyph_extent.width + x;
+        let atlas_y = index / columns * self.glyph_extent.height + y;
+        Some(self.atlas[atlas_y * self.atlas_extent.width + atlas_x])
+    }
+
+    fn glyph_index(&self, character: char) -> Option<usize> {
+        let index = (character as u32).checked_sub(self.first_character as u32)? as usize;
+        (index < self.glyph_count()).then_some(index)
+    }
+
+}
+
+/// Multiplies every channel of the pixel by the channel of the color.
+pub(crate) fn tint(pixel: Pixel, color: Pixel) -> Pixel {
+    let channel = |a: u8, b: u8| ((a as u32 * b as u32 + 127) / 255) as u8;
+    Pixel::new(
+        channel(pixel.red(), color.red()),
+        channel(pixel.green(), color.green()),
+        channel(pixel.blue(), color.blue()),
+        channel(pixel.alpha(), color.alpha()),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn glyphs_in_rows() {
+        let atlas: Vec<Pixel> = (0..12).map(|index| Pixel::new(index, 0, 0, 0xFF)).collect();
+        let glyph_extent = Extent { width: 2, height: 1 };
+        let font = BitmapFont::new(&atlas, Extent { width: 4, height: 3 }, glyph_extent, 'a');
+        assert_eq!(font.glyph_count(), 6);
+
+        assert_eq!(font.glyph_pixel('a', 1, 0), Some(atlas[1]));
+        assert_eq!(font.glyph_pixel('b', 0, 0), Some(atlas[2]));
+        assert_eq!(font.glyph_pixel('c', 1, 0), Some(atlas[5]));
+        assert_eq!(font.glyph_pixel('f', 1, 0), Some(atlas[11]));
+        assert_eq!(font.glyph_pixel('g', 0, 0), None);
+        assert_eq!(font.glyph_pixel('A', 0, 0), None);
+        assert!(font.has_glyph('f') && !font.has_glyph('g'));
+    }
+
+    #[test]
+    fn tint_multiplies_channels() {
+        assert_eq!(tint(Pixel::WHITE, Pixel::new(0x12, 0x34, 0x56, 0x78)), Pixel::new(0x12, 0x34, 0x56, 0x78));
+        assert_eq!(tint(Pixel::new(0x80, 0xFF, 0x00, 0x80), Pixel::WHITE), Pixel::new(0x80, 0xFF, 0x00, 0x80));
+        assert_eq!(tint(Pixel::new(0x80, 0x80, 0x80, 0xFF), Pixel::new(0x80, 0, 0, 0xFF)),
+            Pixel::new(0x40, 0, 0, 0xFF));
+    }
+}
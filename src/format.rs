@@ -0,0 +1,172 @@
+// Copyright (C) 2023 Tristan Gerritsen <tristan@thewoosh.org>
+// All Rights Reserved.
+
+use std::{borrow::Cow, fmt::Debug};
+
+use crate::Pixel;
+
+/// The way a single pixel is stored in the buffer of a swap chain. Shading
+/// and blending happen on [`Pixel`]s, which are converted to and from the
+/// format when they're written to the buffer.
+pub trait PixelFormat: Copy + Debug + PartialEq + Send + Sync + 'static {
+
+    /// Converts the canonical 8-bit RGBA color to this format, losing the
+    /// precision the format can't represent.
+    fn from_pixel(pixel: Pixel) -> Self;
+
+    /// Converts the value to the canonical 8-bit RGBA color.
+    fn to_pixel(self) -> Pixel;
+
+    /// Fills the buffer with the given value.
+    fn fill(buffer: &mut [Self], value: Self) {
+        buffer.fill(value);
+    }
+
+    /// Converts the buffer to 8-bit RGBA, which is what surfaces are able to
+    /// present.
+    fn to_pixels(buffer: &[Self]) -> Cow<'_, [Pixel]> {
+        Cow::Owned(buffer.iter().map(|value| value.to_pixel()).collect())
+    }
+
+}
+
+impl PixelFormat for Pixel {
+
+    #[inline]
+    fn from_pixel(pixel: Pixel) -> Self {
+        pixel
+    }
+
+    #[inline]
+    fn to_pixel(self) -> Pixel {
+        self
+    }
+
+    fn fill(buffer: &mut [Self], value: Self) {
+        fill_pixels(buffer, value);
+    }
+
+    fn to_pixels(buffer: &[Self]) -> Cow<'_, [Pixel]> {
+        Cow::Borrowed(buffer)
+    }
+
+}
+
+/// A 16-bit format with 5 bits for red, 6 bits for green and 5 bits for blue,
+/// from the most to the least significant bit. There is no alpha channel, so
+/// every pixel is opaque.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Rgb565(pub u16);
+
+/// Scales the 8-bit channel down to the given number of bits, rounding to the
+/// nearest value.
+#[inline]
+fn quantize(value: u8, bits: u32) -> u16 {
+    let max = (1 << bits) - 1;
+    ((value as u32 * max + 127) / 255) as u16
+}
+
+/// Scales the channel of the given number of bits up to 8 bits, rounding to
+/// the nearest value.
+#[inline]
+fn expand(value: u16, bits: u32) -> u8 {
+    let max = (1 << bits) - 1;
+    ((value as u32 * 255 + max / 2) / max) as u8
+}
+
+impl PixelFormat for Rgb565 {
+
+    fn from_pixel(pixel: Pixel) -> Self {
+        Self(quantize(pixel.red(), 5) << 11 | quantize(pixel.green(), 6) << 5 | quantize(pixel.blue(), 5))
+    }
+
+    fn to_pixel(self) -> Pixel {
+        Pixel::new(
+            expand(self.0 >> 11, 5),
+            expand((self.0 >> 5) & 0x3F, 6),
+            expand(self.0 & 0x1F, 5),
+            0xFF,
+        )
+    }
+
+}
+
+/// Fills the buffer with the given color. Since a [`Pixel`] is exactly four
+/// bytes, the bulk of the buffer is filled as `u32` words, which compiles to
+/// wide stores.
+fn fill_pixels(buffer: &mut [Pixel], color: Pixel) {
+    // SAFETY: a Pixel consists of four u8 channels without padding, so every
+    // u32 is a valid pixel and vice versa. `align_to_mut` takes care of the
+    // alignment, since Pixel is packed while u32 isn't.
+    let (prefix, words, suffix) = unsafe { buffer.align_to_mut::<u32>() };
+
+    // Reinterpreting the pixel as a native-endian u32 keeps the byte order of
+    // the channels in memory, and thus the order in which they're presented.
+    let pattern = u32::from_ne_bytes([color.red(), color.green(), color.blue(), color.alpha()]);
+
+    prefix.fill(color);
+    words.fill(pattern);
+    suffix.fill(color);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fill_pixels_matches_naive_fill() {
+        let color = Pixel::new(0x12, 0x34, 0x56, 0x78);
+        let mut buffer = vec![Pixel::BLACK; 67];
+
+        // Slicing at different offsets makes sure the unaligned parts at the
+        // start and end are filled as well.
+        for start in 0..4 {
+            for end in 60..67 {
+                buffer.fill(Pixel::BLACK);
+                fill_pixels(&mut buffer[start..end], color);
+
+                let mut expected = vec![Pixel::BLACK; 67];
+                expected[start..end].fill(color);
+                assert_eq!(buffer, expected);
+            }
+        }
+    }
+
+    #[test]
+    fn rgb565_primaries() {
+        assert_eq!(Rgb565::from_pixel(Pixel::RED), Rgb565(0xF800));
+        assert_eq!(Rgb565::from_pixel(Pixel::GREEN), Rgb565(0x07E0));
+        assert_eq!(Rgb565::from_pixel(Pixel::BLUE), Rgb565(0x001F));
+        assert_eq!(Rgb565::from_pixel(Pixel::WHITE), Rgb565(0xFFFF));
+        assert_eq!(Rgb565::from_pixel(Pixel::BLACK), Rgb565(0x0000));
+
+        assert_eq!(Rgb565(0xF800).to_pixel(), Pixel::RED);
+        assert_eq!(Rgb565(0xFFFF).to_pixel(), Pixel::WHITE);
+    }
+
+    #[test]
+    fn rgb565_round_trip_loses_precision() {
+        let pixel = Pixel::new(0x12, 0x34, 0x56, 0x78);
+        assert_eq!(Rgb565::from_pixel(pixel).to_pixel(), Pixel::new(0x10, 0x35, 0x52, 0xFF));
+
+        // Red and blue are off by at most half a step of 255 / 31, and green
+        // by at most half a step of 255 / 63.
+        for value in 0..=0xFF {
+            let round_trip = Rgb565::from_pixel(Pixel::new(value, value, value, value)).to_pixel();
+            assert!(round_trip.red().abs_diff(value) <= 4, "{value}");
+            assert!(round_trip.green().abs_diff(value) <= 2, "{value}");
+            assert!(round_trip.blue().abs_diff(value) <= 4, "{value}");
+            assert_eq!(round_trip.alpha(), 0xFF);
+
+            // Converting back is stable, since the value is representable.
+            assert_eq!(Rgb565::from_pixel(round_trip).to_pixel(), round_trip);
+        }
+    }
+
+    #[test]
+    fn pixel_is_borrowed_for_presenting() {
+        let buffer = [Pixel::RED, Pixel::GREEN];
+        assert!(matches!(Pixel::to_pixels(&buffer), Cow::Borrowed(_)));
+        assert_eq!(Rgb565::to_pixels(&[Rgb565(0x001F)]).as_ref(), &[Pixel::BLUE]);
+    }
+}
@@ -0,0 +1,227 @@
+// Copyright (C) 2023 Tristan Gerritsen <tristan@thewoosh.org>
+// All Rights Reserved.
+
+use crate::{
+    math::Vector2f,
+    shader::{Varyings, MAX_VARYINGS},
+};
+
+/// A value that varies linearly across a triangle, described by its value at
+/// the first vertex and how much it changes per pixel in either direction.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+struct Plane {
+    value: f32,
+    step_x: f32,
+    step_y: f32,
+}
+
+impl Plane {
+
+    /// Creates the plane through the values at the three vertices, given the
+    /// offsets of the second and third vertex to the first one, and the
+    /// reciprocal of the determinant of those offsets.
+    fn new(values: [f32; 3], offset_b: Vector2f, offset_c: Vector2f, inverse_determinant: f32) -> Self {
+        let delta_b = values[1] - values[0];
+        let delta_c = values[2] - values[0];
+        Self {
+            value: values[0],
+            step_x: (delta_b * offset_c.y - delta_c * offset_b.y) * inverse_determinant,
+            step_y: (delta_c * offset_b.x - delta_b * offset_c.x) * inverse_determinant,
+        }
+    }
+
+    /// Creates the plane that has the same value everywhere.
+    fn constant(value: f32) -> Self {
+        Self {
+            value,
+            step_x: 0.0,
+            step_y: 0.0,
+        }
+    }
+
+    #[inline]
+    fn evaluate(&self, offset: Vector2f) -> f32 {
+        self.value + self.step_x * offset.x + self.step_y * offset.y
+    }
+
+}
+
+/// Interpolates the depth and varyings of the vertices of a triangle for
+/// the pixels inside of it.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct Interpolator {
+    origin: Vector2f,
+    depth: Plane,
+    varyings: [Plane; MAX_VARYINGS],
+
+    /// The reciprocal of `w`, by which the varyings are divided when they are
+    /// interpolated perspective-correctly.
+    inverse_w: Option<Plane>,
+}
+
+impl Interpolator {
+
+    /// Creates the interpolator for the triangle with the vertices at the
+    /// given positions in pixels. When the triangle has no area, the values
+    /// of the first vertex are used everywhere.
+    pub(crate) fn new(positions: [Vector2f; 3], depths: [f32; 3], varyings: [&Varyings; 3]) -> Self {
+        let plane = planes(positions);
+        Self {
+            origin: positions[0],
+            depth: plane(depths),
+            varyings: std::array::from_fn(|index| plane(varyings.map(|varyings| varyings[index]))),
+            inverse_w: None,
+        }
+    }
+
+    /// Creates the interpolator just like [`Interpolator::new`], but of which
+    /// the varyings are interpolated perspective-correctly, given the
+    /// reciprocals of the `w` coordinates of the vertices in clip space.
+    pub(crate) fn perspective_correct(positions: [Vector2f; 3], depths: [f32; 3], inverse_w: [f32; 3],
+            varyings: [&Varyings; 3]) -> Self {
+        let plane = planes(positions);
+        Self {
+            origin: positions[0],
+            depth: plane(depths),
+            varyings: std::array::from_fn(|index| {
+                plane(std::array::from_fn(|vertex| varyings[vertex][index] * inverse_w[vertex]))
+            }),
+            inverse_w: Some(plane(inverse_w)),
+        }
+    }
+
+    /// Replaces the varyings by the given values, which are used for every
+    /// pixel of the triangle. The depth is still interpolated.
+    pub(crate) fn with_flat_varyings(mut self, varyings: &Varyings) -> Self {
+        self.varyings = varyings.map(Plane::constant);
+        self.inverse_w = None;
+        self
+    }
+
+    /// Interpolates the depth at the given position.
+    pub(crate) fn depth(&self, position: Vector2f) -> f32 {
+        self.depth.evaluate(self.offset(position))
+    }
+
+    /// Interpolates the varying with the given index at the given position.
+    pub(crate) fn varying(&self, index: usize, position: Vector2f) -> f32 {
+        let offset = self.offset(position);
+        match &self.inverse_w {
+            Some(inverse_w) => self.varyings[index].evaluate(offset) / inverse_w.evaluate(offset),
+            None => self.varyings[index].evaluate(offset),
+        }
+    }
+
+    /// Computes how much the varying with the given index changes from the
+    /// position to the next pixel in the `x` and `y` direction, which is the
+    /// same everywhere unless it's interpolated perspective-correctly.
+    pub(crate) fn varying_derivatives(&self, index: usize, position: Vector2f) -> (f32, f32) {
+        let value = self.varying(index, position);
+        (
+            self.varying(index, Vector2f::new(position.x + 1.0, position.y)) - value,
+            self.varying(index, Vector2f::new(position.x, position.y + 1.0)) - value,
+        )
+    }
+
+    #[inline]
+    fn offset(&self, position: Vector2f) -> Vector2f {
+        Vector2f::new(position.x - self.origin.x, position.y - self.origin.y)
+    }
+
+}
+
+/// Sets up the planes through the values at the vertices at the given
+/// positions, relative to the first one.
+fn planes(positions: [Vector2f; 3]) -> impl Fn([f32; 3]) -> Plane {
+    let origin = positions[0];
+    let offset_b = Vector2f::new(positions[1].x - origin.x, positions[1].y - origin.y);
+    let offset_c = Vector2f::new(positions[2].x - origin.x, positions[2].y - origin.y);
+
+    let determinant = offset_b.x * offset_c.y - offset_c.x * offset_b.y;
+    let inverse_determinant = if determinant == 0.0 { 0.0 } else { 1.0 / determinant };
+    move |values| Plane::new(values, offset_b, offset_c, inverse_determinant)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn interpolates_at_vertices_and_between_them() {
+        let positions = [Vector2f::new(1.0, 1.0), Vector2f::new(9.0, 1.0), Vector2f::new(1.0, 5.0)];
+        let mut varyings = [[0.0; MAX_VARYINGS]; 3];
+        varyings[1][0] = 8.0;
+        varyings[2][3] = -4.0;
+
+        let varyings_per_vertex = [&varyings[0], &varyings[1], &varyings[2]];
+        let interpolator = Interpolator::new(positions, [0.0, 0.5, 1.0], varyings_per_vertex);
+
+        for (position, varyings) in positions.iter().zip(&varyings) {
+            assert_eq!(interpolator.varying(0, *position), varyings[0]);
+            assert_eq!(interpolator.varying(3, *position), varyings[3]);
+        }
+
+        let middle = Vector2f::new(5.0, 3.0);
+        assert_eq!(interpolator.varying(0, middle), 4.0);
+        assert_eq!(interpolator.varying(3, middle), -2.0);
+        assert_eq!(interpolator.depth(middle), 0.75);
+        assert_eq!(interpolator.varying(7, middle), 0.0);
+    }
+
+    #[test]
+    fn varying_derivatives_per_pixel() {
+        let positions = [Vector2f::new(0.0, 0.0), Vector2f::new(8.0, 0.0), Vector2f::new(0.0, 4.0)];
+        let mut varyings = [[0.0; MAX_VARYINGS]; 3];
+        varyings[1][0] = 2.0;
+        varyings[2][0] = -1.0;
+        let varyings_per_vertex = [&varyings[0], &varyings[1], &varyings[2]];
+
+        let interpolator = Interpolator::new(positions, [0.0; 3], varyings_per_vertex);
+        assert_eq!(interpolator.varying_derivatives(0, Vector2f::new(2.0, 1.0)), (0.25, -0.25));
+        assert_eq!(interpolator.varying_derivatives(1, Vector2f::new(2.0, 1.0)), (0.0, 0.0));
+    }
+
+    #[test]
+    fn degenerate_triangle_uses_first_vertex() {
+        let positions = [Vector2f::new(0.0, 0.0), Vector2f::new(2.0, 2.0), Vector2f::new(4.0, 4.0)];
+        let varyings = [[1.0; MAX_VARYINGS], [2.0; MAX_VARYINGS], [3.0; MAX_VARYINGS]];
+        let varyings_per_vertex = [&varyings[0], &varyings[1], &varyings[2]];
+        let interpolator = Interpolator::new(positions, [0.1, 0.2, 0.3], varyings_per_vertex);
+        assert_eq!(interpolator.varying(2, Vector2f::new(2.0, 2.0)), 1.0);
+        assert_eq!(interpolator.depth(Vector2f::new(4.0, 4.0)), 0.1);
+    }
+
+    #[test]
+    fn perspective_correct_varyings() {
+        let positions = [Vector2f::new(0.0, 0.0), Vector2f::new(4.0, 0.0), Vector2f::new(0.0, 4.0)];
+        let mut varyings = [[0.0; MAX_VARYINGS]; 3];
+        varyings[1][0] = 1.0;
+        let varyings_per_vertex = [&varyings[0], &varyings[1], &varyings[2]];
+
+        // The second vertex is three times as far away as the others.
+        let inverse_w = [1.0, 1.0 / 3.0, 1.0];
+        let interpolator = Interpolator::perspective_correct(positions, [0.0; 3], inverse_w,
+            varyings_per_vertex);
+        for (position, varyings) in positions.iter().zip(&varyings) {
+            assert!((interpolator.varying(0, *position) - varyings[0]).abs() < 1e-6);
+        }
+        assert!((interpolator.varying(0, Vector2f::new(2.0, 0.0)) - 0.25).abs() < 1e-6);
+
+        let affine = Interpolator::new(positions, [0.0; 3], varyings_per_vertex);
+        assert_eq!(affine.varying(0, Vector2f::new(2.0, 0.0)), 0.5);
+    }
+
+    #[test]
+    fn flat_varyings_are_constant() {
+        let positions = [Vector2f::new(0.0, 0.0), Vector2f::new(4.0, 0.0), Vector2f::new(0.0, 4.0)];
+        let varyings = [[0.0; MAX_VARYINGS], [4.0; MAX_VARYINGS], [8.0; MAX_VARYINGS]];
+        let varyings_per_vertex = [&varyings[0], &varyings[1], &varyings[2]];
+        let interpolator = Interpolator::new(positions, [0.0, 1.0, 0.0], varyings_per_vertex)
+            .with_flat_varyings(&varyings[2]);
+
+        for position in positions {
+            assert_eq!(interpolator.varying(5, position), 8.0);
+        }
+        assert_eq!(interpolator.depth(Vector2f::new(2.0, 0.0)), 0.5);
+    }
+}
@@ -1,38 +1,75 @@
 // Copyright (C) 2023 Tristan Gerritsen <tristan@thewoosh.org>
 // All Rights Reserved.
 
+//! Without the `std` feature, which is enabled by default, the crate is
+//! `no_std`, and only the [`math`] module and [`TriangleVertices`] are
+//! available, e.g. to reuse the geometry on microcontrollers. The functions
+//! that need square roots or trigonometry, such as [`Vector2f::distance`] and
+//! [`math::Matrix4::perspective`], still need the feature. The `no_std` job
+//! of the CI workflow builds the crate without it for
+//! `thumbv7em-none-eabihf`, which has no standard library at all, and runs
+//! the tests of the geometry with `cargo test --no-default-features`.
+//!
+//! The `winit` feature, which is enabled by default as well, adds the APIs
+//! that take winit's windows and logical sizes, such as [`swap_chain::SwapChain::new`].
+//! Without it, swap chains are created from an [`swap_chain::Extent`] in
+//! physical pixels, which is enough to render headless to a
+//! [`platform::MemorySurface`]. The default `win32` feature adds the surface
+//! that presents to Win32 windows, which is the only part that needs the
+//! `windows` crate. The headless job of the CI workflow checks that the
+//! crate passes its tests with `cargo test --no-default-features --features
+//! std`, without pulling in any of these crates.
+
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
+
 use math::Vector2f;
 
+#[cfg(feature = "std")]
+pub mod batch;
+#[cfg(feature = "std")]
+pub mod blend;
+#[cfg(feature = "std")]
+pub mod camera;
+#[cfg(feature = "std")]
+mod error;
+#[cfg(feature = "std")]
+pub mod filter;
+#[cfg(feature = "std")]
+pub mod font;
+#[cfg(feature = "std")]
+pub mod format;
+#[cfg(feature = "std")]
+mod interpolation;
 pub mod math;
+#[cfg(feature = "std")]
+pub mod multisample;
+#[cfg(feature = "std")]
+pub mod pixel;
+#[cfg(feature = "std")]
 pub mod platform;
+#[cfg(feature = "std")]
+pub mod raytrace;
+#[cfg(feature = "std")]
 pub mod shader;
+#[cfg(feature = "std")]
+pub mod stencil;
+#[cfg(feature = "std")]
 pub mod swap_chain;
+#[cfg(feature = "std")]
+pub mod texture;
+#[cfg(feature = "std")]
+pub mod tiled;
+#[cfg(feature = "std")]
+pub mod tonemap;
 
-#[repr(C, packed)]
-#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
-pub struct Pixel {
-    red: u8,
-    green: u8,
-    blue: u8,
-    alpha: u8,
-}
-
-impl Pixel {
-
-    pub const fn new(red: u8, green: u8, blue: u8, alpha: u8) -> Self {
-        Self { alpha, red, green, blue }
-    }
-
-    /// The color black.
-    pub const BLACK: Self = Pixel::new(0x00, 0x00, 0x00, 0xF);
-
-    /// The color red.
-    pub const RED: Self = Pixel::new(0xFF, 0xFF, 0xFF, 0xFF);
-
-}
+#[cfg(feature = "std")]
+pub use error::Error;
+#[cfg(feature = "std")]
+pub use pixel::Pixel;
 
-#[repr(packed)]
+#[repr(C, packed)]
 #[derive(Copy, Clone, Debug, PartialEq, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct TriangleVertices {
     pub a: Vector2f,
     pub b: Vector2f,
@@ -43,4 +80,60 @@ impl TriangleVertices {
     pub fn new(a: Vector2f, b: Vector2f, c: Vector2f) -> Self {
         Self { a, b, c }
     }
+
+    /// Creates the triangle from its three vertices.
+    pub fn from_array([a, b, c]: [Vector2f; 3]) -> Self {
+        Self { a, b, c }
+    }
+
+    /// Groups every three consecutive points into a triangle. When the amount
+    /// of points isn't a multiple of three, the remaining one or two points
+    /// are dropped.
+    pub fn chunks_from(points: &[Vector2f]) -> impl Iterator<Item = TriangleVertices> + '_ {
+        points.chunks_exact(3).map(|chunk| Self::new(chunk[0], chunk[1], chunk[2]))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn triangle_vertices_from_points() {
+        let points: Vec<Vector2f> = (0..8).map(|index| Vector2f::new(index as f32, -index as f32)).collect();
+        let triangle = |first: usize| {
+            TriangleVertices::from_array([points[first], points[first + 1], points[first + 2]])
+        };
+
+        let triangles: Vec<_> = TriangleVertices::chunks_from(&points[..6]).collect();
+        assert_eq!(triangles, [triangle(0), triangle(3)]);
+        assert_eq!(triangles[1], TriangleVertices::new(points[3], points[4], points[5]));
+
+        // The remaining points are dropped.
+        assert_eq!(TriangleVertices::chunks_from(&points[..7]).collect::<Vec<_>>(), triangles);
+        assert_eq!(TriangleVertices::chunks_from(&points).collect::<Vec<_>>(), triangles);
+        assert_eq!(TriangleVertices::chunks_from(&points[..2]).count(), 0);
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn triangle_vertices_serde_round_trip() {
+        let vertices = TriangleVertices::new(
+            Vector2f::new(-1.0, 0.5),
+            Vector2f::new(0.25, -0.75),
+            Vector2f::new(1.0, 1.0),
+        );
+
+        let json = serde_json::to_string(&vertices).unwrap();
+        assert_eq!(json, r#"{"a":{"x":-1.0,"y":0.5},"b":{"x":0.25,"y":-0.75},"c":{"x":1.0,"y":1.0}}"#);
+        assert_eq!(serde_json::from_str::<TriangleVertices>(&json).unwrap(), vertices);
+    }
+
+    #[test]
+    #[cfg(all(feature = "serde", feature = "std"))]
+    fn pixel_serde_round_trip() {
+        let pixel = Pixel::new(0x12, 0x34, 0x56, 0x78);
+        let json = serde_json::to_string(&pixel).unwrap();
+        assert_eq!(serde_json::from_str::<Pixel>(&json).unwrap(), pixel);
+    }
 }
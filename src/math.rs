@@ -1,9 +1,13 @@
 // Copyright (C) 2023 Tristan Gerritsen <tristan@thewoosh.org>
 // All Rights Reserved.
 
-use std::ops::{Mul, Range};
+use core::{
+    fmt,
+    ops::{Add, Index, IndexMut, Mul, Range, Sub},
+};
 
-use num_traits::{Num, Signed};
+use arrayvec::ArrayVec;
+use num_traits::{float::FloatCore, Num, Signed};
 
 pub fn min<T>(lhs: T, rhs: T) -> T
         where T: Copy + PartialOrd {
@@ -26,6 +30,7 @@ pub fn max<T>(lhs: T, rhs: T) -> T
 // Can't do packing, see https://github.com/rust-lang/rust/issues/82523 :(
 // #[repr(packed)]
 #[derive(Copy, Clone, Default, Debug, PartialEq, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Vector2<T> where T: Num + Copy {
     pub x: T,
     pub y: T,
@@ -37,10 +42,233 @@ impl<T> Vector2<T> where T: Num + Copy {
     pub fn new(x: T, y: T) -> Self {
         Self { x, y }
     }
+
+    /// Gets the components as an array, `[x, y]`.
+    pub fn as_array(&self) -> [T; 2] {
+        [self.x, self.y]
+    }
+
+    /// The scalar cross product of the two vectors, i.e. the z component of
+    /// the cross product of the vectors extended with a z of zero. It is
+    /// positive when `other` is counter-clockwise from this vector, given
+    /// that `y` goes up, negative when it's clockwise, and zero when the
+    /// vectors are parallel.
+    pub fn cross(&self, other: Self) -> T {
+        self.x * other.y - self.y * other.x
+    }
+}
+
+impl<T> Vector2<T> where T: Num + Copy + PartialOrd {
+
+    /// Clamps every component between the corresponding components of `lo`
+    /// and `hi`. When a component of `lo` is larger than that of `hi`, the
+    /// latter wins.
+    pub fn clamp(self, lo: Self, hi: Self) -> Self {
+        self.max(lo).min(hi)
+    }
+
+    /// Takes the largest of every component.
+    pub fn max(self, other: Self) -> Self {
+        Self::new(max(self.x, other.x), max(self.y, other.y))
+    }
+
+    /// Takes the smallest of every component.
+    pub fn min(self, other: Self) -> Self {
+        Self::new(min(self.x, other.x), min(self.y, other.y))
+    }
+
+}
+
+impl<T> Vector2<T> where T: Num + Signed + Copy {
+
+    /// Takes the absolute value of every component.
+    pub fn abs(self) -> Self {
+        Self::new(self.x.abs(), self.y.abs())
+    }
+
+}
+
+impl<T> From<(T, T)> for Vector2<T> where T: Num + Copy {
+    fn from((x, y): (T, T)) -> Self {
+        Self { x, y }
+    }
+}
+
+impl<T> From<[T; 2]> for Vector2<T> where T: Num + Copy {
+    fn from([x, y]: [T; 2]) -> Self {
+        Self { x, y }
+    }
+}
+
+impl<T> From<Vector2<T>> for (T, T) where T: Num + Copy {
+    fn from(vector: Vector2<T>) -> Self {
+        (vector.x, vector.y)
+    }
+}
+
+impl<T> From<Vector2<T>> for [T; 2] where T: Num + Copy {
+    fn from(vector: Vector2<T>) -> Self {
+        [vector.x, vector.y]
+    }
+}
+
+/// Formats the vector as `(x, y)`.
+impl<T> fmt::Display for Vector2<T> where T: Num + Copy + fmt::Display {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "({}, {})", self.x, self.y)
+    }
+}
+
+/// Gets the component with the index, where `x` is 0 and `y` is 1.
+///
+/// # Panics
+/// Panics when the index isn't below 2.
+impl<T> Index<usize> for Vector2<T> where T: Num + Copy {
+    type Output = T;
+
+    fn index(&self, index: usize) -> &T {
+        match index {
+            0 => &self.x,
+            1 => &self.y,
+            _ => panic!("index {index} is out of range for Vector2 with 2 components"),
+        }
+    }
+}
+
+impl<T> IndexMut<usize> for Vector2<T> where T: Num + Copy {
+    fn index_mut(&mut self, index: usize) -> &mut T {
+        match index {
+            0 => &mut self.x,
+            1 => &mut self.y,
+            _ => panic!("index {index} is out of range for Vector2 with 2 components"),
+        }
+    }
+}
+
+/// The way a coordinate is rounded to an integer.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Rounding {
+
+    /// Round to the nearest integer, and halfway cases away from zero.
+    #[default]
+    Nearest,
+
+    /// Round towards negative infinity.
+    Floor,
+
+    /// Round towards positive infinity.
+    Ceil,
+
+    /// Round towards zero, dropping the fractional part.
+    Truncate,
+
+}
+
+impl Rounding {
+
+    /// Rounds the value to an integer, saturating at the bounds of `i32`. NaN
+    /// becomes zero.
+    pub fn apply(&self, value: f32) -> i32 {
+        let value = match self {
+            Rounding::Nearest => FloatCore::round(value),
+            Rounding::Floor => FloatCore::floor(value),
+            Rounding::Ceil => FloatCore::ceil(value),
+            Rounding::Truncate => FloatCore::trunc(value),
+        };
+        value as i32
+    }
+
+}
+
+impl Vector2f {
+    /// The angle of the vector from the positive X axis in radians, between
+    /// `-π` and `π`, increasing counter-clockwise.
+    #[cfg(feature = "std")]
+    pub fn angle(&self) -> f32 {
+        self.y.atan2(self.x)
+    }
+
+    /// Checks whether both coordinates differ at most `epsilon` from those of
+    /// the other vector, to compare the results of float arithmetic.
+    pub fn approx_eq(&self, other: Vector2f, epsilon: f32) -> bool {
+        (self.x - other.x).abs() <= epsilon && (self.y - other.y).abs() <= epsilon
+    }
+
+    /// Rounds both coordinates towards positive infinity.
+    pub fn ceil_to_i32(&self) -> Vector2<i32> {
+        self.to_i32(Rounding::Ceil)
+    }
+
+    /// The distance between the points `a` and `b`.
+    #[cfg(feature = "std")]
+    pub fn distance(a: Vector2f, b: Vector2f) -> f32 {
+        Vector2f::distance_squared(a, b).sqrt()
+    }
+
+    /// The square of the distance between the points `a` and `b`, which is
+    /// cheaper to compute when only comparing distances.
+    pub fn distance_squared(a: Vector2f, b: Vector2f) -> f32 {
+        let offset = Vector2f::new(b.x - a.x, b.y - a.y);
+        offset.dot(offset)
+    }
+
+    /// The dot product of the two vectors.
+    pub fn dot(&self, other: Vector2f) -> f32 {
+        self.x * other.x + self.y * other.y
+    }
+
+    /// Rounds both coordinates towards negative infinity.
+    pub fn floor_to_i32(&self) -> Vector2<i32> {
+        self.to_i32(Rounding::Floor)
+    }
+
+    /// Creates the unit vector pointing in the direction of the angle, in
+    /// radians, counter-clockwise from the positive X axis.
+    #[cfg(feature = "std")]
+    pub fn from_angle(radians: f32) -> Vector2f {
+        let (sin, cos) = radians.sin_cos();
+        Vector2f::new(cos, sin)
+    }
+
+    /// Linearly interpolates between `a` and `b`, where `t = 0` yields `a`
+    /// and `t = 1` yields `b`. `t` isn't clamped, so it can extrapolate.
+    pub fn lerp(a: Vector2f, b: Vector2f, t: f32) -> Vector2f {
+        Vector2f::new(a.x + (b.x - a.x) * t, a.y + (b.y - a.y) * t)
+    }
+
+    /// Reflects the vector off a surface with the given normal, which must be
+    /// of unit length.
+    pub fn reflect(self, normal: Vector2f) -> Vector2f {
+        let scale = 2.0 * self.dot(normal);
+        Vector2f::new(self.x - scale * normal.x, self.y - scale * normal.y)
+    }
+
+    /// Rotates the vector counter-clockwise by the angle in radians.
+    #[cfg(feature = "std")]
+    pub fn rotated(self, radians: f32) -> Vector2f {
+        let (sin, cos) = radians.sin_cos();
+        Vector2f::new(self.x * cos - self.y * sin, self.x * sin + self.y * cos)
+    }
+
+    /// Rounds both coordinates to the nearest integer.
+    pub fn round_to_i32(&self) -> Vector2<i32> {
+        self.to_i32(Rounding::Nearest)
+    }
+
+    /// Rounds both coordinates to integers in the given way, saturating at
+    /// the bounds of `i32`.
+    pub fn to_i32(&self, rounding: Rounding) -> Vector2<i32> {
+        Vector2::new(rounding.apply(self.x), rounding.apply(self.y))
+    }
+
+    /// Rounds both coordinates towards zero.
+    pub fn trunc_to_i32(&self) -> Vector2<i32> {
+        self.to_i32(Rounding::Truncate)
+    }
 }
 
-#[repr(packed)]
 #[derive(Copy, Clone, Default, Debug, PartialEq, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Vector3 {
     pub x: f32,
     pub y: f32,
@@ -49,6 +277,15 @@ pub struct Vector3 {
 
 impl Vector3 {
 
+    pub fn new(x: f32, y: f32, z: f32) -> Self {
+        Self { x, y, z }
+    }
+
+    /// Gets the components as an array, `[x, y, z]`.
+    pub fn as_array(&self) -> [f32; 3] {
+        [self.x, self.y, self.z]
+    }
+
     /// The cross product of the two vectors.
     pub fn cross(&self, other: Vector3) -> Vector3 {
         Vector3 {
@@ -58,6 +295,35 @@ impl Vector3 {
         }
     }
 
+    /// The dot product of the two vectors.
+    pub fn dot(&self, other: Vector3) -> f32 {
+        self.x * other.x + self.y * other.y + self.z * other.z
+    }
+
+    /// The length of the vector.
+    #[cfg(feature = "std")]
+    pub fn length(&self) -> f32 {
+        self.dot(*self).sqrt()
+    }
+
+    /// Linearly interpolates between `a` and `b`, where `t = 0` yields `a`
+    /// and `t = 1` yields `b`. `t` isn't clamped, so it can extrapolate.
+    pub fn lerp(a: Vector3, b: Vector3, t: f32) -> Vector3 {
+        Vector3 {
+            x: a.x + (b.x - a.x) * t,
+            y: a.y + (b.y - a.y) * t,
+            z: a.z + (b.z - a.z) * t,
+        }
+    }
+
+    /// Scales the vector to unit length. The zero vector has no direction,
+    /// so it yields NaN components.
+    #[cfg(feature = "std")]
+    pub fn normalized(&self) -> Vector3 {
+        let length = self.length();
+        Vector3::new(self.x / length, self.y / length, self.z / length)
+    }
+
     /// Sizzle the x and y components off this 3D vector.
     pub fn xy(&self) -> Vector2f {
         Vector2 { x: self.x, y: self.y }
@@ -65,6 +331,65 @@ impl Vector3 {
 
 }
 
+impl From<(f32, f32, f32)> for Vector3 {
+    fn from((x, y, z): (f32, f32, f32)) -> Self {
+        Self { x, y, z }
+    }
+}
+
+impl From<[f32; 3]> for Vector3 {
+    fn from([x, y, z]: [f32; 3]) -> Self {
+        Self { x, y, z }
+    }
+}
+
+impl From<Vector3> for (f32, f32, f32) {
+    fn from(vector: Vector3) -> Self {
+        (vector.x, vector.y, vector.z)
+    }
+}
+
+impl From<Vector3> for [f32; 3] {
+    fn from(vector: Vector3) -> Self {
+        [vector.x, vector.y, vector.z]
+    }
+}
+
+/// Formats the vector as `(x, y, z)`.
+impl fmt::Display for Vector3 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "({}, {}, {})", self.x, self.y, self.z)
+    }
+}
+
+/// Gets the component with the index, where `x` is 0, `y` is 1 and `z` is 2.
+///
+/// # Panics
+/// Panics when the index isn't below 3.
+impl Index<usize> for Vector3 {
+    type Output = f32;
+
+    fn index(&self, index: usize) -> &f32 {
+        match index {
+            0 => &self.x,
+            1 => &self.y,
+            2 => &self.z,
+            _ => panic!("index {index} is out of range for Vector3 with 3 components"),
+        }
+    }
+}
+
+impl IndexMut<usize> for Vector3 {
+    fn index_mut(&mut self, index: usize) -> &mut f32 {
+        match index {
+            0 => &mut self.x,
+            1 => &mut self.y,
+            2 => &mut self.z,
+            _ => panic!("index {index} is out of range for Vector3 with 3 components"),
+        }
+    }
+}
+
 /// The cross product of the two vectors.
 impl Mul<Vector3> for Vector3 {
     type Output = Vector3;
@@ -75,106 +400,1615 @@ impl Mul<Vector3> for Vector3 {
     }
 }
 
-pub struct Rectangle2D<T> where T: Num + Copy {
-    lefttopmost: Vector2<T>,
-    rightbottommost: Vector2<T>,
+impl Add<Vector3> for Vector3 {
+    type Output = Vector3;
+
+    fn add(self, rhs: Vector3) -> Self::Output {
+        Vector3::new(self.x + rhs.x, self.y + rhs.y, self.z + rhs.z)
+    }
 }
 
-impl<T> Rectangle2D<T> where T: Num + Copy {
-    /// Get the X coordinate
-    pub fn x(&self) -> T {
-        self.lefttopmost.x
+impl Sub<Vector3> for Vector3 {
+    type Output = Vector3;
+
+    fn sub(self, rhs: Vector3) -> Self::Output {
+        Vector3::new(self.x - rhs.x, self.y - rhs.y, self.z - rhs.z)
     }
+}
 
-    /// Get the range of the X coordinate
-    pub fn x_range(&self) -> Range<T> {
-        self.lefttopmost.x..self.rightbottommost.x
+/// A four-dimensional vector, mostly used for homogeneous clip-space
+/// coordinates.
+#[derive(Copy, Clone, Default, Debug, PartialEq, PartialOrd)]
+pub struct Vector4 {
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+    pub w: f32,
+}
+
+impl Vector4 {
+
+    pub fn new(x: f32, y: f32, z: f32, w: f32) -> Self {
+        Self { x, y, z, w }
     }
 
-    /// Get the Y coordinate
-    pub fn y(&self) -> T {
-        self.lefttopmost.y
+    /// Gets the components as an array, `[x, y, z, w]`.
+    pub fn as_array(&self) -> [f32; 4] {
+        [self.x, self.y, self.z, self.w]
     }
 
-    /// Get the range of the Y coordinate
-    pub fn y_range(&self) -> Range<T> {
-        self.lefttopmost.y..self.rightbottommost.y
+    /// Linearly interpolates between `a` and `b`, where `t = 0` yields `a`
+    /// and `t = 1` yields `b`. `t` isn't clamped, so it can extrapolate.
+    pub fn lerp(a: Vector4, b: Vector4, t: f32) -> Vector4 {
+        Vector4 {
+            x: a.x + (b.x - a.x) * t,
+            y: a.y + (b.y - a.y) * t,
+            z: a.z + (b.z - a.z) * t,
+            w: a.w + (b.w - a.w) * t,
+        }
     }
+
+    /// Divides the x, y and z components by w, converting homogeneous
+    /// clip-space coordinates to normalized device coordinates.
+    pub fn perspective_divide(&self) -> Vector3 {
+        Vector3 {
+            x: self.x / self.w,
+            y: self.y / self.w,
+            z: self.z / self.w,
+        }
+    }
+
 }
 
-pub struct Triangle2D<T>(pub Vector2<T>, pub Vector2<T>, pub Vector2<T>) where T: Num + Copy + PartialOrd;
+impl From<(f32, f32, f32, f32)> for Vector4 {
+    fn from((x, y, z, w): (f32, f32, f32, f32)) -> Self {
+        Self { x, y, z, w }
+    }
+}
 
-impl<T> Triangle2D<T> where T: Num + Signed + Copy + PartialOrd {
+impl From<[f32; 4]> for Vector4 {
+    fn from([x, y, z, w]: [f32; 4]) -> Self {
+        Self { x, y, z, w }
+    }
+}
 
-    /// Gets the area of the triangle using [Heron's Formula](https://en.wikipedia.org/wiki/Heron%27s_formula).
-    pub fn area(&self) -> T {
-        (
-            (self.1.x - self.0.x) * (self.2.y - self.0.y)
-                -
-            (self.2.x - self.0.x) * (self.1.y - self.0.y)
-        ).abs()
+impl From<Vector4> for (f32, f32, f32, f32) {
+    fn from(vector: Vector4) -> Self {
+        (vector.x, vector.y, vector.z, vector.w)
     }
+}
 
-    /// Creates a rectangle that encapsulates the triangle.
-    pub fn encapsulating_rectangle(&self) -> Rectangle2D<T> {
-        Rectangle2D {
-            lefttopmost: Vector2::new(self.min_x(), self.min_y()),
-            rightbottommost: Vector2::new(self.max_x(), self.max_y()),
+impl From<Vector4> for [f32; 4] {
+    fn from(vector: Vector4) -> Self {
+        [vector.x, vector.y, vector.z, vector.w]
+    }
+}
+
+/// Formats the vector as `(x, y, z, w)`.
+impl fmt::Display for Vector4 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "({}, {}, {}, {})", self.x, self.y, self.z, self.w)
+    }
+}
+
+/// Gets the component with the index, where `x` is 0, `y` is 1, `z` is 2
+/// and `w` is 3.
+///
+/// # Panics
+/// Panics when the index isn't below 4.
+impl Index<usize> for Vector4 {
+    type Output = f32;
+
+    fn index(&self, index: usize) -> &f32 {
+        match index {
+            0 => &self.x,
+            1 => &self.y,
+            2 => &self.z,
+            3 => &self.w,
+            _ => panic!("index {index} is out of range for Vector4 with 4 components"),
         }
     }
+}
 
-    /// Tests if the point is inside the triangle.
-    pub fn hit_test(&self, point: Vector2<T>) -> bool {
-        let area = self.area();
+impl IndexMut<usize> for Vector4 {
+    fn index_mut(&mut self, index: usize) -> &mut f32 {
+        match index {
+            0 => &mut self.x,
+            1 => &mut self.y,
+            2 => &mut self.z,
+            3 => &mut self.w,
+            _ => panic!("index {index} is out of range for Vector4 with 4 components"),
+        }
+    }
+}
 
-        let a1 = Triangle2D(point, self.0, self.1).area();
-        let a2 = Triangle2D(point, self.1, self.2).area();
-        let a3 = Triangle2D(point, self.2, self.0).area();
+/// A 3x3 matrix of which the elements are stored row by row, mostly used for
+/// transformations of 2D points in homogeneous coordinates. The matrix is
+/// applied to column vectors, i.e. `matrix * vector`.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Matrix3 {
+    pub rows: [[f32; 3]; 3],
+}
 
-        a1 + a2 + a3 == area
+impl Matrix3 {
+
+    /// The matrix that doesn't transform vectors.
+    pub const IDENTITY: Matrix3 = Matrix3 {
+        rows: [
+            [1.0, 0.0, 0.0],
+            [0.0, 1.0, 0.0],
+            [0.0, 0.0, 1.0],
+        ],
+    };
+
+    pub fn new(rows: [[f32; 3]; 3]) -> Self {
+        Self { rows }
     }
 
-    /// Finds the highest x coordinate.
-    pub fn max_x(&self) -> T {
-        max(self.0.x, max(self.1.x, self.2.x))
+    /// Creates a matrix that rotates points counter-clockwise around the
+    /// origin by the angle in radians.
+    #[cfg(feature = "std")]
+    pub fn rotation(radians: f32) -> Self {
+        let (sin, cos) = radians.sin_cos();
+        Self::new([
+            [cos, -sin, 0.0],
+            [sin, cos, 0.0],
+            [0.0, 0.0, 1.0],
+        ])
     }
 
-    /// Finds the lowest y coordinate.
-    pub fn max_y(&self) -> T {
-        max(self.0.y, max(self.1.y, self.2.y))
+    /// Creates a matrix that scales both axes by the given factor.
+    pub fn scaling(x: f32, y: f32) -> Self {
+        Self::new([
+            [x, 0.0, 0.0],
+            [0.0, y, 0.0],
+            [0.0, 0.0, 1.0],
+        ])
     }
 
-    /// Finds the lowest x coordinate.
-    pub fn min_x(&self) -> T {
-        min(self.0.x, min(self.1.x, self.2.x))
+    /// Transforms the point, which is treated as the homogeneous coordinate
+    /// `(x, y, 1)`. The matrix is assumed to be affine, i.e. to have
+    /// `[0, 0, 1]` as its last row.
+    pub fn transform_point(&self, point: Vector2f) -> Vector2f {
+        let transformed = *self * Vector3 { x: point.x, y: point.y, z: 1.0 };
+        transformed.xy()
     }
 
-    /// Finds the lowest y coordinate.
-    pub fn min_y(&self) -> T {
-        min(self.0.y, min(self.1.y, self.2.y))
+    /// Creates a matrix that moves points by the given offset.
+    pub fn translation(x: f32, y: f32) -> Self {
+        Self::new([
+            [1.0, 0.0, x],
+            [0.0, 1.0, y],
+            [0.0, 0.0, 1.0],
+        ])
+    }
+
+    /// Swaps the rows and the columns of the matrix.
+    pub fn transpose(&self) -> Self {
+        let mut rows = [[0.0; 3]; 3];
+        for (i, row) in rows.iter_mut().enumerate() {
+            for (j, element) in row.iter_mut().enumerate() {
+                *element = self.rows[j][i];
+            }
+        }
+        Self::new(rows)
     }
 
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+impl Default for Matrix3 {
+    fn default() -> Self {
+        Self::IDENTITY
+    }
+}
 
-    #[test]
-    fn triangle2d_bounds() {
-        let triangle = Triangle2D(Vector2::new(0.0, 0.0), Vector2::new(1.0, 1.0),
-            Vector2::new(2.0, 2.0));
-        assert_eq!(triangle.min_x(), 0.0);
-        assert_eq!(triangle.min_y(), 0.0);
-        assert_eq!(triangle.max_x(), 2.0);
-        assert_eq!(triangle.max_y(), 2.0);
+/// Combines the transformations, such that `rhs` is applied first.
+impl Mul<Matrix3> for Matrix3 {
+    type Output = Matrix3;
 
-        let triangle = Triangle2D(Vector2::new(-2.0, -8.0), Vector2::new(525.7, 732.5),
-            Vector2::new(1.0, 1.0));
-        assert_eq!(triangle.min_x(), -2.0);
-        assert_eq!(triangle.min_y(), -8.0);
-        assert_eq!(triangle.max_x(), 525.7);
-        assert_eq!(triangle.max_y(), 732.5);
+    fn mul(self, rhs: Matrix3) -> Self::Output {
+        let mut rows = [[0.0; 3]; 3];
+        for (i, row) in rows.iter_mut().enumerate() {
+            for (j, element) in row.iter_mut().enumerate() {
+                *element = (0..3).map(|k| self.rows[i][k] * rhs.rows[k][j]).sum();
+            }
+        }
+        Matrix3::new(rows)
+    }
+}
+
+/// Transforms the vector.
+impl Mul<Vector3> for Matrix3 {
+    type Output = Vector3;
+
+    fn mul(self, rhs: Vector3) -> Self::Output {
+        let row = |i: usize| {
+            let row = &self.rows[i];
+            row[0] * rhs.x + row[1] * rhs.y + row[2] * rhs.z
+        };
+        Vector3 { x: row(0), y: row(1), z: row(2) }
+    }
+}
+
+/// A 4x4 matrix of which the elements are stored row by row. The matrix is
+/// applied to column vectors, i.e. `matrix * vector`.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Matrix4 {
+    pub rows: [[f32; 4]; 4],
+}
+
+impl Matrix4 {
+
+    /// The matrix that doesn't transform vectors.
+    pub const IDENTITY: Matrix4 = Matrix4 {
+        rows: [
+            [1.0, 0.0, 0.0, 0.0],
+            [0.0, 1.0, 0.0, 0.0],
+            [0.0, 0.0, 1.0, 0.0],
+            [0.0, 0.0, 0.0, 1.0],
+        ],
+    };
+
+    pub fn new(rows: [[f32; 4]; 4]) -> Self {
+        Self { rows }
+    }
+
+    /// Creates a perspective projection matrix for a camera looking down the
+    /// -Z axis, with the vertical field of view in radians. The near and far
+    /// planes are mapped to a depth of -1 and 1 respectively, like OpenGL.
+    #[cfg(feature = "std")]
+    pub fn perspective(fov_y: f32, aspect_ratio: f32, near: f32, far: f32) -> Self {
+        let focal_length = 1.0 / (fov_y / 2.0).tan();
+        Self::new([
+            [focal_length / aspect_ratio, 0.0, 0.0, 0.0],
+            [0.0, focal_length, 0.0, 0.0],
+            [0.0, 0.0, (far + near) / (near - far), 2.0 * far * near / (near - far)],
+            [0.0, 0.0, -1.0, 0.0],
+        ])
+    }
+
+    /// Creates a matrix that scales every axis by the given factor.
+    pub fn scaling(x: f32, y: f32, z: f32) -> Self {
+        Self::new([
+            [x, 0.0, 0.0, 0.0],
+            [0.0, y, 0.0, 0.0],
+            [0.0, 0.0, z, 0.0],
+            [0.0, 0.0, 0.0, 1.0],
+        ])
+    }
+
+    /// Creates a matrix that moves points by the given offset.
+    pub fn translation(x: f32, y: f32, z: f32) -> Self {
+        Self::new([
+            [1.0, 0.0, 0.0, x],
+            [0.0, 1.0, 0.0, y],
+            [0.0, 0.0, 1.0, z],
+            [0.0, 0.0, 0.0, 1.0],
+        ])
+    }
+
+    /// Swaps the rows and the columns of the matrix.
+    pub fn transpose(&self) -> Self {
+        let mut rows = [[0.0; 4]; 4];
+        for (i, row) in rows.iter_mut().enumerate() {
+            for (j, element) in row.iter_mut().enumerate() {
+                *element = self.rows[j][i];
+            }
+        }
+        Self::new(rows)
+    }
+
+}
+
+impl Default for Matrix4 {
+    fn default() -> Self {
+        Self::IDENTITY
+    }
+}
+
+/// Combines the transformations, such that `rhs` is applied first.
+impl Mul<Matrix4> for Matrix4 {
+    type Output = Matrix4;
+
+    fn mul(self, rhs: Matrix4) -> Self::Output {
+        let mut rows = [[0.0; 4]; 4];
+        for (i, row) in rows.iter_mut().enumerate() {
+            for (j, element) in row.iter_mut().enumerate() {
+                *element = (0..4).map(|k| self.rows[i][k] * rhs.rows[k][j]).sum();
+            }
+        }
+        Matrix4::new(rows)
+    }
+}
+
+/// Transforms the vector.
+impl Mul<Vector4> for Matrix4 {
+    type Output = Vector4;
+
+    fn mul(self, rhs: Vector4) -> Self::Output {
+        let row = |i: usize| {
+            let row = &self.rows[i];
+            row[0] * rhs.x + row[1] * rhs.y + row[2] * rhs.z + row[3] * rhs.w
+        };
+        Vector4::new(row(0), row(1), row(2), row(3))
+    }
+}
+
+/// An axis-aligned rectangle, where the left top-most corner is inclusive and
+/// the right bottom-most corner is exclusive.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Rectangle2D<T> where T: Num + Copy {
+    lefttopmost: Vector2<T>,
+    rightbottommost: Vector2<T>,
+}
+
+impl<T> Rectangle2D<T> where T: Num + Copy + PartialOrd {
+    /// Creates a rectangle from the left top-most (inclusive) and right
+    /// bottom-most (exclusive) corners.
+    pub fn new(min: Vector2<T>, max: Vector2<T>) -> Self {
+        Self {
+            lefttopmost: min,
+            rightbottommost: max,
+        }
+    }
+
+    /// Checks if the point is inside the rectangle.
+    pub fn contains(&self, point: Vector2<T>) -> bool {
+        point.x >= self.lefttopmost.x && point.x < self.rightbottommost.x
+            && point.y >= self.lefttopmost.y && point.y < self.rightbottommost.y
+    }
+
+    /// Creates a rectangle spanning the two opposite corners, which can be
+    /// given in any order.
+    pub fn from_points(a: Vector2<T>, b: Vector2<T>) -> Self {
+        Self {
+            lefttopmost: Vector2::new(min(a.x, b.x), min(a.y, b.y)),
+            rightbottommost: Vector2::new(max(a.x, b.x), max(a.y, b.y)),
+        }
+    }
+
+    /// Get the height
+    pub fn height(&self) -> T {
+        self.rightbottommost.y - self.lefttopmost.y
+    }
+
+    /// Get the overlapping area of the two rectangles, or `None` if they
+    /// don't overlap.
+    pub fn intersect(&self, other: &Rectangle2D<T>) -> Option<Rectangle2D<T>> {
+        let lefttopmost = Vector2::new(
+            max(self.lefttopmost.x, other.lefttopmost.x),
+            max(self.lefttopmost.y, other.lefttopmost.y),
+        );
+        let rightbottommost = Vector2::new(
+            min(self.rightbottommost.x, other.rightbottommost.x),
+            min(self.rightbottommost.y, other.rightbottommost.y),
+        );
+
+        if lefttopmost.x >= rightbottommost.x || lefttopmost.y >= rightbottommost.y {
+            return None;
+        }
+
+        Some(Rectangle2D { lefttopmost, rightbottommost })
+    }
+
+    /// Gets the smallest rectangle that contains both rectangles.
+    pub fn union(&self, other: &Rectangle2D<T>) -> Rectangle2D<T> {
+        Rectangle2D {
+            lefttopmost: Vector2::new(
+                min(self.lefttopmost.x, other.lefttopmost.x),
+                min(self.lefttopmost.y, other.lefttopmost.y),
+            ),
+            rightbottommost: Vector2::new(
+                max(self.rightbottommost.x, other.rightbottommost.x),
+                max(self.rightbottommost.y, other.rightbottommost.y),
+            ),
+        }
+    }
+
+    /// Get the width
+    pub fn width(&self) -> T {
+        self.rightbottommost.x - self.lefttopmost.x
+    }
+
+    /// Get the X coordinate
+    pub fn x(&self) -> T {
+        self.lefttopmost.x
+    }
+
+    /// Get the range of the X coordinate
+    pub fn x_range(&self) -> Range<T> {
+        self.lefttopmost.x..self.rightbottommost.x
+    }
+
+    /// Get the Y coordinate
+    pub fn y(&self) -> T {
+        self.lefttopmost.y
+    }
+
+    /// Get the range of the Y coordinate
+    pub fn y_range(&self) -> Range<T> {
+        self.lefttopmost.y..self.rightbottommost.y
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Triangle2D<T>(pub Vector2<T>, pub Vector2<T>, pub Vector2<T>) where T: Num + Copy + PartialOrd;
+
+impl<T> Triangle2D<T> where T: Num + Signed + Copy + PartialOrd {
+
+    /// Gets twice the area of the triangle, i.e. the area of the parallelogram
+    /// spanned by its edges, which avoids dividing integer coordinates.
+    pub fn area(&self) -> T {
+        let ab = Vector2::new(self.1.x - self.0.x, self.1.y - self.0.y);
+        let ac = Vector2::new(self.2.x - self.0.x, self.2.y - self.0.y);
+        ab.cross(ac).abs()
+    }
+
+    /// Creates a rectangle that encapsulates the triangle.
+    pub fn encapsulating_rectangle(&self) -> Rectangle2D<T> {
+        Rectangle2D {
+            lefttopmost: Vector2::new(self.min_x(), self.min_y()),
+            rightbottommost: Vector2::new(self.max_x(), self.max_y()),
+        }
+    }
+
+    /// Tests if the triangle has no area, i.e. when its vertices are
+    /// collinear or identical.
+    pub fn is_degenerate(&self) -> bool {
+        self.area().is_zero()
+    }
+
+    /// Tests if the point is inside the triangle.
+    pub fn hit_test(&self, point: Vector2<T>) -> bool {
+        let area = self.area();
+
+        let a1 = Triangle2D(point, self.0, self.1).area();
+        let a2 = Triangle2D(point, self.1, self.2).area();
+        let a3 = Triangle2D(point, self.2, self.0).area();
+
+        a1 + a2 + a3 == area
+    }
+
+    /// Finds the highest x coordinate.
+    pub fn max_x(&self) -> T {
+        max(self.0.x, max(self.1.x, self.2.x))
+    }
+
+    /// Finds the lowest y coordinate.
+    pub fn max_y(&self) -> T {
+        max(self.0.y, max(self.1.y, self.2.y))
+    }
+
+    /// Finds the lowest x coordinate.
+    pub fn min_x(&self) -> T {
+        min(self.0.x, min(self.1.x, self.2.x))
+    }
+
+    /// Finds the lowest y coordinate.
+    pub fn min_y(&self) -> T {
+        min(self.0.y, min(self.1.y, self.2.y))
+    }
+
+    /// Splits the triangle into four triangles at the midpoints of its edges:
+    /// one at every vertex and one in the middle, all with the same winding
+    /// as this triangle. For integer coordinates, the midpoints are rounded
+    /// towards zero, so the triangles only tile this one exactly when the
+    /// coordinates are even.
+    pub fn subdivide(&self) -> [Triangle2D<T>; 4] {
+        let two = T::one() + T::one();
+        let midpoint = |a: Vector2<T>, b: Vector2<T>| Vector2::new((a.x + b.x) / two, (a.y + b.y) / two);
+
+        let ab = midpoint(self.0, self.1);
+        let bc = midpoint(self.1, self.2);
+        let ca = midpoint(self.2, self.0);
+        [
+            Triangle2D(self.0, ab, ca),
+            Triangle2D(ab, self.1, bc),
+            Triangle2D(ca, bc, self.2),
+            Triangle2D(ab, bc, ca),
+        ]
+    }
+
+}
+
+/// The point where a ray hits a [`Triangle3D`], see
+/// [`Triangle3D::ray_hit`].
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct RayHit {
+
+    /// The distance from the origin of the ray to the point, in multiples of
+    /// the direction of the ray.
+    pub t: f32,
+
+    /// The barycentric weight of the second vertex of the triangle at the
+    /// point.
+    pub u: f32,
+
+    /// The barycentric weight of the third vertex of the triangle at the
+    /// point. The weight of the first vertex is `1 - u - v`.
+    pub v: f32,
+
+    /// Whether the ray hits the side of the triangle its normal points to.
+    pub front_facing: bool,
+
+}
+
+/// A triangle in three dimensions. Its vertices are counter-clockwise when
+/// seen from the side its [normal](Triangle3D::normal) points to, following
+/// the right-hand rule.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct Triangle3D(pub Vector3, pub Vector3, pub Vector3);
+
+impl Triangle3D {
+
+    /// Gets the area of the triangle. Unlike [`Triangle2D::area`], this is
+    /// the area itself, not twice the area.
+    #[cfg(feature = "std")]
+    pub fn area(&self) -> f32 {
+        self.edge_cross().length() / 2.0
+    }
+
+    /// Gets the distance along the ray from the origin in the given direction
+    /// to the point where it hits the triangle, in multiples of the
+    /// direction, see [`Triangle3D::ray_hit`].
+    pub fn intersect_ray(&self, origin: Vector3, direction: Vector3) -> Option<f32> {
+        self.ray_hit(origin, direction).map(|hit| hit.t)
+    }
+
+    /// Tests if the triangle has no area, i.e. when its vertices are
+    /// collinear or identical.
+    pub fn is_degenerate(&self) -> bool {
+        self.edge_cross() == Vector3::default()
+    }
+
+    /// Gets the unit normal of the triangle, which is the cross product of
+    /// the edges from the first vertex to the second and the third. A
+    /// degenerate triangle has no normal, which yields NaN components.
+    #[cfg(feature = "std")]
+    pub fn normal(&self) -> Vector3 {
+        self.edge_cross().normalized()
+    }
+
+    /// Gets the coefficients `(a, b, c, d)` of the plane through the
+    /// triangle, of which the points satisfy `ax + by + cz + d = 0`. `(a, b,
+    /// c)` is the [normal](Triangle3D::normal), so the left hand side is the
+    /// signed distance of a point to the plane.
+    #[cfg(feature = "std")]
+    pub fn plane(&self) -> Vector4 {
+        let normal = self.normal();
+        Vector4::new(normal.x, normal.y, normal.z, -normal.dot(self.0))
+    }
+
+    /// Intersects the ray from the origin in the given direction with the
+    /// triangle, using the Möller–Trumbore algorithm. Both sides of the
+    /// triangle are hit, but only in front of the origin. Rays parallel to
+    /// the triangle never hit it, nor do they hit degenerate triangles.
+    pub fn ray_hit(&self, origin: Vector3, direction: Vector3) -> Option<RayHit> {
+        let edge1 = self.1 - self.0;
+        let edge2 = self.2 - self.0;
+        let p = direction.cross(edge2);
+        let determinant = edge1.dot(p);
+        if determinant.abs() < f32::EPSILON {
+            return None;
+        }
+
+        let inverse_determinant = 1.0 / determinant;
+        let offset = origin - self.0;
+        let u = offset.dot(p) * inverse_determinant;
+        if !(0.0..=1.0).contains(&u) {
+            return None;
+        }
+
+        let q = offset.cross(edge1);
+        let v = direction.dot(q) * inverse_determinant;
+        if v < 0.0 || u + v > 1.0 {
+            return None;
+        }
+
+        let t = edge2.dot(q) * inverse_determinant;
+        (t > f32::EPSILON).then_some(RayHit { t, u, v, front_facing: determinant > 0.0 })
+    }
+
+    fn edge_cross(&self) -> Vector3 {
+        (self.1 - self.0).cross(self.2 - self.0)
+    }
+
+}
+
+/// An edge function of a triangle edge, as used by half-space rasterizers.
+/// It evaluates to zero on the edge, to a positive value on one side and a
+/// negative value on the other side. Because the function is linear, it can
+/// be stepped incrementally across a scanline.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct EdgeFunction {
+    step_x: i64,
+    step_y: i64,
+    constant: i64,
+}
+
+impl EdgeFunction {
+
+    /// Creates the edge function for the edge from `from` to `to`, which is
+    /// positive for points to the left of the edge (in a Y-down coordinate
+    /// system, points on the clockwise side).
+    pub fn new(from: Vector2<i32>, to: Vector2<i32>) -> Self {
+        let (from_x, from_y) = (from.x as i64, from.y as i64);
+        let (to_x, to_y) = (to.x as i64, to.y as i64);
+        Self {
+            step_x: from_y - to_y,
+            step_y: to_x - from_x,
+            constant: from_x * to_y - from_y * to_x,
+        }
+    }
+
+    /// Creates the three edge functions for the triangle, oriented such that
+    /// all of them are non-negative inside of the triangle.
+    pub fn for_triangle(triangle: &Triangle2D<i32>) -> [EdgeFunction; 3] {
+        let edges = [
+            EdgeFunction::new(triangle.0, triangle.1),
+            EdgeFunction::new(triangle.1, triangle.2),
+            EdgeFunction::new(triangle.2, triangle.0),
+        ];
+
+        if edges[0].evaluate(triangle.2) < 0 {
+            edges.map(EdgeFunction::flipped)
+        } else {
+            edges
+        }
+    }
+
+    /// Evaluates the edge function at the given point.
+    #[inline]
+    pub fn evaluate(&self, point: Vector2<i32>) -> i64 {
+        self.step_x * point.x as i64 + self.step_y * point.y as i64 + self.constant
+    }
+
+    /// Tests if the edge is a top or a left edge of a triangle of which the
+    /// inside is on the positive side, in a Y-down coordinate system.
+    pub fn is_top_left(&self) -> bool {
+        self.step_x > 0 || (self.step_x == 0 && self.step_y > 0)
+    }
+
+    /// Biases the edge function according to the top-left fill rule, such
+    /// that points exactly on the edge are only non-negative when it's a top
+    /// or left edge. An edge shared by two triangles is thus filled by only
+    /// one of them.
+    pub fn with_fill_rule(self) -> EdgeFunction {
+        if self.is_top_left() {
+            self
+        } else {
+            EdgeFunction {
+                constant: self.constant - 1,
+                ..self
+            }
+        }
+    }
+
+    /// Moves the edge outwards, such that the function is non-negative at
+    /// every point of which the axis-aligned square with the given width
+    /// overlaps the positive side of the edge. This is used for conservative
+    /// rasterization, where every pixel touched by the triangle is covered.
+    pub fn dilated(self, width: i64) -> EdgeFunction {
+        EdgeFunction {
+            constant: self.constant + (self.step_x.abs() + self.step_y.abs()) * width / 2,
+            ..self
+        }
+    }
+
+    /// Gets the edge function with the sign of the sides swapped.
+    pub fn flipped(self) -> EdgeFunction {
+        EdgeFunction {
+            step_x: -self.step_x,
+            step_y: -self.step_y,
+            constant: -self.constant,
+        }
+    }
+
+    /// Gets the amount the function changes when moving one unit to the right.
+    #[inline]
+    pub fn step_x(&self) -> i64 {
+        self.step_x
+    }
+
+    /// Gets the amount the function changes when moving one unit down.
+    #[inline]
+    pub fn step_y(&self) -> i64 {
+        self.step_y
+    }
+
+}
+
+/// The maximum amount of vertices of the polygon that is the result of
+/// clipping a triangle against a rectangle.
+const MAX_CLIPPED_POLYGON_VERTICES: usize = 7;
+
+/// The maximum amount of triangles [`clip_triangle`] can produce.
+pub const MAX_CLIPPED_TRIANGLES: usize = MAX_CLIPPED_POLYGON_VERTICES - 2;
+
+type ClippedPolygon<T> = ArrayVec<Vector2<T>, MAX_CLIPPED_POLYGON_VERTICES>;
+
+/// Clips the polygon against a single edge of the clip rectangle, using the
+/// [Sutherland–Hodgman algorithm](https://en.wikipedia.org/wiki/Sutherland%E2%80%93Hodgman_algorithm).
+/// The `distance` function returns how far a point is on the inside of the
+/// edge, which is negative for points outside of it.
+fn clip_polygon_against_edge<T>(polygon: &ClippedPolygon<T>, distance: impl Fn(Vector2<T>) -> T)
+        -> ClippedPolygon<T>
+        where T: FloatCore {
+    let mut output = ClippedPolygon::new();
+
+    let Some(&last) = polygon.last() else {
+        return output;
+    };
+
+    let mut previous = last;
+    for &current in polygon {
+        let previous_distance = distance(previous);
+        let current_distance = distance(current);
+
+        if (current_distance >= T::zero()) != (previous_distance >= T::zero()) {
+            let t = previous_distance / (previous_distance - current_distance);
+            output.push(Vector2::new(
+                previous.x + (current.x - previous.x) * t,
+                previous.y + (current.y - previous.y) * t,
+            ));
+        }
+
+        if current_distance >= T::zero() {
+            output.push(current);
+        }
+
+        previous = current;
+    }
+
+    output
+}
+
+/// Tests whether the points, in order, make up a convex polygon, i.e. one
+/// that turns in the same direction at every point, and doesn't wind around
+/// more than once. Polygons with fewer than three points, or of which all
+/// points are collinear, aren't convex.
+pub fn is_convex_polygon(points: &[Vector2f]) -> bool {
+    if points.len() < 3 {
+        return false;
+    }
+
+    let edge = |index: usize| {
+        let (from, to) = (points[index], points[(index + 1) % points.len()]);
+        Vector2f::new(to.x - from.x, to.y - from.y)
+    };
+
+    let mut turn = 0.0;
+    let mut direction_changes = 0;
+    let mut direction = (0..points.len()).rev().map(|index| edge(index).x).find(|x| *x != 0.0);
+    for index in 0..points.len() {
+        let (current, next) = (edge(index), edge((index + 1) % points.len()));
+        let cross = current.cross(next);
+        if cross != 0.0 {
+            if turn != 0.0 && cross.signum() != turn {
+                return false;
+            }
+            turn = cross.signum();
+        }
+
+        // The edges of a polygon that winds around once only change between
+        // going left and right twice.
+        if current.x != 0.0 && direction.is_some_and(|x| x.signum() != current.x.signum()) {
+            direction_changes += 1;
+        }
+        if current.x != 0.0 {
+            direction = Some(current.x);
+        }
+    }
+
+    turn != 0.0 && direction_changes <= 2
+}
+
+/// Clips the triangle against the rectangle, and splits the remaining
+/// polygon back into triangles. A triangle that is completely inside of the
+/// rectangle is returned as-is, and one that is completely outside of it
+/// results in no triangles. Triangles without area are omitted.
+pub fn clip_triangle<T>(triangle: &Triangle2D<T>, rectangle: &Rectangle2D<T>)
+        -> ArrayVec<Triangle2D<T>, MAX_CLIPPED_TRIANGLES>
+        where T: FloatCore + Signed {
+    let mut polygon = ClippedPolygon::new();
+    polygon.push(triangle.0);
+    polygon.push(triangle.1);
+    polygon.push(triangle.2);
+
+    let min = rectangle.lefttopmost;
+    let max = rectangle.rightbottommost;
+    let polygon = clip_polygon_against_edge(&polygon, |point| point.x - min.x);
+    let polygon = clip_polygon_against_edge(&polygon, |point| max.x - point.x);
+    let polygon = clip_polygon_against_edge(&polygon, |point| point.y - min.y);
+    let polygon = clip_polygon_against_edge(&polygon, |point| max.y - point.y);
+
+    // The intersections can be slightly off due to rounding errors, so make
+    // sure they don't end up outside of the rectangle.
+    let clamp = |point: Vector2<T>| Vector2::new(point.x.max(min.x).min(max.x), point.y.max(min.y).min(max.y));
+
+    let mut triangles = ArrayVec::new();
+    for i in 2..polygon.len() {
+        let triangle = Triangle2D(clamp(polygon[0]), clamp(polygon[i - 1]), clamp(polygon[i]));
+        if triangle.area() != T::zero() {
+            triangles.push(triangle);
+        }
+    }
+    triangles
+}
+
+/// A vertex with a position in homogeneous clip-space coordinates, of which
+/// the position and other attributes can be interpolated when clipping.
+pub trait ClipVertex: Copy {
+
+    /// Gets the position in homogeneous clip-space coordinates.
+    fn clip_position(&self) -> Vector4;
+
+    /// Linearly interpolates between `a` and `b`.
+    fn interpolate(a: Self, b: Self, t: f32) -> Self;
+
+}
+
+impl ClipVertex for Vector4 {
+
+    fn clip_position(&self) -> Vector4 {
+        *self
+    }
+
+    fn interpolate(a: Self, b: Self, t: f32) -> Self {
+        Vector4::lerp(a, b, t)
+    }
+
+}
+
+/// The maximum amount of triangles [`clip_triangle_near_plane`] can produce.
+pub const MAX_NEAR_CLIPPED_TRIANGLES: usize = 2;
+
+/// Clips the triangle, in homogeneous clip-space coordinates, against the
+/// near plane `z = -w`. This must happen before the perspective divide,
+/// since vertices behind the camera would otherwise be mirrored onto the
+/// screen. A triangle crossing the plane is split into one or two triangles.
+pub fn clip_triangle_near_plane<V>(triangle: &[V; 3]) -> ArrayVec<[V; 3], MAX_NEAR_CLIPPED_TRIANGLES>
+        where V: ClipVertex {
+    let distance = |vertex: &V| {
+        let position = vertex.clip_position();
+        position.z + position.w
+    };
+
+    let mut polygon = ArrayVec::<V, 4>::new();
+    for (i, current) in triangle.iter().enumerate() {
+        let previous = &triangle[(i + 2) % 3];
+        let previous_distance = distance(previous);
+        let current_distance = distance(current);
+
+        if (current_distance >= 0.0) != (previous_distance >= 0.0) {
+            let t = previous_distance / (previous_distance - current_distance);
+            polygon.push(V::interpolate(*previous, *current, t));
+        }
+
+        if current_distance >= 0.0 {
+            polygon.push(*current);
+        }
+    }
+
+    let mut triangles = ArrayVec::new();
+    for i in 2..polygon.len() {
+        triangles.push([polygon[0], polygon[i - 1], polygon[i]]);
+    }
+    triangles
+}
+
+/// Splits the triangle into four triangles at the midpoints of its edges,
+/// like [`Triangle2D::subdivide`], of which the attributes of the vertices
+/// at the midpoints are interpolated. This is useful to add detail before
+/// displacing the vertices.
+pub fn subdivide_triangle<V>(triangle: &[V; 3]) -> [[V; 3]; 4] where V: ClipVertex {
+    let [a, b, c] = *triangle;
+    let ab = V::interpolate(a, b, 0.5);
+    let bc = V::interpolate(b, c, 0.5);
+    let ca = V::interpolate(c, a, 0.5);
+    [
+        [a, ab, ca],
+        [ab, b, bc],
+        [ca, bc, c],
+        [ab, bc, ca],
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn vector2f_lerp() {
+        let a = Vector2f::new(-1.0, 2.0);
+        let b = Vector2f::new(3.0, 4.0);
+        assert_eq!(Vector2f::lerp(a, b, 0.0), a);
+        assert_eq!(Vector2f::lerp(a, b, 1.0), b);
+        assert_eq!(Vector2f::lerp(a, b, 0.5), Vector2f::new(1.0, 3.0));
+    }
+
+    #[test]
+    fn vector2_conversions() {
+        assert_eq!(Vector2::from((3, -4)), Vector2::new(3, -4));
+        assert_eq!(Vector2::from([1.5, 2.0]), Vector2::new(1.5, 2.0));
+
+        let tuple: (i32, i32) = Vector2::new(7, 8).into();
+        assert_eq!(tuple, (7, 8));
+        let array: [f32; 2] = Vector2::new(0.5, -0.25).into();
+        assert_eq!(array, [0.5, -0.25]);
+
+        assert_eq!(Vector2::new(3, -4).to_string(), "(3, -4)");
+        assert_eq!(Vector2::new(1.5, 0.0).to_string(), "(1.5, 0)");
+    }
+
+    #[test]
+    fn vector2f_reflect() {
+        let velocity = Vector2f::new(3.0, -2.0);
+        assert_eq!(velocity.reflect(Vector2f::new(0.0, 1.0)), Vector2f::new(3.0, 2.0));
+        assert_eq!(velocity.reflect(Vector2f::new(-1.0, 0.0)), Vector2f::new(-3.0, -2.0));
+
+        // Reflecting off a diagonal swaps the components.
+        let normal = Vector2f::new(1.0, -1.0);
+        let normal = Vector2f::new(normal.x / 2.0f32.sqrt(), normal.y / 2.0f32.sqrt());
+        let reflected = Vector2f::new(1.0, 0.0).reflect(normal);
+        assert!(reflected.x.abs() < 0.0001 && (reflected.y - 1.0).abs() < 0.0001, "{reflected:?}");
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn vector2f_angles() {
+        use std::f32::consts::{FRAC_PI_2, PI};
+        let close = |a: Vector2f, b: Vector2f| Vector2f::distance(a, b) < 0.0001;
+
+        let rotated = Vector2f::new(1.0, 0.0).rotated(FRAC_PI_2);
+        assert!(close(rotated, Vector2f::new(0.0, 1.0)), "{rotated:?}");
+        assert!(close(Vector2f::new(2.0, 3.0).rotated(PI), Vector2f::new(-2.0, -3.0)));
+
+        assert!(close(Vector2f::from_angle(FRAC_PI_2), Vector2f::new(0.0, 1.0)));
+        assert_eq!(Vector2f::new(0.0, 2.0).angle(), FRAC_PI_2);
+        assert_eq!(Vector2f::new(-1.0, 0.0).angle(), PI);
+        assert!((Vector2f::from_angle(-2.5).angle() + 2.5).abs() < 0.0001);
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn vector2f_distance() {
+        let a = Vector2f::new(1.0, 2.0);
+        let b = Vector2f::new(4.0, 6.0);
+        assert_eq!(Vector2f::distance_squared(a, b), 25.0);
+        assert_eq!(Vector2f::distance(a, b), 5.0);
+        assert_eq!(Vector2f::distance(b, a), 5.0);
+        assert_eq!(Vector2f::distance(a, a), 0.0);
+        assert_eq!(a.dot(b), 16.0);
+    }
+
+    #[test]
+    fn vector2_component_wise() {
+        let a = Vector2::new(-3, 5);
+        let b = Vector2::new(2, -7);
+        assert_eq!(a.min(b), Vector2::new(-3, -7));
+        assert_eq!(a.max(b), Vector2::new(2, 5));
+        assert_eq!(a.abs(), Vector2::new(3, 5));
+        assert_eq!(b.abs(), Vector2::new(2, 7));
+        assert_eq!(Vector2::new(-0.5, 0.25).abs(), Vector2::new(0.5, 0.25));
+    }
+
+    #[test]
+    fn vector2_clamp() {
+        let lo = Vector2::new(-1.0, 0.0);
+        let hi = Vector2::new(1.0, 10.0);
+        assert_eq!(Vector2::new(-4.0, 20.0).clamp(lo, hi), Vector2::new(-1.0, 10.0));
+        assert_eq!(Vector2::new(4.0, -20.0).clamp(lo, hi), Vector2::new(1.0, 0.0));
+        assert_eq!(Vector2::new(0.5, 5.0).clamp(lo, hi), Vector2::new(0.5, 5.0));
+    }
+
+    #[test]
+    fn vector3_and_vector4_conversions() {
+        let vector = Vector3::from((1.0, 2.0, 3.0));
+        assert_eq!(vector, Vector3::from([1.0, 2.0, 3.0]));
+        assert_eq!(<(f32, f32, f32)>::from(vector), (1.0, 2.0, 3.0));
+        assert_eq!(<[f32; 3]>::from(vector), [1.0, 2.0, 3.0]);
+        assert_eq!(vector.to_string(), "(1, 2, 3)");
+
+        let vector = Vector4::from((1.0, 2.0, 3.0, 0.5));
+        assert_eq!(vector, Vector4::from([1.0, 2.0, 3.0, 0.5]));
+        assert_eq!(vector, Vector4::new(1.0, 2.0, 3.0, 0.5));
+        assert_eq!(<(f32, f32, f32, f32)>::from(vector), (1.0, 2.0, 3.0, 0.5));
+        assert_eq!(<[f32; 4]>::from(vector), [1.0, 2.0, 3.0, 0.5]);
+        assert_eq!(vector.to_string(), "(1, 2, 3, 0.5)");
+    }
+
+    #[test]
+    fn vector3_lerp() {
+        let a = Vector3 { x: 0.0, y: -2.0, z: 8.0 };
+        let b = Vector3 { x: 1.0, y: 2.0, z: 4.0 };
+        assert_eq!(Vector3::lerp(a, b, 0.0), a);
+        assert_eq!(Vector3::lerp(a, b, 1.0), b);
+        assert_eq!(Vector3::lerp(a, b, 0.5), Vector3 { x: 0.5, y: 0.0, z: 6.0 });
+    }
+
+    #[test]
+    fn vector3_cross_and_xy() {
+        let x = Vector3 { x: 1.0, y: 0.0, z: 0.0 };
+        let y = Vector3 { x: 0.0, y: 1.0, z: 0.0 };
+        let z = Vector3 { x: 0.0, y: 0.0, z: 1.0 };
+        assert_eq!(x.cross(y), z);
+        assert_eq!(y.cross(x), Vector3 { x: 0.0, y: 0.0, z: -1.0 });
+        assert_eq!(y * z, x);
+
+        let a = Vector3 { x: 2.0, y: -3.0, z: 4.0 };
+        let b = Vector3 { x: -1.0, y: 5.0, z: 0.5 };
+        assert_eq!(a.cross(b), Vector3 { x: -21.5, y: -5.0, z: 7.0 });
+        assert_eq!(a.xy(), Vector2f::new(2.0, -3.0));
+    }
+
+    #[test]
+    fn rectangle2d_dimensions() {
+        let rectangle = Rectangle2D::new(Vector2::new(-2, 3), Vector2::new(5, 7));
+        assert_eq!(rectangle.width(), 7);
+        assert_eq!(rectangle.height(), 4);
+        assert_eq!(rectangle, Rectangle2D::from_points(Vector2::new(5, 3), Vector2::new(-2, 7)));
+        assert_eq!(rectangle, Rectangle2D::from_points(Vector2::new(5, 7), Vector2::new(-2, 3)));
+    }
+
+    #[test]
+    fn rectangle2d_contains() {
+        let rectangle = Rectangle2D::new(Vector2::new(-2, 3), Vector2::new(5, 7));
+        assert!(rectangle.contains(Vector2::new(-2, 3)));
+        assert!(rectangle.contains(Vector2::new(4, 6)));
+        assert!(!rectangle.contains(Vector2::new(5, 6)));
+        assert!(!rectangle.contains(Vector2::new(4, 7)));
+        assert!(!rectangle.contains(Vector2::new(-3, 4)));
+    }
+
+    #[test]
+    fn rectangle2d_union() {
+        let a = Rectangle2D::new(Vector2::new(0, 0), Vector2::new(10, 10));
+        let b = Rectangle2D::new(Vector2::new(5, -5), Vector2::new(15, 5));
+        let expected = Rectangle2D::new(Vector2::new(0, -5), Vector2::new(15, 10));
+        assert_eq!(a.union(&b), expected);
+        assert_eq!(b.union(&a), expected);
+
+        let nested = Rectangle2D::new(Vector2::new(2, 3), Vector2::new(4, 5));
+        assert_eq!(a.union(&nested), a);
+    }
+
+    #[test]
+    fn rectangle2d_intersect() {
+        let a = Rectangle2D::new(Vector2::new(0, 0), Vector2::new(10, 10));
+
+        // Overlapping
+        let b = Rectangle2D::new(Vector2::new(5, -5), Vector2::new(15, 5));
+        let expected = Rectangle2D::new(Vector2::new(5, 0), Vector2::new(10, 5));
+        assert_eq!(a.intersect(&b), Some(expected));
+        assert_eq!(b.intersect(&a), Some(expected));
+
+        // Disjoint
+        let b = Rectangle2D::new(Vector2::new(20, 20), Vector2::new(30, 30));
+        assert_eq!(a.intersect(&b), None);
+
+        // Touching edges don't overlap, since the far edge is exclusive.
+        let b = Rectangle2D::new(Vector2::new(10, 0), Vector2::new(20, 10));
+        assert_eq!(a.intersect(&b), None);
+
+        // Nested
+        let b = Rectangle2D::new(Vector2::new(2, 3), Vector2::new(4, 5));
+        assert_eq!(a.intersect(&b), Some(b));
+        assert_eq!(b.intersect(&a), Some(b));
+    }
+
+    #[test]
+    fn clip_triangle_inside() {
+        let rectangle = Rectangle2D::new(Vector2::new(0.0, 0.0), Vector2::new(10.0, 10.0));
+        let triangle = Triangle2D(Vector2::new(1.0, 1.0), Vector2::new(9.0, 1.0), Vector2::new(5.0, 9.0));
+        assert_eq!(clip_triangle(&triangle, &rectangle).as_slice(), &[triangle]);
+    }
+
+    #[test]
+    fn clip_triangle_outside() {
+        let rectangle = Rectangle2D::new(Vector2::new(0.0, 0.0), Vector2::new(10.0, 10.0));
+        let triangle = Triangle2D(Vector2::new(-9.0, 1.0), Vector2::new(-1.0, 1.0), Vector2::new(-5.0, 9.0));
+        assert!(clip_triangle(&triangle, &rectangle).is_empty());
+    }
+
+    #[test]
+    fn clip_triangle_halfway_off_left_edge() {
+        let rectangle = Rectangle2D::new(Vector2::new(0.0, 0.0), Vector2::new(10.0, 10.0));
+        let triangle = Triangle2D(Vector2::new(-4.0, 0.0), Vector2::new(4.0, 0.0), Vector2::new(4.0, 8.0));
+
+        // The clipped polygon is (0, 4), (0, 0), (4, 0), (4, 8).
+        assert_eq!(clip_triangle(&triangle, &rectangle).as_slice(), &[
+            Triangle2D(Vector2::new(0.0, 4.0), Vector2::new(0.0, 0.0), Vector2::new(4.0, 0.0)),
+            Triangle2D(Vector2::new(0.0, 4.0), Vector2::new(4.0, 0.0), Vector2::new(4.0, 8.0)),
+        ]);
+    }
+
+    #[test]
+    fn vector2f_to_i32() {
+        let positive = Vector2f::new(1.5, 2.4);
+        assert_eq!(positive.round_to_i32(), Vector2::new(2, 2));
+        assert_eq!(positive.floor_to_i32(), Vector2::new(1, 2));
+        assert_eq!(positive.ceil_to_i32(), Vector2::new(2, 3));
+        assert_eq!(positive.trunc_to_i32(), Vector2::new(1, 2));
+
+        // Flooring and truncating differ for negative coordinates.
+        let negative = Vector2f::new(-1.5, -2.4);
+        assert_eq!(negative.round_to_i32(), Vector2::new(-2, -2));
+        assert_eq!(negative.floor_to_i32(), Vector2::new(-2, -3));
+        assert_eq!(negative.ceil_to_i32(), Vector2::new(-1, -2));
+        assert_eq!(negative.trunc_to_i32(), Vector2::new(-1, -2));
+
+        assert_eq!(Vector2f::new(f32::NAN, 1e12).round_to_i32(), Vector2::new(0, i32::MAX));
+    }
+
+    #[test]
+    fn vector2_cross() {
+        let x = Vector2::new(2, 0);
+        let y = Vector2::new(0, 3);
+        assert_eq!(x.cross(y), 6);
+        assert_eq!(y.cross(x), -6);
+        assert_eq!(x.cross(Vector2::new(-4, 0)), 0);
+        assert_eq!(x.cross(x), 0);
+
+        let a = Vector2f::new(1.5, -2.0);
+        assert_eq!(a.cross(Vector2f::new(3.0, -4.0)), 0.0);
+        assert_eq!(a.cross(Vector2f::new(2.0, 1.5)), 6.25);
+    }
+
+    #[test]
+    fn vector2f_approx_eq() {
+        let a = Vector2f::new(1.0, -2.0);
+        assert!(a.approx_eq(Vector2f::new(1.0 + 1e-6, -2.0 - 1e-6), 1e-5));
+        assert!(a.approx_eq(Vector2f::new(0.1 * 10.0, -0.2 * 10.0), f32::EPSILON));
+        assert!(!a.approx_eq(Vector2f::new(1.0, -2.1), 1e-5));
+        assert!(!a.approx_eq(Vector2f::new(1.5, -2.0), 0.1));
+        assert!(!a.approx_eq(Vector2f::new(f32::NAN, -2.0), 1.0));
+    }
+
+    #[test]
+    fn convex_polygons() {
+        let square = [
+            Vector2f::new(0.0, 0.0),
+            Vector2f::new(4.0, 0.0),
+            Vector2f::new(4.0, 4.0),
+            Vector2f::new(0.0, 4.0),
+        ];
+        assert!(is_convex_polygon(&square));
+        let reversed: Vec<Vector2f> = square.iter().rev().copied().collect();
+        assert!(is_convex_polygon(&reversed));
+
+        // Collinear points on an edge don't make the polygon concave.
+        let mut with_collinear = square.to_vec();
+        with_collinear.insert(1, Vector2f::new(2.0, 0.0));
+        assert!(is_convex_polygon(&with_collinear));
+
+        let mut concave = square.to_vec();
+        concave.insert(1, Vector2f::new(2.0, 1.0));
+        assert!(!is_convex_polygon(&concave));
+
+        let pentagram = [
+            Vector2f::new(0.0, -10.0),
+            Vector2f::new(5.9, 8.1),
+            Vector2f::new(-9.5, -3.1),
+            Vector2f::new(9.5, -3.1),
+            Vector2f::new(-5.9, 8.1),
+        ];
+        assert!(!is_convex_polygon(&pentagram));
+
+        assert!(!is_convex_polygon(&square[..2]));
+        let collinear = [Vector2f::new(0.0, 0.0), Vector2f::new(1.0, 1.0), Vector2f::new(2.0, 2.0)];
+        assert!(!is_convex_polygon(&collinear));
+    }
+
+    #[test]
+    fn subdivide_tiles_triangle() {
+        let triangle = Triangle2D(Vector2::new(-4, 2), Vector2::new(12, 6), Vector2::new(2, 14));
+        let triangles = triangle.subdivide();
+
+        let area: i32 = triangles.iter().map(Triangle2D::area).sum();
+        assert_eq!(area, triangle.area());
+        let winding = |triangle: &Triangle2D<i32>| {
+            (triangle.1.x - triangle.0.x) * (triangle.2.y - triangle.0.y)
+                - (triangle.2.x - triangle.0.x) * (triangle.1.y - triangle.0.y)
+        };
+        for sub_triangle in &triangles {
+            assert_eq!(sub_triangle.area() * 4, triangle.area());
+            assert_eq!(winding(sub_triangle).signum(), winding(&triangle).signum());
+        }
+
+        let bounds = triangle.encapsulating_rectangle();
+        for y in bounds.y_range() {
+            for x in bounds.x_range() {
+                let point = Vector2::new(x, y);
+                let covered = triangles.iter().any(|sub_triangle| sub_triangle.hit_test(point));
+                assert_eq!(covered, triangle.hit_test(point), "{point}");
+            }
+        }
+    }
+
+    #[test]
+    fn subdivide_interpolates_attributes() {
+        let triangle = [
+            Vector4::new(0.0, 0.0, 0.0, 1.0),
+            Vector4::new(4.0, 0.0, 2.0, 1.0),
+            Vector4::new(0.0, 4.0, 4.0, 3.0),
+        ];
+        let triangles = subdivide_triangle(&triangle);
+        assert_eq!(triangles[0], [
+            triangle[0],
+            Vector4::new(2.0, 0.0, 1.0, 1.0),
+            Vector4::new(0.0, 2.0, 2.0, 2.0),
+        ]);
+        assert_eq!(triangles[3], [
+            Vector4::new(2.0, 0.0, 1.0, 1.0),
+            Vector4::new(2.0, 2.0, 3.0, 2.0),
+            Vector4::new(0.0, 2.0, 2.0, 2.0),
+        ]);
+        assert_eq!(triangles[1][1], triangle[1]);
+        assert_eq!(triangles[2][2], triangle[2]);
+    }
+
+    #[test]
+    fn clip_triangle_covering_rectangle() {
+        let rectangle = Rectangle2D::new(Vector2::new(0.0, 0.0), Vector2::new(10.0, 10.0));
+        let triangle = Triangle2D(Vector2::new(-100.0, -100.0), Vector2::new(300.0, -100.0),
+            Vector2::new(-100.0, 300.0));
+
+        let triangles = clip_triangle(&triangle, &rectangle);
+        let area: f32 = triangles.iter().map(Triangle2D::area).sum();
+        assert!((area - 200.0).abs() < 0.001);
+        for triangle in &triangles {
+            let bounds = triangle.encapsulating_rectangle();
+            assert!(bounds.x() >= 0.0 && bounds.y() >= 0.0);
+            assert!(bounds.x_range().end <= 10.0 && bounds.y_range().end <= 10.0);
+        }
+    }
+
+    #[test]
+    fn vector4_perspective_divide() {
+        let vector = Vector4::new(2.0, -4.0, 1.0, 2.0);
+        assert_eq!(vector.perspective_divide(), Vector3 { x: 1.0, y: -2.0, z: 0.5 });
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn matrix3_transforms() {
+        let close = |a: Vector2f, b: Vector2f| Vector2f::distance(a, b) < 0.0001;
+        let point = Vector2f::new(2.0, -1.0);
+
+        assert_eq!(Matrix3::IDENTITY.transform_point(point), point);
+        assert_eq!(Matrix3::translation(1.0, 3.0).transform_point(point), Vector2f::new(3.0, 2.0));
+        assert_eq!(Matrix3::scaling(2.0, 0.5).transform_point(point), Vector2f::new(4.0, -0.5));
+
+        let rotation = Matrix3::rotation(std::f32::consts::FRAC_PI_2);
+        assert!(close(rotation.transform_point(point), Vector2f::new(1.0, 2.0)));
+
+        // The translation is applied after the rotation.
+        let combined = Matrix3::translation(1.0, 0.0) * rotation;
+        assert!(close(combined.transform_point(point), Vector2f::new(2.0, 2.0)));
+        assert_eq!(Matrix3::translation(1.0, 3.0).transpose().rows[2], [1.0, 3.0, 1.0]);
+    }
+
+    #[test]
+    fn matrix4_identity_and_transpose() {
+        let vector = Vector4::new(1.0, -2.0, 3.0, 1.0);
+        assert_eq!(Matrix4::IDENTITY * vector, vector);
+        assert_eq!(Matrix4::default(), Matrix4::IDENTITY);
+
+        let matrix = Matrix4::translation(4.0, 5.0, 6.0);
+        assert_eq!(matrix.transpose().rows[3], [4.0, 5.0, 6.0, 1.0]);
+        assert_eq!(matrix.transpose().transpose(), matrix);
+    }
+
+    #[test]
+    fn matrix4_transforms() {
+        let point = Vector4::new(1.0, 2.0, 3.0, 1.0);
+        assert_eq!(Matrix4::translation(1.0, -1.0, 2.0) * point, Vector4::new(2.0, 1.0, 5.0, 1.0));
+        assert_eq!(Matrix4::scaling(2.0, 3.0, -1.0) * point, Vector4::new(2.0, 6.0, -3.0, 1.0));
+
+        // Directions aren't affected by translation.
+        let direction = Vector4::new(1.0, 2.0, 3.0, 0.0);
+        assert_eq!(Matrix4::translation(1.0, -1.0, 2.0) * direction, direction);
+
+        // The translation is applied after the scaling.
+        let combined = Matrix4::translation(1.0, 0.0, 0.0) * Matrix4::scaling(2.0, 2.0, 2.0);
+        assert_eq!(combined * point, Vector4::new(3.0, 4.0, 6.0, 1.0));
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn matrix4_perspective() {
+        let projection = Matrix4::perspective(std::f32::consts::FRAC_PI_2, 2.0, 1.0, 10.0);
+
+        let near = (projection * Vector4::new(0.0, 1.0, -1.0, 1.0)).perspective_divide();
+        assert!((near.z - -1.0).abs() < 1e-6);
+        assert!((near.y - 1.0).abs() < 1e-6);
+
+        let far = (projection * Vector4::new(2.0, 0.0, -10.0, 1.0)).perspective_divide();
+        assert!((far.z - 1.0).abs() < 1e-6);
+        assert!((far.x - 0.1).abs() < 1e-6);
+    }
+
+    #[test]
+    fn clip_triangle_near_plane_in_front() {
+        let triangle = [
+            Vector4::new(0.0, 1.0, 0.0, 1.0),
+            Vector4::new(1.0, -1.0, 0.0, 1.0),
+            Vector4::new(-1.0, -1.0, 0.0, 1.0),
+        ];
+        assert_eq!(clip_triangle_near_plane(&triangle).as_slice(), &[triangle]);
+    }
+
+    #[test]
+    fn clip_triangle_near_plane_behind() {
+        let triangle = [
+            Vector4::new(0.0, 1.0, -3.0, -1.0),
+            Vector4::new(1.0, -1.0, -3.0, -1.0),
+            Vector4::new(-1.0, -1.0, -3.0, -1.0),
+        ];
+        assert!(clip_triangle_near_plane(&triangle).is_empty());
+    }
+
+    #[test]
+    fn clip_triangle_near_plane_partially_behind() {
+        let triangle = [
+            Vector4::new(0.0, 1.0, 0.0, 1.0),
+            Vector4::new(1.0, -1.0, 0.0, 1.0),
+            Vector4::new(0.0, 0.0, -3.0, -1.0),
+        ];
+
+        let triangles = clip_triangle_near_plane(&triangle);
+        assert_eq!(triangles.len(), 2);
+
+        for vertex in triangles.iter().flatten() {
+            assert!(vertex.z + vertex.w >= -0.0001, "{vertex:?} is behind the near plane");
+            assert!(vertex.w > 0.0, "{vertex:?} is behind the camera");
+
+            let position = vertex.perspective_divide();
+            assert!(position.x.is_finite() && position.y.is_finite());
+            assert!(position.x.abs() <= 4.0 / 3.0 + 0.0001, "{position:?}");
+            assert!(position.y.abs() <= 4.0 / 3.0 + 0.0001, "{position:?}");
+        }
+
+        // The edge from the first vertex to the one behind the camera is cut
+        // at a fifth of its length.
+        let cut = Vector4::new(0.0, 0.8, -0.6, 0.6);
+        assert!(triangles.iter().flatten().any(|vertex| {
+            (vertex.y - cut.y).abs() < 0.0001 && (vertex.w - cut.w).abs() < 0.0001
+        }));
+    }
+
+    #[test]
+    fn edge_function_sides() {
+        let edge = EdgeFunction::new(Vector2::new(0, 0), Vector2::new(4, 0));
+        assert_eq!(edge.evaluate(Vector2::new(2, 0)), 0);
+        assert!(edge.evaluate(Vector2::new(2, 3)) > 0);
+        assert!(edge.evaluate(Vector2::new(2, -3)) < 0);
+        assert_eq!(edge.flipped().evaluate(Vector2::new(2, 3)), -edge.evaluate(Vector2::new(2, 3)));
+    }
+
+    #[test]
+    fn index_vector_components() {
+        let mut vector = Vector2::new(3, -4);
+        assert_eq!((vector[0], vector[1]), (3, -4));
+        vector[1] = 7;
+        assert_eq!(vector.as_array(), [3, 7]);
+
+        let mut vector = Vector3 { x: 1.0, y: 2.0, z: 3.0 };
+        vector[2] += 1.0;
+        assert_eq!((vector[0], vector[1], vector[2]), (1.0, 2.0, 4.0));
+        assert_eq!(vector.as_array(), [1.0, 2.0, 4.0]);
+
+        let mut vector = Vector4::new(1.0, 2.0, 3.0, 4.0);
+        for index in 0..4 {
+            vector[index] *= 2.0;
+        }
+        assert_eq!((vector[0], vector[1], vector[2], vector[3]), (2.0, 4.0, 6.0, 8.0));
+        assert_eq!(vector.as_array(), [2.0, 4.0, 6.0, 8.0]);
+    }
+
+    #[test]
+    #[should_panic(expected = "index 2 is out of range for Vector2 with 2 components")]
+    fn index_vector2_out_of_range() {
+        let _ = Vector2f::new(0.0, 0.0)[2];
+    }
+
+    #[test]
+    #[should_panic(expected = "index 3 is out of range for Vector3 with 3 components")]
+    fn index_vector3_out_of_range() {
+        let mut vector = Vector3::default();
+        vector[3] = 1.0;
+    }
+
+    #[test]
+    #[should_panic(expected = "index 4 is out of range for Vector4 with 4 components")]
+    fn index_vector4_out_of_range() {
+        let _ = Vector4::default()[4];
+    }
+
+    #[test]
+    fn edge_function_dilated() {
+        // The edge along y = x, of which the positive side is below it.
+        let edge = EdgeFunction::new(Vector2::new(0, 0), Vector2::new(4, 4));
+        assert!(edge.evaluate(Vector2::new(2, 1)) < 0);
+        assert!(edge.dilated(2).evaluate(Vector2::new(3, 1)) >= 0);
+        assert!(edge.dilated(2).evaluate(Vector2::new(4, 1)) < 0);
+
+        // A square of a single unit just touches the edge at its corner.
+        assert!(edge.dilated(1).evaluate(Vector2::new(2, 1)) >= 0);
+        assert!(edge.dilated(1).evaluate(Vector2::new(3, 1)) < 0);
+        assert_eq!(edge.dilated(0), edge);
+    }
+
+    #[test]
+    fn edge_function_steps() {
+        let edge = EdgeFunction::new(Vector2::new(-3, 7), Vector2::new(12, -5));
+        let point = Vector2::new(4, 9);
+        assert_eq!(edge.evaluate(point) + edge.step_x(), edge.evaluate(Vector2::new(5, 9)));
+        assert_eq!(edge.evaluate(point) + edge.step_y(), edge.evaluate(Vector2::new(4, 10)));
+    }
+
+    #[test]
+    fn edge_functions_for_triangle_match_hit_test() {
+        let clockwise = Triangle2D(Vector2::new(0, 0), Vector2::new(10, 2), Vector2::new(3, 8));
+        let counter_clockwise = Triangle2D(clockwise.0, clockwise.2, clockwise.1);
+
+        for triangle in [clockwise, counter_clockwise] {
+            let edges = EdgeFunction::for_triangle(&triangle);
+            for y in -2..12 {
+                for x in -2..12 {
+                    let point = Vector2::new(x, y);
+                    let inside = edges.iter().all(|edge| edge.evaluate(point) >= 0);
+                    assert_eq!(inside, triangle.hit_test(point), "{point:?}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn edge_function_fill_rule_covers_shared_edges_once() {
+        // Two triangles forming a quad, sharing the diagonal.
+        let quads = [
+            [Vector2::new(0, 0), Vector2::new(6, 0), Vector2::new(6, 6), Vector2::new(0, 6)],
+            [Vector2::new(1, 0), Vector2::new(7, 3), Vector2::new(2, 9), Vector2::new(-3, 4)],
+        ];
+
+        for [a, b, c, d] in quads {
+            let triangles = [Triangle2D(a, b, c), Triangle2D(a, c, d)];
+            let edges = triangles.map(|triangle| {
+                EdgeFunction::for_triangle(&triangle).map(EdgeFunction::with_fill_rule)
+            });
+
+            for y in -4..12 {
+                for x in -4..12 {
+                    let point = Vector2::new(x, y);
+                    let covered = edges.iter()
+                        .filter(|edges| edges.iter().all(|edge| edge.evaluate(point) >= 0))
+                        .count();
+                    assert!(covered <= 1, "{point:?}");
+                }
+            }
+        }
+
+        let edge = EdgeFunction::new(Vector2::new(0, 0), Vector2::new(4, 0));
+        assert!(edge.is_top_left());
+        assert!(!edge.flipped().is_top_left());
+        assert_eq!(edge.flipped().with_fill_rule().evaluate(Vector2::new(2, 0)), -1);
+    }
+
+    #[test]
+    fn triangle2d_bounds() {
+        let triangle = Triangle2D(Vector2::new(0.0, 0.0), Vector2::new(1.0, 1.0),
+            Vector2::new(2.0, 2.0));
+        assert_eq!(triangle.min_x(), 0.0);
+        assert_eq!(triangle.min_y(), 0.0);
+        assert_eq!(triangle.max_x(), 2.0);
+        assert_eq!(triangle.max_y(), 2.0);
+
+        let triangle = Triangle2D(Vector2::new(-2.0, -8.0), Vector2::new(525.7, 732.5),
+            Vector2::new(1.0, 1.0));
+        assert_eq!(triangle.min_x(), -2.0);
+        assert_eq!(triangle.min_y(), -8.0);
+        assert_eq!(triangle.max_x(), 525.7);
+        assert_eq!(triangle.max_y(), 732.5);
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn vector3_dot_and_length() {
+        let a = Vector3::new(2.0, -3.0, 6.0);
+        assert_eq!(a.dot(Vector3::new(1.0, 2.0, 0.5)), -1.0);
+        assert_eq!(a.length(), 7.0);
+        assert_eq!(a.normalized(), Vector3::new(2.0 / 7.0, -3.0 / 7.0, 6.0 / 7.0));
+        assert_eq!(a - Vector3::new(1.0, 1.0, 1.0), Vector3::new(1.0, -4.0, 5.0));
+        assert_eq!(a + Vector3::new(1.0, 1.0, 1.0), Vector3::new(3.0, -2.0, 7.0));
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn triangle3d_in_xy_plane() {
+        let a = Vector3::new(0.0, 0.0, 2.0);
+        let b = Vector3::new(4.0, 0.0, 2.0);
+        let c = Vector3::new(0.0, 3.0, 2.0);
+
+        let triangle = Triangle3D(a, b, c);
+        assert_eq!(triangle.normal(), Vector3::new(0.0, 0.0, 1.0));
+        assert_eq!(Triangle3D(a, c, b).normal(), Vector3::new(0.0, 0.0, -1.0));
+        assert_eq!(triangle.plane(), Vector4::new(0.0, 0.0, 1.0, -2.0));
+
+        let triangle_2d = Triangle2D(a.xy(), b.xy(), c.xy());
+        assert_eq!(triangle.area(), 6.0);
+        assert_eq!(triangle.area() * 2.0, triangle_2d.area());
+        assert!(!triangle.is_degenerate());
+        assert!(Triangle3D(a, b, Vector3::new(8.0, 0.0, 2.0)).is_degenerate());
+    }
+
+    #[test]
+    fn triangle3d_ray_hits_centroid() {
+        let triangle = Triangle3D(
+            Vector3::new(-1.0, -1.0, 0.0),
+            Vector3::new(2.0, -1.0, 0.0),
+            Vector3::new(-1.0, 2.0, 0.0),
+        );
+        let down = Vector3::new(0.0, 0.0, -1.0);
+
+        let hit = triangle.ray_hit(Vector3::new(0.0, 0.0, 4.0), down).unwrap();
+        assert!((hit.t - 4.0).abs() < 1e-6);
+        assert!((hit.u - 1.0 / 3.0).abs() < 1e-6 && (hit.v - 1.0 / 3.0).abs() < 1e-6, "{hit:?}");
+        assert!(hit.front_facing);
+
+        // Twice as long a direction halves the distance.
+        let t = triangle.intersect_ray(Vector3::new(0.0, 0.0, 4.0), Vector3::new(0.0, 0.0, -2.0));
+        assert!(t.is_some_and(|t| (t - 2.0).abs() < 1e-6));
+
+        let back = triangle.ray_hit(Vector3::new(0.0, 0.0, -3.0), Vector3::new(0.0, 0.0, 1.0)).unwrap();
+        assert!((back.t - 3.0).abs() < 1e-6);
+        assert!(!back.front_facing);
+    }
+
+    #[test]
+    fn triangle3d_ray_misses() {
+        let triangle = Triangle3D(
+            Vector3::new(0.0, 0.0, 0.0),
+            Vector3::new(1.0, 0.0, 0.0),
+            Vector3::new(0.0, 1.0, 0.0),
+        );
+        let down = Vector3::new(0.0, 0.0, -1.0);
+
+        assert_eq!(triangle.intersect_ray(Vector3::new(0.8, 0.8, 1.0), down), None);
+        assert_eq!(triangle.intersect_ray(Vector3::new(-0.1, 0.5, 1.0), down), None);
+
+        // The triangle is behind the origin of the ray.
+        assert_eq!(triangle.intersect_ray(Vector3::new(0.25, 0.25, -1.0), down), None);
+
+        // The ray is parallel to the triangle, even though it lies in its
+        // plane.
+        let parallel = Vector3::new(1.0, 0.0, 0.0);
+        assert_eq!(triangle.intersect_ray(Vector3::new(-1.0, 0.25, 0.0), parallel), None);
+        assert_eq!(triangle.intersect_ray(Vector3::new(-1.0, 0.25, 1.0), parallel), None);
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn triangle3d_plane_distance() {
+        let triangle = Triangle3D(
+            Vector3::new(1.0, 0.0, 0.0),
+            Vector3::new(0.0, 1.0, 0.0),
+            Vector3::new(0.0, 0.0, 1.0),
+        );
+        let plane = triangle.plane();
+        let distance = |point: Vector3| plane.x * point.x + plane.y * point.y + plane.z * point.z + plane.w;
+        let side = 1.0 / 3.0f32.sqrt();
+        assert!((distance(Vector3::default()) + side).abs() < 1e-6);
+        assert!(distance(Vector3::new(1.0, 1.0, 1.0)) > 0.0);
+        assert!(distance(Vector3::new(0.5, 0.5, 0.0)).abs() < 1e-6);
+    }
+
+    #[test]
+    fn triangle2d_is_degenerate() {
+        assert!(!Triangle2D(Vector2::new(0, 0), Vector2::new(4, 0), Vector2::new(0, 4)).is_degenerate());
+        assert!(Triangle2D(Vector2::new(3, 3), Vector2::new(3, 3), Vector2::new(3, 3)).is_degenerate());
+        assert!(Triangle2D(Vector2::new(0, 0), Vector2::new(2, 1), Vector2::new(6, 3)).is_degenerate());
     }
 
     #[test]
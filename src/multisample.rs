@@ -0,0 +1,123 @@
+// Copyright (C) 2023 Tristan Gerritsen <tristan@thewoosh.org>
+// All Rights Reserved.
+
+use crate::{format::PixelFormat, math::Vector2, pixel::AlphaMode, Pixel};
+
+/// The number of samples that are stored for every pixel. With more than one
+/// sample, the coverage of a triangle is tested at multiple positions within
+/// the pixel, and the samples are averaged when resolving the swap chain, which
+/// smooths the edges of triangles.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub enum SampleCount {
+
+    /// A single sample per pixel, i.e. no anti-aliasing.
+    #[default]
+    X1,
+
+    /// Two samples per pixel.
+    X2,
+
+    /// Four samples per pixel, in a rotated grid.
+    X4,
+
+}
+
+/// The sample positions, relative to the sample position of a pixel without
+/// multisampling, in units of 1/16th of a pixel. These are the standard
+/// sample patterns used by Direct3D.
+const OFFSETS_X1: [Vector2<i32>; 1] = [Vector2 { x: 0, y: 0 }];
+const OFFSETS_X2: [Vector2<i32>; 2] = [Vector2 { x: 4, y: 4 }, Vector2 { x: -4, y: -4 }];
+const OFFSETS_X4: [Vector2<i32>; 4] = [
+    Vector2 { x: -2, y: -6 },
+    Vector2 { x: 6, y: -2 },
+    Vector2 { x: -6, y: 2 },
+    Vector2 { x: 2, y: 6 },
+];
+
+impl SampleCount {
+
+    /// Gets the number of samples per pixel.
+    pub const fn count(&self) -> usize {
+        match self {
+            SampleCount::X1 => 1,
+            SampleCount::X2 => 2,
+            SampleCount::X4 => 4,
+        }
+    }
+
+    /// Gets the positions of the samples within a pixel, in units of
+    /// 1/[`SampleCount::precision`] of a pixel.
+    pub(crate) fn offsets(&self) -> &'static [Vector2<i32>] {
+        match self {
+            SampleCount::X1 => &OFFSETS_X1,
+            SampleCount::X2 => &OFFSETS_X2,
+            SampleCount::X4 => &OFFSETS_X4,
+        }
+    }
+
+    /// Gets the number of subpixel steps per pixel that vertex positions are
+    /// snapped to. Without multisampling, vertices are snapped to whole
+    /// pixels.
+    pub(crate) const fn precision(&self) -> i32 {
+        match self {
+            SampleCount::X1 => 1,
+            SampleCount::X2 | SampleCount::X4 => 16,
+        }
+    }
+
+}
+
+/// Averages the samples of a pixel. Straight alpha samples are weighted by
+/// their alpha, so that the color of transparent samples doesn't bleed into
+/// the result.
+pub(crate) fn resolve_samples<P: PixelFormat>(samples: &[P], alpha_mode: AlphaMode) -> Pixel {
+    let mut sums = [0u32; 4];
+    for sample in samples {
+        let sample = match alpha_mode {
+            AlphaMode::Straight => sample.to_pixel().premultiplied(),
+            AlphaMode::Premultiplied => sample.to_pixel(),
+        };
+
+        sums[0] += sample.red() as u32;
+        sums[1] += sample.green() as u32;
+        sums[2] += sample.blue() as u32;
+        sums[3] += sample.alpha() as u32;
+    }
+
+    let count = samples.len() as u32;
+    let average = sums.map(|sum| ((sum + count / 2) / count) as u8);
+    let resolved = Pixel::new(average[0], average[1], average[2], average[3]);
+
+    match alpha_mode {
+        AlphaMode::Straight => resolved.unpremultiplied(),
+        AlphaMode::Premultiplied => resolved,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn offsets_are_inside_of_the_pixel() {
+        for sample_count in [SampleCount::X1, SampleCount::X2, SampleCount::X4] {
+            let offsets = sample_count.offsets();
+            assert_eq!(offsets.len(), sample_count.count());
+
+            let half = sample_count.precision() / 2;
+            assert!(offsets.iter().all(|offset| offset.x.abs() <= half && offset.y.abs() <= half));
+        }
+    }
+
+    #[test]
+    fn resolve_averages_samples() {
+        let samples = [Pixel::WHITE, Pixel::WHITE, Pixel::BLACK, Pixel::BLACK];
+        assert_eq!(resolve_samples(&samples, AlphaMode::Straight), Pixel::new(0x80, 0x80, 0x80, 0xFF));
+        assert_eq!(resolve_samples(&samples[..1], AlphaMode::Straight), Pixel::WHITE);
+
+        // A transparent sample only contributes its alpha.
+        let samples = [Pixel::new(0xFF, 0x00, 0x00, 0xFF), Pixel::new(0x00, 0xFF, 0x00, 0x00)];
+        assert_eq!(resolve_samples(&samples, AlphaMode::Straight), Pixel::new(0xFF, 0x00, 0x00, 0x80));
+        assert_eq!(resolve_samples(&samples, AlphaMode::Premultiplied), Pixel::new(0x80, 0x80, 0x00, 0x80));
+    }
+}
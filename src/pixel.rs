@@ -0,0 +1,820 @@
+// Copyright (C) 2023 Tristan Gerritsen <tristan@thewoosh.org>
+// All Rights Reserved.
+
+use std::fmt;
+
+/// An error that occurred whilst parsing a color string using
+/// [`Pixel::from_hex_str`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ParseColorError {
+
+    /// The string didn't start with a `#`.
+    MissingHashPrefix,
+
+    /// The amount of hex digits wasn't 3, 6 or 8.
+    InvalidLength(usize),
+
+    /// The string contained a character that isn't a hex digit.
+    InvalidCharacter(char),
+
+}
+
+impl fmt::Display for ParseColorError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseColorError::MissingHashPrefix => write!(f, "color must start with '#'"),
+            ParseColorError::InvalidLength(length) => {
+                write!(f, "color must have 3, 6 or 8 hex digits, but has {length}")
+            }
+            ParseColorError::InvalidCharacter(c) => write!(f, "color contains non-hex character {c:?}"),
+        }
+    }
+}
+
+impl std::error::Error for ParseColorError {}
+
+/// The color space in which color operations such as blending are performed.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub enum ColorSpace {
+
+    /// Operate directly on the gamma-encoded sRGB values of the pixels. This
+    /// is cheap, but darkens midtones.
+    #[default]
+    Srgb,
+
+    /// Decode the pixels to linear light before operating on them, and
+    /// encode the result back to sRGB afterwards.
+    Linear,
+
+}
+
+/// The way in which the color channels of a pixel relate to its alpha
+/// channel.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub enum AlphaMode {
+
+    /// The color channels are independent of the alpha channel.
+    #[default]
+    Straight,
+
+    /// The color channels are already multiplied by the alpha channel, so no
+    /// color channel is ever larger than the alpha channel.
+    Premultiplied,
+
+}
+
+impl AlphaMode {
+
+    /// Converts a pixel with straight alpha to this alpha mode.
+    pub fn encode(&self, pixel: Pixel) -> Pixel {
+        match self {
+            AlphaMode::Straight => pixel,
+            AlphaMode::Premultiplied => pixel.premultiplied(),
+        }
+    }
+
+    /// Converts a pixel in this alpha mode to straight alpha.
+    pub fn decode(&self, pixel: Pixel) -> Pixel {
+        match self {
+            AlphaMode::Straight => pixel,
+            AlphaMode::Premultiplied => pixel.unpremultiplied(),
+        }
+    }
+
+}
+
+/// The Porter-Duff operators that [composite](Pixel::composite) a source
+/// pixel with a destination pixel, by weighting both of them with a factor
+/// that depends on the alpha of the other one.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub enum PorterDuff {
+
+    /// Neither pixel is kept, which gives a fully transparent pixel.
+    Clear,
+
+    /// Only the source is kept.
+    Source,
+
+    /// Only the destination is kept.
+    Destination,
+
+    /// The source is placed over the destination.
+    #[default]
+    SourceOver,
+
+    /// The destination is placed over the source.
+    DestinationOver,
+
+    /// The source is kept where the destination is.
+    SourceIn,
+
+    /// The destination is kept where the source is.
+    DestinationIn,
+
+    /// The source is kept where the destination isn't.
+    SourceOut,
+
+    /// The destination is kept where the source isn't.
+    DestinationOut,
+
+    /// The source is placed over the destination where the destination is.
+    SourceAtop,
+
+    /// The destination is placed over the source where the source is.
+    DestinationAtop,
+
+    /// Both are kept where the other one isn't.
+    Xor,
+
+}
+
+impl PorterDuff {
+
+    /// Gets the factors of the source and the destination, given their
+    /// alphas, where `0xFF` means one.
+    fn factors(&self, source_alpha: u8, destination_alpha: u8) -> (u32, u32) {
+        let (source, destination) = (source_alpha as u32, destination_alpha as u32);
+        match self {
+            PorterDuff::Clear => (0, 0),
+            PorterDuff::Source => (0xFF, 0),
+            PorterDuff::Destination => (0, 0xFF),
+            PorterDuff::SourceOver => (0xFF, 0xFF - source),
+            PorterDuff::DestinationOver => (0xFF - destination, 0xFF),
+            PorterDuff::SourceIn => (destination, 0),
+            PorterDuff::DestinationIn => (0, source),
+            PorterDuff::SourceOut => (0xFF - destination, 0),
+            PorterDuff::DestinationOut => (0, 0xFF - source),
+            PorterDuff::SourceAtop => (destination, 0xFF - source),
+            PorterDuff::DestinationAtop => (0xFF - destination, source),
+            PorterDuff::Xor => (0xFF - destination, 0xFF - source),
+        }
+    }
+
+}
+
+/// Decodes a normalized sRGB value to linear light, using the standard sRGB
+/// transfer function.
+pub(crate) fn srgb_to_linear(value: f32) -> f32 {
+    if value <= 0.04045 {
+        value / 12.92
+    } else {
+        ((value + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+/// Encodes a linear light value to a normalized sRGB value, using the
+/// standard sRGB transfer function.
+pub(crate) fn linear_to_srgb(value: f32) -> f32 {
+    if value <= 0.0031308 {
+        value * 12.92
+    } else {
+        1.055 * value.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+/// Converts a normalized value to a byte, clamping it to the valid range.
+fn normalized_to_u8(value: f32) -> u8 {
+    (value.clamp(0.0, 1.0) * 255.0).round() as u8
+}
+
+/// Creates an opaque pixel from a hue in degrees, the chroma and the value
+/// that has to be added to every channel to match the lightness.
+fn pixel_from_hue_chroma(hue: f32, chroma: f32, offset: f32) -> Pixel {
+    let sector = hue.rem_euclid(360.0) / 60.0;
+    let x = chroma * (1.0 - (sector % 2.0 - 1.0).abs());
+
+    let (red, green, blue) = match sector as u32 {
+        0 => (chroma, x, 0.0),
+        1 => (x, chroma, 0.0),
+        2 => (0.0, chroma, x),
+        3 => (0.0, x, chroma),
+        4 => (x, 0.0, chroma),
+        _ => (chroma, 0.0, x),
+    };
+
+    Pixel::new(
+        normalized_to_u8(red + offset),
+        normalized_to_u8(green + offset),
+        normalized_to_u8(blue + offset),
+        0xFF,
+    )
+}
+
+/// A color with 8 bits per channel, of which the bytes are stored in memory
+/// in the order red, green, blue and alpha. Read as a little-endian `u32`,
+/// red is thus in the lowest byte, which is the layout that is handed to the
+/// presentation surfaces.
+#[repr(C, packed)]
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Pixel {
+    red: u8,
+    green: u8,
+    blue: u8,
+    alpha: u8,
+}
+
+impl Pixel {
+
+    pub const fn new(red: u8, green: u8, blue: u8, alpha: u8) -> Self {
+        Self { alpha, red, green, blue }
+    }
+
+    /// The color black.
+    pub const BLACK: Self = Pixel::new(0x00, 0x00, 0x00, 0xFF);
+
+    /// The color white.
+    pub const WHITE: Self = Pixel::new(0xFF, 0xFF, 0xFF, 0xFF);
+
+    /// The color red.
+    pub const RED: Self = Pixel::new(0xFF, 0x00, 0x00, 0xFF);
+
+    /// The color green.
+    pub const GREEN: Self = Pixel::new(0x00, 0xFF, 0x00, 0xFF);
+
+    /// The color blue.
+    pub const BLUE: Self = Pixel::new(0x00, 0x00, 0xFF, 0xFF);
+
+    /// A fully transparent pixel.
+    pub const TRANSPARENT: Self = Pixel::new(0x00, 0x00, 0x00, 0x00);
+
+    /// Creates a pixel from a `0xRRGGBBAA` value.
+    pub const fn from_u32(value: u32) -> Self {
+        Self::new((value >> 24) as u8, (value >> 16) as u8, (value >> 8) as u8, value as u8)
+    }
+
+    /// Converts the pixel to a `0xRRGGBBAA` value.
+    pub const fn to_u32(&self) -> u32 {
+        (self.red as u32) << 24 | (self.green as u32) << 16 | (self.blue as u32) << 8 | self.alpha as u32
+    }
+
+    /// Creates a pixel from bytes in the order blue, green, red and alpha, as
+    /// used by many windowing systems.
+    pub const fn from_bgra([blue, green, red, alpha]: [u8; 4]) -> Self {
+        Self::new(red, green, blue, alpha)
+    }
+
+    /// Converts the pixel to bytes in the order blue, green, red and alpha.
+    pub const fn to_bgra(&self) -> [u8; 4] {
+        [self.blue, self.green, self.red, self.alpha]
+    }
+
+    /// Parses a color in the `#rgb`, `#rrggbb` or `#rrggbbaa` format. When
+    /// the alpha channel is omitted, the color is fully opaque.
+    pub fn from_hex_str(string: &str) -> Result<Self, ParseColorError> {
+        let digits = string.strip_prefix('#').ok_or(ParseColorError::MissingHashPrefix)?;
+
+        if let Some(c) = digits.chars().find(|c| !c.is_ascii_hexdigit()) {
+            return Err(ParseColorError::InvalidCharacter(c));
+        }
+
+        // All characters are ASCII hex digits at this point, so this can't
+        // fail and every digit is exactly one byte.
+        let value = u32::from_str_radix(digits, 16).unwrap_or_default();
+
+        match digits.len() {
+            3 => {
+                let expand = |nibble: u32| (nibble & 0xF) as u8 * 0x11;
+                Ok(Self::new(expand(value >> 8), expand(value >> 4), expand(value), 0xFF))
+            }
+            6 => Ok(Self::from_u32(value << 8 | 0xFF)),
+            8 => Ok(Self::from_u32(value)),
+            length => Err(ParseColorError::InvalidLength(length)),
+        }
+    }
+
+    /// Formats the color as a `#rrggbbaa` string.
+    pub fn to_hex_string(&self) -> String {
+        format!("#{:08x}", self.to_u32())
+    }
+
+    /// Decodes the pixel to linear light, as normalized `[red, green, blue,
+    /// alpha]` values. Alpha isn't gamma-encoded, so it is only normalized.
+    pub fn to_linear(&self) -> [f32; 4] {
+        [
+            srgb_to_linear(self.red as f32 / 255.0),
+            srgb_to_linear(self.green as f32 / 255.0),
+            srgb_to_linear(self.blue as f32 / 255.0),
+            self.alpha as f32 / 255.0,
+        ]
+    }
+
+    /// Encodes normalized linear `[red, green, blue, alpha]` values to an sRGB
+    /// pixel. Values outside of `[0, 1]` are clamped.
+    pub fn from_linear(color: [f32; 4]) -> Self {
+        Self::new(
+            normalized_to_u8(linear_to_srgb(color[0])),
+            normalized_to_u8(linear_to_srgb(color[1])),
+            normalized_to_u8(linear_to_srgb(color[2])),
+            normalized_to_u8(color[3]),
+        )
+    }
+
+    /// Creates a pixel from normalized `[red, green, blue, alpha]` values,
+    /// without decoding them. Values outside of `[0, 1]` are clamped.
+    pub fn from_normalized(color: [f32; 4]) -> Self {
+        Self::new(
+            normalized_to_u8(color[0]),
+            normalized_to_u8(color[1]),
+            normalized_to_u8(color[2]),
+            normalized_to_u8(color[3]),
+        )
+    }
+
+    /// Gets the channels as normalized `[red, green, blue, alpha]` values,
+    /// which are still sRGB-encoded, unlike [`Pixel::to_linear`].
+    pub fn to_normalized(&self) -> [f32; 4] {
+        [self.red, self.green, self.blue, self.alpha].map(|channel| channel as f32 / 255.0)
+    }
+
+    /// Linearly interpolates between `a` and `b` per channel, directly on the
+    /// stored sRGB values. `t` is clamped to `[0, 1]`, where `0` yields `a`
+    /// and `1` yields `b`.
+    pub fn lerp(a: Pixel, b: Pixel, t: f32) -> Pixel {
+        let t = t.clamp(0.0, 1.0);
+        let channel = |a: u8, b: u8| (a as f32 + (b as f32 - a as f32) * t).round() as u8;
+        Pixel::new(
+            channel(a.red, b.red),
+            channel(a.green, b.green),
+            channel(a.blue, b.blue),
+            channel(a.alpha, b.alpha),
+        )
+    }
+
+    /// Checks whether every channel differs at most `tolerance` from that of
+    /// the other pixel, to compare colors that went through rounding, such as
+    /// interpolated or blended ones.
+    pub fn approx_eq(&self, other: Pixel, tolerance: u8) -> bool {
+        self.red.abs_diff(other.red) <= tolerance
+            && self.green.abs_diff(other.green) <= tolerance
+            && self.blue.abs_diff(other.blue) <= tolerance
+            && self.alpha.abs_diff(other.alpha) <= tolerance
+    }
+
+    /// Linearly interpolates between `a` and `b` per channel in the given
+    /// color space. See [`Pixel::lerp`].
+    pub fn lerp_in(color_space: ColorSpace, a: Pixel, b: Pixel, t: f32) -> Pixel {
+        match color_space {
+            ColorSpace::Srgb => Pixel::lerp(a, b, t),
+            ColorSpace::Linear => {
+                let t = t.clamp(0.0, 1.0);
+                let a = a.to_linear();
+                let b = b.to_linear();
+                Pixel::from_linear(std::array::from_fn(|i| a[i] + (b[i] - a[i]) * t))
+            }
+        }
+    }
+
+    /// Creates an opaque pixel from a hue in degrees `[0, 360)`, and a
+    /// saturation and value in `[0, 1]`.
+    pub fn from_hsv(hue: f32, saturation: f32, value: f32) -> Pixel {
+        let saturation = saturation.clamp(0.0, 1.0);
+        let value = value.clamp(0.0, 1.0);
+        let chroma = value * saturation;
+        pixel_from_hue_chroma(hue, chroma, value - chroma)
+    }
+
+    /// Creates an opaque pixel from a hue in degrees `[0, 360)`, and a
+    /// saturation and lightness in `[0, 1]`.
+    pub fn from_hsl(hue: f32, saturation: f32, lightness: f32) -> Pixel {
+        let saturation = saturation.clamp(0.0, 1.0);
+        let lightness = lightness.clamp(0.0, 1.0);
+        let chroma = (1.0 - (2.0 * lightness - 1.0).abs()) * saturation;
+        pixel_from_hue_chroma(hue, chroma, lightness - chroma / 2.0)
+    }
+
+    /// Gets the hue in degrees, the chroma, and the minimum and maximum of
+    /// the normalized color channels.
+    fn hue_chroma_min_max(&self) -> (f32, f32, f32, f32) {
+        let red = self.red as f32 / 255.0;
+        let green = self.green as f32 / 255.0;
+        let blue = self.blue as f32 / 255.0;
+
+        let max = red.max(green).max(blue);
+        let min = red.min(green).min(blue);
+        let chroma = max - min;
+
+        let hue = if chroma == 0.0 {
+            0.0
+        } else if max == red {
+            60.0 * ((green - blue) / chroma).rem_euclid(6.0)
+        } else if max == green {
+            60.0 * ((blue - red) / chroma + 2.0)
+        } else {
+            60.0 * ((red - green) / chroma + 4.0)
+        };
+
+        (hue, chroma, min, max)
+    }
+
+    /// Converts the color to a `(hue, saturation, value)` triple, with the
+    /// hue in degrees `[0, 360)`. The alpha channel is ignored.
+    pub fn to_hsv(&self) -> (f32, f32, f32) {
+        let (hue, chroma, _, max) = self.hue_chroma_min_max();
+        let saturation = if max == 0.0 { 0.0 } else { chroma / max };
+        (hue, saturation, max)
+    }
+
+    /// Converts the color to a `(hue, saturation, lightness)` triple, with
+    /// the hue in degrees `[0, 360)`. The alpha channel is ignored.
+    pub fn to_hsl(&self) -> (f32, f32, f32) {
+        let (hue, chroma, min, max) = self.hue_chroma_min_max();
+        let lightness = (max + min) / 2.0;
+        let saturation = if chroma == 0.0 {
+            0.0
+        } else {
+            chroma / (1.0 - (2.0 * lightness - 1.0).abs())
+        };
+        (hue, saturation, lightness)
+    }
+
+    /// Gets the relative luminance in `[0, 1]`, which is the weighted sum of
+    /// the channels in linear light, with the Rec. 709 weights. The alpha
+    /// channel is ignored.
+    pub fn luminance(&self) -> f32 {
+        let [red, green, blue, _] = self.to_linear();
+        0.2126 * red + 0.7152 * green + 0.0722 * blue
+    }
+
+    /// Converts the color to the gray of the same [luminance](Pixel::luminance),
+    /// keeping the alpha channel.
+    pub fn grayscale(&self) -> Pixel {
+        let luminance = self.luminance();
+        Pixel::from_linear([luminance, luminance, luminance, self.alpha as f32 / 255.0])
+    }
+
+    /// Multiplies the color channels by the alpha channel.
+    pub fn premultiplied(&self) -> Pixel {
+        let alpha = self.alpha as u32;
+        let channel = |value: u8| ((value as u32 * alpha + 127) / 255) as u8;
+        Pixel::new(channel(self.red), channel(self.green), channel(self.blue), self.alpha)
+    }
+
+    /// Composites the source over the destination with the Porter-Duff
+    /// operator. Both pixels, and the result, have premultiplied alpha, so
+    /// every channel, including alpha, is the sum of the channels of the
+    /// pixels weighted by the factors of the operator.
+    pub fn composite(source: Pixel, destination: Pixel, operator: PorterDuff) -> Pixel {
+        let (source_factor, destination_factor) = operator.factors(source.alpha, destination.alpha);
+        let channel = |source: u8, destination: u8| {
+            let sum = source as u32 * source_factor + destination as u32 * destination_factor;
+            ((sum + 127) / 255).min(0xFF) as u8
+        };
+        Pixel::new(
+            channel(source.red, destination.red),
+            channel(source.green, destination.green),
+            channel(source.blue, destination.blue),
+            channel(source.alpha, destination.alpha),
+        )
+    }
+
+    /// Divides the color channels by the alpha channel, which is the inverse
+    /// of [`Pixel::premultiplied`]. Fully transparent pixels become
+    /// [`Pixel::TRANSPARENT`], since their color is lost.
+    pub fn unpremultiplied(&self) -> Pixel {
+        let alpha = self.alpha as u32;
+        if alpha == 0 {
+            return Pixel::TRANSPARENT;
+        }
+
+        let channel = |value: u8| ((value as u32 * 255 + alpha / 2) / alpha).min(0xFF) as u8;
+        Pixel::new(channel(self.red), channel(self.green), channel(self.blue), self.alpha)
+    }
+
+    // The struct is packed, so the accessors below return copies of the
+    // fields instead of ever creating references to them.
+
+    /// Gets the red channel.
+    #[inline]
+    pub const fn red(&self) -> u8 {
+        self.red
+    }
+
+    /// Gets the green channel.
+    #[inline]
+    pub const fn green(&self) -> u8 {
+        self.green
+    }
+
+    /// Gets the blue channel.
+    #[inline]
+    pub const fn blue(&self) -> u8 {
+        self.blue
+    }
+
+    /// Gets the alpha channel.
+    #[inline]
+    pub const fn alpha(&self) -> u8 {
+        self.alpha
+    }
+
+    /// Returns this pixel with the red channel replaced.
+    #[inline]
+    pub const fn with_red(self, red: u8) -> Self {
+        Self { red, ..self }
+    }
+
+    /// Returns this pixel with the green channel replaced.
+    #[inline]
+    pub const fn with_green(self, green: u8) -> Self {
+        Self { green, ..self }
+    }
+
+    /// Returns this pixel with the blue channel replaced.
+    #[inline]
+    pub const fn with_blue(self, blue: u8) -> Self {
+        Self { blue, ..self }
+    }
+
+    /// Returns this pixel with the alpha channel replaced.
+    #[inline]
+    pub const fn with_alpha(self, alpha: u8) -> Self {
+        Self { alpha, ..self }
+    }
+
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn channel_accessors() {
+        let pixel = Pixel::new(0x12, 0x34, 0x56, 0x78);
+        assert_eq!(pixel.red(), 0x12);
+        assert_eq!(pixel.green(), 0x34);
+        assert_eq!(pixel.blue(), 0x56);
+        assert_eq!(pixel.alpha(), 0x78);
+    }
+
+    #[test]
+    fn composite_porter_duff() {
+        let source = Pixel::new(0x80, 0x00, 0x00, 0x80);
+        let destination = Pixel::new(0x00, 0x00, 0xFF, 0xFF);
+        let composite = |operator| Pixel::composite(source, destination, operator);
+
+        assert_eq!(composite(PorterDuff::Clear), Pixel::TRANSPARENT);
+        assert_eq!(composite(PorterDuff::Source), source);
+        assert_eq!(composite(PorterDuff::Destination), destination);
+        assert_eq!(composite(PorterDuff::SourceOver), Pixel::new(0x80, 0x00, 0x7F, 0xFF));
+        assert_eq!(composite(PorterDuff::DestinationOver), destination);
+        assert_eq!(composite(PorterDuff::SourceIn), source);
+        assert_eq!(composite(PorterDuff::DestinationIn), Pixel::new(0x00, 0x00, 0x80, 0x80));
+        assert_eq!(composite(PorterDuff::SourceOut), Pixel::TRANSPARENT);
+        assert_eq!(composite(PorterDuff::DestinationOut), Pixel::new(0x00, 0x00, 0x7F, 0x7F));
+        assert_eq!(composite(PorterDuff::SourceAtop), Pixel::new(0x80, 0x00, 0x7F, 0xFF));
+        assert_eq!(composite(PorterDuff::DestinationAtop), Pixel::new(0x00, 0x00, 0x80, 0x80));
+        assert_eq!(composite(PorterDuff::Xor), Pixel::new(0x00, 0x00, 0x7F, 0x7F));
+
+        // With a translucent destination, both pixels are kept by xor:
+        // 0x80 * 0xBF / 0xFF for red, and 0x40 * 0x7F / 0xFF for green.
+        let destination = Pixel::new(0x00, 0x40, 0x00, 0x40);
+        assert_eq!(Pixel::composite(source, destination, PorterDuff::Xor), Pixel::new(0x60, 0x20, 0x00, 0x80));
+        assert_eq!(Pixel::composite(source, destination, PorterDuff::SourceOver),
+            Pixel::new(0x80, 0x20, 0x00, 0xA0));
+    }
+
+    fn channels(pixel: Pixel) -> [u8; 4] {
+        [pixel.red(), pixel.green(), pixel.blue(), pixel.alpha()]
+    }
+
+    #[test]
+    fn color_constants() {
+        assert_eq!(channels(Pixel::BLACK), [0x00, 0x00, 0x00, 0xFF]);
+        assert_eq!(channels(Pixel::WHITE), [0xFF, 0xFF, 0xFF, 0xFF]);
+        assert_eq!(channels(Pixel::RED), [0xFF, 0x00, 0x00, 0xFF]);
+        assert_eq!(channels(Pixel::GREEN), [0x00, 0xFF, 0x00, 0xFF]);
+        assert_eq!(channels(Pixel::BLUE), [0x00, 0x00, 0xFF, 0xFF]);
+        assert_eq!(channels(Pixel::TRANSPARENT), [0x00, 0x00, 0x00, 0x00]);
+    }
+
+    #[test]
+    fn bgra_round_trip() {
+        let pixel = Pixel::new(0x12, 0x34, 0x56, 0x78);
+        assert_eq!(pixel.to_bgra(), [0x56, 0x34, 0x12, 0x78]);
+        assert_eq!(Pixel::from_bgra([0x56, 0x34, 0x12, 0x78]), pixel);
+        assert_eq!(Pixel::RED.to_bgra(), [0x00, 0x00, 0xFF, 0xFF]);
+    }
+
+    #[test]
+    fn memory_layout_is_rgba() {
+        let pixel = Pixel::new(0x12, 0x34, 0x56, 0x78);
+        let bytes: [u8; 4] = unsafe { std::mem::transmute(pixel) };
+        assert_eq!(bytes, [0x12, 0x34, 0x56, 0x78]);
+    }
+
+    #[test]
+    fn u32_round_trip() {
+        let pixel = Pixel::from_u32(0x12345678);
+        assert_eq!(channels(pixel), [0x12, 0x34, 0x56, 0x78]);
+        assert_eq!(pixel.to_u32(), 0x12345678);
+    }
+
+    #[test]
+    fn from_hex_str_formats() {
+        assert_eq!(Pixel::from_hex_str("#f80"), Ok(Pixel::new(0xFF, 0x88, 0x00, 0xFF)));
+        assert_eq!(Pixel::from_hex_str("#30A7F8"), Ok(Pixel::new(0x30, 0xA7, 0xF8, 0xFF)));
+        assert_eq!(Pixel::from_hex_str("#30a7f880"), Ok(Pixel::new(0x30, 0xA7, 0xF8, 0x80)));
+    }
+
+    #[test]
+    fn from_hex_str_malformed() {
+        assert_eq!(Pixel::from_hex_str("30a7f8"), Err(ParseColorError::MissingHashPrefix));
+        assert_eq!(Pixel::from_hex_str("#"), Err(ParseColorError::InvalidLength(0)));
+        assert_eq!(Pixel::from_hex_str("#30a7f"), Err(ParseColorError::InvalidLength(5)));
+        assert_eq!(Pixel::from_hex_str("#30a7f8801"), Err(ParseColorError::InvalidLength(9)));
+        assert_eq!(Pixel::from_hex_str("#30g7f8"), Err(ParseColorError::InvalidCharacter('g')));
+        assert_eq!(Pixel::from_hex_str("#+30a7f"), Err(ParseColorError::InvalidCharacter('+')));
+    }
+
+    #[test]
+    fn to_hex_string() {
+        assert_eq!(Pixel::new(0x30, 0xA7, 0xF8, 0xFF).to_hex_string(), "#30a7f8ff");
+        assert_eq!(Pixel::TRANSPARENT.to_hex_string(), "#00000000");
+
+        let pixel = Pixel::from_u32(0xDEADBEEF);
+        assert_eq!(Pixel::from_hex_str(&pixel.to_hex_string()), Ok(pixel));
+    }
+
+    #[test]
+    fn linear_round_trip() {
+        for value in 0..=255 {
+            let pixel = Pixel::new(value, value, value, value);
+            assert_eq!(Pixel::from_linear(pixel.to_linear()), pixel);
+        }
+    }
+
+    #[test]
+    fn normalized_round_trip() {
+        for value in 0..=255 {
+            let pixel = Pixel::new(value, 255 - value, value / 2, value);
+            assert_eq!(Pixel::from_normalized(pixel.to_normalized()), pixel);
+        }
+        assert_eq!(Pixel::new(0xFF, 0x00, 0x80, 0x33).to_normalized(), [1.0, 0.0, 128.0 / 255.0, 0.2]);
+        assert_eq!(Pixel::from_normalized([2.0, -1.0, 0.5, 1.0]), Pixel::new(0xFF, 0x00, 0x80, 0xFF));
+    }
+
+    #[test]
+    fn linear_gray_differs_from_srgb_gray() {
+        let linear_gray = Pixel::from_linear([0.5, 0.5, 0.5, 1.0]);
+        assert_eq!(linear_gray, Pixel::new(0xBC, 0xBC, 0xBC, 0xFF));
+
+        let srgb_gray = Pixel::new(0x80, 0x80, 0x80, 0xFF).to_linear();
+        assert!((srgb_gray[0] - 0.2158).abs() < 0.001);
+        assert_eq!(srgb_gray[3], 1.0);
+    }
+
+    #[test]
+    fn lerp() {
+        let a = Pixel::new(0x00, 0x40, 0xFF, 0x00);
+        let b = Pixel::new(0xFF, 0x80, 0x00, 0xFF);
+        assert_eq!(Pixel::lerp(a, b, 0.0), a);
+        assert_eq!(Pixel::lerp(a, b, 1.0), b);
+        assert_eq!(Pixel::lerp(a, b, 0.5), Pixel::new(0x80, 0x60, 0x80, 0x80));
+
+        assert_eq!(Pixel::lerp(a, b, -1.0), a);
+        assert_eq!(Pixel::lerp(a, b, 2.0), b);
+    }
+
+    #[test]
+    fn approx_eq() {
+        let pixel = Pixel::new(0x80, 0x40, 0x20, 0xFF);
+        assert!(pixel.approx_eq(pixel, 0));
+        assert!(pixel.approx_eq(Pixel::new(0x81, 0x3F, 0x20, 0xFE), 1));
+        assert!(!pixel.approx_eq(Pixel::new(0x81, 0x40, 0x20, 0xFF), 0));
+        assert!(!pixel.approx_eq(Pixel::new(0x80, 0x40, 0x20, 0x00), 0x80));
+        assert!(Pixel::BLACK.approx_eq(Pixel::WHITE, 0xFF));
+    }
+
+    #[test]
+    fn lerp_in_linear_space() {
+        let linear = ColorSpace::Linear;
+        assert_eq!(Pixel::lerp_in(linear, Pixel::BLACK, Pixel::WHITE, 0.0), Pixel::BLACK);
+        assert_eq!(Pixel::lerp_in(linear, Pixel::BLACK, Pixel::WHITE, 1.0), Pixel::WHITE);
+        assert_eq!(Pixel::lerp_in(linear, Pixel::BLACK, Pixel::WHITE, 0.5),
+            Pixel::new(0xBC, 0xBC, 0xBC, 0xFF));
+        assert_eq!(Pixel::lerp_in(ColorSpace::Srgb, Pixel::BLACK, Pixel::WHITE, 0.5),
+            Pixel::new(0x80, 0x80, 0x80, 0xFF));
+    }
+
+    #[test]
+    fn hsv_primaries() {
+        assert_eq!(Pixel::RED.to_hsv(), (0.0, 1.0, 1.0));
+        assert_eq!(Pixel::GREEN.to_hsv(), (120.0, 1.0, 1.0));
+        assert_eq!(Pixel::BLUE.to_hsv(), (240.0, 1.0, 1.0));
+
+        assert_eq!(Pixel::from_hsv(0.0, 1.0, 1.0), Pixel::RED);
+        assert_eq!(Pixel::from_hsv(120.0, 1.0, 1.0), Pixel::GREEN);
+        assert_eq!(Pixel::from_hsv(240.0, 1.0, 1.0), Pixel::BLUE);
+        assert_eq!(Pixel::from_hsv(360.0, 1.0, 1.0), Pixel::RED);
+    }
+
+    #[test]
+    fn hsl_primaries() {
+        assert_eq!(Pixel::RED.to_hsl(), (0.0, 1.0, 0.5));
+        assert_eq!(Pixel::GREEN.to_hsl(), (120.0, 1.0, 0.5));
+        assert_eq!(Pixel::BLUE.to_hsl(), (240.0, 1.0, 0.5));
+
+        assert_eq!(Pixel::from_hsl(0.0, 1.0, 0.5), Pixel::RED);
+        assert_eq!(Pixel::from_hsl(120.0, 1.0, 0.5), Pixel::GREEN);
+        assert_eq!(Pixel::from_hsl(240.0, 1.0, 0.5), Pixel::BLUE);
+    }
+
+    #[test]
+    fn hsv_hsl_gray() {
+        let gray = Pixel::new(0x80, 0x80, 0x80, 0x40);
+
+        let (hue, saturation, value) = gray.to_hsv();
+        assert_eq!((hue, saturation), (0.0, 0.0));
+        assert_eq!(Pixel::from_hsv(hue, saturation, value).with_alpha(gray.alpha()), gray);
+
+        let (hue, saturation, lightness) = gray.to_hsl();
+        assert_eq!((hue, saturation), (0.0, 0.0));
+        assert_eq!(Pixel::from_hsl(hue, saturation, lightness).with_alpha(gray.alpha()), gray);
+    }
+
+    #[test]
+    fn hsv_hsl_round_trip() {
+        let pixel = Pixel::new(0x30, 0xA7, 0xF8, 0xFF);
+
+        let (hue, saturation, value) = pixel.to_hsv();
+        assert_eq!(Pixel::from_hsv(hue, saturation, value), pixel);
+
+        let (hue, saturation, lightness) = pixel.to_hsl();
+        assert_eq!(Pixel::from_hsl(hue, saturation, lightness), pixel);
+    }
+
+    #[test]
+    fn luminance_of_pure_colors() {
+        let close = |a: f32, b: f32| (a - b).abs() < 1e-6;
+        assert!(close(Pixel::RED.luminance(), 0.2126));
+        assert!(close(Pixel::GREEN.luminance(), 0.7152));
+        assert!(close(Pixel::BLUE.luminance(), 0.0722));
+        assert!(close(Pixel::WHITE.luminance(), 1.0));
+        assert_eq!(Pixel::BLACK.luminance(), 0.0);
+    }
+
+    #[test]
+    fn grayscale() {
+        assert_eq!(Pixel::WHITE.grayscale(), Pixel::WHITE);
+        assert_eq!(Pixel::new(0x00, 0x00, 0x00, 0x40).grayscale(), Pixel::new(0x00, 0x00, 0x00, 0x40));
+        assert_eq!(Pixel::new(0x80, 0x80, 0x80, 0x12).grayscale(), Pixel::new(0x80, 0x80, 0x80, 0x12));
+
+        // Green is much brighter than blue of the same value.
+        let green = Pixel::GREEN.grayscale();
+        let blue = Pixel::BLUE.grayscale();
+        assert_eq!(green.red(), green.green());
+        assert_eq!(green.green(), green.blue());
+        assert!(green.red() > blue.red());
+    }
+
+    #[test]
+    fn premultiplied() {
+        let straight = Pixel::new(0xFF, 0x80, 0x00, 0x80);
+        let premultiplied = Pixel::new(0x80, 0x40, 0x00, 0x80);
+        assert_eq!(straight.premultiplied(), premultiplied);
+        assert_eq!(premultiplied.unpremultiplied(), straight);
+        assert_eq!(Pixel::new(0xFF, 0x80, 0x00, 0x00).premultiplied(), Pixel::TRANSPARENT);
+        assert_eq!(Pixel::WHITE.premultiplied(), Pixel::WHITE);
+        assert_eq!(Pixel::new(0x80, 0x40, 0x00, 0x00).unpremultiplied(), Pixel::TRANSPARENT);
+    }
+
+    #[test]
+    fn premultiplied_round_trip() {
+        fn within_one(a: Pixel, b: Pixel) -> bool {
+            channels(a).iter().zip(channels(b)).all(|(a, b)| a.abs_diff(b) <= 1)
+        }
+
+        for alpha in 0..=255 {
+            for value in 0..=alpha {
+                let pixel = Pixel::new(value, value / 2, 0, alpha);
+                assert!(within_one(pixel.unpremultiplied().premultiplied(), pixel), "{pixel:?}");
+            }
+        }
+
+        // Straight colors lose precision proportional to the transparency, so
+        // only mostly opaque pixels round-trip within 1 LSB.
+        for alpha in 0x80..=0xFF {
+            for value in 0..=255 {
+                let pixel = Pixel::new(value, 255 - value, value / 2, alpha);
+                assert!(within_one(pixel.premultiplied().unpremultiplied(), pixel), "{pixel:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn channel_builders() {
+        let pixel = Pixel::new(0x00, 0x00, 0x00, 0x00)
+            .with_red(0xAB)
+            .with_green(0xCD)
+            .with_blue(0xEF)
+            .with_alpha(0x01);
+        assert_eq!(pixel, Pixel::new(0xAB, 0xCD, 0xEF, 0x01));
+
+        let pixel = pixel.with_green(0x42);
+        assert_eq!(pixel.red(), 0xAB);
+        assert_eq!(pixel.green(), 0x42);
+        assert_eq!(pixel.blue(), 0xEF);
+        assert_eq!(pixel.alpha(), 0x01);
+    }
+}
@@ -0,0 +1,258 @@
+// Copyright (C) 2023 Tristan Gerritsen <tristan@thewoosh.org>
+// All Rights Reserved.
+
+use crate::{math::{Rectangle2D, Vector2}, Pixel, swap_chain::Extent};
+
+use super::{PresentationSurface, SurfaceOrientation, SurfacePresentationError};
+
+/// A surface that copies the presented pixels into memory, instead of showing
+/// them in a window. Useful for offscreen rendering and testing.
+#[derive(Clone, Debug)]
+pub struct MemorySurface {
+    extent: Extent,
+    pixels: Vec<Pixel>,
+    orientation: SurfaceOrientation,
+}
+
+impl MemorySurface {
+
+    /// Creates a surface to which nothing has been presented yet.
+    pub fn new() -> Self {
+        Self {
+            extent: Extent { width: 0, height: 0 },
+            pixels: Vec::new(),
+            orientation: SurfaceOrientation::default(),
+        }
+    }
+
+    /// Gets the extent of the image that was presented last.
+    pub fn extent(&self) -> Extent {
+        self.extent
+    }
+
+    /// Gets the order in which the rows of presented buffers are stored.
+    pub fn orientation(&self) -> SurfaceOrientation {
+        self.orientation
+    }
+
+    /// Gets the pixels of the image that was presented last, with the top
+    /// row first, regardless of the orientation.
+    pub fn pixels(&self) -> &[Pixel] {
+        &self.pixels
+    }
+
+    /// Presents the source rectangle of the buffer, in pixels, scaled to the
+    /// destination rectangle of the image using the nearest pixels, like the
+    /// surface of a window does. The rest of the image is kept as it is. When
+    /// nothing was presented yet, the image gets the extent of the buffer,
+    /// and is transparent outside of the destination rectangle.
+    pub fn present_region(&mut self, buffer: &[Pixel], extent: Extent, src_rect: Rectangle2D<i32>,
+            dst_rect: Rectangle2D<i32>) -> Result<(), SurfacePresentationError> {
+        if extent.width.checked_mul(extent.height) != Some(buffer.len()) {
+            return Err(SurfacePresentationError::BufferSizeMismatch);
+        }
+        let is_empty = |rect: &Rectangle2D<i32>| rect.width() <= 0 || rect.height() <= 0;
+        if is_empty(&src_rect) || is_empty(&dst_rect) {
+            return Ok(());
+        }
+        if bounds(extent).intersect(&src_rect) != Some(src_rect) {
+            return Err(SurfacePresentationError::RegionOutOfBounds);
+        }
+
+        if self.pixels.is_empty() {
+            self.extent = extent;
+            self.pixels = vec![Pixel::TRANSPARENT; buffer.len()];
+        }
+        let Some(visible) = bounds(self.extent).intersect(&dst_rect) else {
+            return Ok(());
+        };
+
+        let scale = |offset: i32, src: i32, dst: i32| (offset as i64 * src as i64 / dst as i64) as i32;
+        for y in visible.y_range() {
+            let source_y = src_rect.y() + scale(y - dst_rect.y(), src_rect.height(), dst_rect.height());
+            let source_y = source_y as usize;
+            let source_row = match self.orientation {
+                SurfaceOrientation::TopDown => source_y,
+                SurfaceOrientation::BottomUp => extent.height - 1 - source_y,
+            };
+            for x in visible.x_range() {
+                let source_x = src_rect.x() + scale(x - dst_rect.x(), src_rect.width(), dst_rect.width());
+                self.pixels[y as usize * self.extent.width + x as usize] =
+                    buffer[source_row * extent.width + source_x as usize];
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Sets the order in which the rows of presented buffers are stored.
+    pub fn set_orientation(&mut self, orientation: SurfaceOrientation) {
+        self.orientation = orientation;
+    }
+
+}
+
+impl Default for MemorySurface {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PresentationSurface for MemorySurface {
+
+    fn present(&mut self, buffer: &[Pixel], extent: Extent) -> Result<(), SurfacePresentationError> {
+        if extent.width.checked_mul(extent.height) != Some(buffer.len()) {
+            return Err(SurfacePresentationError::BufferSizeMismatch);
+        }
+
+        self.extent = extent;
+        self.pixels.clear();
+        match self.orientation {
+            SurfaceOrientation::TopDown => self.pixels.extend_from_slice(buffer),
+            SurfaceOrientation::BottomUp => {
+                for row in buffer.chunks_exact(extent.width).rev() {
+                    self.pixels.extend_from_slice(row);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn present_rect(&mut self, buffer: &[Pixel], extent: Extent, rect: Rectangle2D<i32>)
+            -> Result<(), SurfacePresentationError> {
+        if extent != self.extent || self.pixels.is_empty() {
+            return self.present(buffer, extent);
+        }
+
+        match bounds(extent).intersect(&rect) {
+            Some(rect) => self.present_region(buffer, extent, rect, rect),
+            None => Ok(()),
+        }
+    }
+
+}
+
+/// Gets the rectangle covering the extent.
+fn bounds(extent: Extent) -> Rectangle2D<i32> {
+    let width = extent.width.min(i32::MAX as usize) as i32;
+    let height = extent.height.min(i32::MAX as usize) as i32;
+    Rectangle2D::new(Vector2::new(0, 0), Vector2::new(width, height))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::swap_chain::SwapChain;
+
+    const EXTENT: Extent = Extent { width: 3, height: 4 };
+
+    /// An image which gets brighter from the top to the bottom row.
+    fn upright_gradient() -> Vec<Pixel> {
+        (0..EXTENT.height)
+            .flat_map(|y| {
+                let value = (y * 0xFF / (EXTENT.height - 1)) as u8;
+                [Pixel::new(value, value, value, 0xFF); EXTENT.width]
+            })
+            .collect()
+    }
+
+    #[test]
+    fn gradient_is_upright_in_both_orientations() {
+        let gradient = upright_gradient();
+        let mut surface = MemorySurface::new();
+
+        surface.present(&gradient, EXTENT).unwrap();
+        assert_eq!(surface.pixels(), gradient);
+
+        // The same image stored bottom-up has its rows reversed.
+        let bottom_up: Vec<Pixel> = gradient.chunks_exact(EXTENT.width).rev().flatten().copied().collect();
+        surface.set_orientation(SurfaceOrientation::BottomUp);
+        surface.present(&bottom_up, EXTENT).unwrap();
+        assert_eq!(surface.pixels(), gradient);
+        assert_eq!(surface.pixels()[0], Pixel::BLACK);
+        assert_eq!(surface.extent(), EXTENT);
+    }
+
+    #[test]
+    fn present_rect_copies_only_rect() {
+        let gradient = upright_gradient();
+        let mut surface = MemorySurface::new();
+
+        // Without a previous image, the whole buffer is presented.
+        let rect = Rectangle2D::new(Vector2::new(1, 1), Vector2::new(3, 2));
+        surface.present_rect(&gradient, EXTENT, rect).unwrap();
+        assert_eq!(surface.pixels(), gradient);
+
+        let white = [Pixel::WHITE; 12];
+        surface.present_rect(&white, EXTENT, rect).unwrap();
+        let mut expected = gradient.clone();
+        expected[4] = Pixel::WHITE;
+        expected[5] = Pixel::WHITE;
+        assert_eq!(surface.pixels(), expected);
+
+        surface.set_orientation(SurfaceOrientation::BottomUp);
+        let mut bottom_up = [Pixel::BLACK; 12];
+        bottom_up[3 * EXTENT.width] = Pixel::RED;
+        let corner = Rectangle2D::new(Vector2::new(0, 0), Vector2::new(1, 1));
+        surface.present_rect(&bottom_up, EXTENT, corner).unwrap();
+        expected[0] = Pixel::RED;
+        assert_eq!(surface.pixels(), expected);
+    }
+
+    #[test]
+    fn present_region_copies_source_region() {
+        let gradient = upright_gradient();
+        let mut surface = MemorySurface::new();
+        surface.present(&[Pixel::BLUE; 12], EXTENT).unwrap();
+
+        // The middle pixel of the second and third row is copied to the
+        // bottom left, and scaled to two pixels wide.
+        let source = Rectangle2D::new(Vector2::new(1, 1), Vector2::new(2, 3));
+        let destination = Rectangle2D::new(Vector2::new(0, 2), Vector2::new(2, 4));
+        surface.present_region(&gradient, EXTENT, source, destination).unwrap();
+
+        let mut expected = vec![Pixel::BLUE; 12];
+        expected[6] = gradient[4];
+        expected[7] = gradient[4];
+        expected[9] = gradient[7];
+        expected[10] = gradient[7];
+        assert_eq!(surface.pixels(), expected);
+
+        // The destination is clipped, while the source must be inside.
+        let outside = Rectangle2D::new(Vector2::new(2, 3), Vector2::new(4, 4));
+        surface.present_region(&gradient, EXTENT, outside, destination).unwrap_err();
+        surface.present_region(&gradient, EXTENT, source, outside).unwrap();
+        expected[11] = gradient[4];
+        assert_eq!(surface.pixels(), expected);
+    }
+
+    /// Creating, drawing, presenting and resizing only needs an extent, so
+    /// this also runs without the `winit` feature.
+    #[test]
+    fn headless_swap_chain() {
+        let mut swap_chain = SwapChain::with_extent(EXTENT);
+        swap_chain.clear(Pixel::BLUE);
+        swap_chain.fill_rect(Rectangle2D::new(Vector2::new(1, 1), Vector2::new(3, 2)), Pixel::WHITE);
+
+        let mut surface = MemorySurface::new();
+        swap_chain.present(&mut surface).unwrap();
+        let mut expected = vec![Pixel::BLUE; 12];
+        expected[4] = Pixel::WHITE;
+        expected[5] = Pixel::WHITE;
+        assert_eq!(surface.pixels(), expected);
+
+        let extent = Extent { width: 2, height: 2 };
+        swap_chain.resize(extent, Pixel::RED);
+        swap_chain.present(&mut surface).unwrap();
+        assert_eq!(surface.extent(), extent);
+        assert_eq!(surface.pixels(), [Pixel::RED; 4]);
+    }
+
+    #[test]
+    fn mismatched_buffer_is_rejected() {
+        let mut surface = MemorySurface::new();
+        let result = surface.present(&[Pixel::BLACK; 11], EXTENT);
+        assert!(matches!(result, Err(SurfacePresentationError::BufferSizeMismatch)));
+    }
+}
@@ -1,8 +1,102 @@
 // Copyright (C) 2023 Tristan Gerritsen <tristan@thewoosh.org>
 // All Rights Reserved.
 
-#[cfg(windows)]
+pub mod memory;
+
+#[cfg(all(windows, feature = "win32"))]
 pub mod win32;
 
-#[cfg(windows)]
+pub use memory::MemorySurface;
+
+#[cfg(all(windows, feature = "win32"))]
 pub use win32::Surface;
+
+use std::fmt;
+
+use crate::{math::Rectangle2D, Pixel, swap_chain::Extent};
+
+#[derive(Debug)]
+pub enum SurfaceCreationError {
+
+    /// The given window handle is not valid.
+    InvalidHandle,
+
+    /// The current platform is not supported.
+    UnsupportedPlatform,
+
+}
+
+impl fmt::Display for SurfaceCreationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SurfaceCreationError::InvalidHandle => write!(f, "the window handle is not valid"),
+            SurfaceCreationError::UnsupportedPlatform => write!(f, "the platform is not supported"),
+        }
+    }
+}
+
+impl std::error::Error for SurfaceCreationError {}
+
+#[derive(Debug)]
+pub enum SurfacePresentationError {
+
+    /// The given image to present was too large to present.
+    ImageTooLarge,
+
+    /// The number of pixels in the buffer doesn't match the extent.
+    BufferSizeMismatch,
+
+    /// The region of the buffer to present doesn't lie inside of it.
+    RegionOutOfBounds,
+
+}
+
+impl fmt::Display for SurfacePresentationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SurfacePresentationError::ImageTooLarge => write!(f, "the image is too large to present"),
+            SurfacePresentationError::BufferSizeMismatch => {
+                write!(f, "the number of pixels doesn't match the extent")
+            }
+            SurfacePresentationError::RegionOutOfBounds => {
+                write!(f, "the region to present doesn't lie inside of the buffer")
+            }
+        }
+    }
+}
+
+impl std::error::Error for SurfacePresentationError {}
+
+/// The order in which the rows of a presented buffer are stored.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub enum SurfaceOrientation {
+
+    /// The first row of the buffer is the top row of the image, which is how
+    /// the swap chain stores its pixels.
+    #[default]
+    TopDown,
+
+    /// The first row of the buffer is the bottom row of the image, which is
+    /// common when using OpenGL-style coordinates, where Y points upwards.
+    BottomUp,
+
+}
+
+/// Something that a swap chain can present its pixels to.
+pub trait PresentationSurface {
+
+    /// Presents the buffer, of which the rows are stored in the orientation
+    /// of the surface.
+    fn present(&mut self, buffer: &[Pixel], extent: Extent) -> Result<(), SurfacePresentationError>;
+
+    /// Presents the part of the buffer inside of the rectangle, in pixels,
+    /// when the rest of it is unchanged since the last present. Surfaces that
+    /// can't update part of their image present the whole buffer, which is
+    /// what this does by default.
+    fn present_rect(&mut self, buffer: &[Pixel], extent: Extent, rect: Rectangle2D<i32>)
+            -> Result<(), SurfacePresentationError> {
+        let _ = rect;
+        self.present(buffer, extent)
+    }
+
+}
@@ -1,35 +1,22 @@
 // Copyright (C) 2023 Tristan Gerritsen <tristan@thewoosh.org>
 // All Rights Reserved.
 
+#[cfg(feature = "winit")]
 use raw_window_handle::{HasRawWindowHandle, RawWindowHandle};
-use windows::Win32::Foundation::{HWND, GetLastError};
+use windows::Win32::Foundation::{HWND, GetLastError, RECT};
 use windows::Win32::Graphics::Gdi::{
     GetDC, StretchDIBits, ValidateRect, BITMAPINFOHEADER, BI_BITFIELDS, DIB_RGB_COLORS, HDC,
     RGBQUAD, SRCCOPY, GDI_ERROR,
 };
 use windows::Win32::UI::WindowsAndMessaging::IsWindow;
+#[cfg(feature = "winit")]
 use winit::window::Window;
 
-use crate::{Pixel, swap_chain};
+use crate::{math::{Rectangle2D, Vector2}, Pixel, swap_chain};
 
-#[derive(Debug)]
-pub enum SurfaceCreationError {
+use super::{PresentationSurface, SurfaceOrientation};
 
-    /// The given window handle is not valid.
-    InvalidHandle,
-
-    /// The current platform is not supported.
-    UnsupportedPlatform,
-
-}
-
-#[derive(Debug)]
-pub enum SurfacePresentationError {
-
-    /// The given image to present was too large to present.
-    ImageTooLarge,
-
-}
+pub use super::{SurfaceCreationError, SurfacePresentationError};
 
 const NULL_QUAD: RGBQUAD = RGBQUAD {
     rgbBlue: 0,
@@ -38,6 +25,11 @@ const NULL_QUAD: RGBQUAD = RGBQUAD {
     rgbReserved: 0,
 };
 
+/// The masks of the red, green and blue channels of the 32-bit pixels of the
+/// DIB, as DWORDs. An RGBQUAD stores its bytes as blue, green, red and
+/// reserved, so setting `rgbBlue` sets the lowest byte of the mask. The red
+/// mask is thus `0x000000FF`, the green one `0x0000FF00` and the blue one
+/// `0x00FF0000`, matching the memory layout of [`Pixel`].
 const BITMAP_COLOR_DESCRIPTORS: [RGBQUAD; 3] = [
     RGBQUAD {
         rgbBlue: 0xff,
@@ -67,17 +59,26 @@ struct BitmapColoredInfo {
 pub struct Surface {
     window: HWND,
     device_context: HDC,
+    orientation: SurfaceOrientation,
 }
 
 impl Surface {
 
     /// Creates a new surface for the given window.
+    #[cfg(feature = "winit")]
     pub fn new(window: &Window) -> Result<Self, SurfaceCreationError> {
         let RawWindowHandle::Win32(handle) = window.raw_window_handle() else {
             return Err(SurfaceCreationError::UnsupportedPlatform);
         };
 
-        let hwnd = HWND(handle.hwnd as _);
+        Self::from_hwnd(handle.hwnd as _)
+    }
+
+    /// Creates a new surface for the Win32 window with the given handle, for
+    /// windows that aren't created through winit. The window has to outlive
+    /// the surface.
+    pub fn from_hwnd(hwnd: isize) -> Result<Self, SurfaceCreationError> {
+        let hwnd = HWND(hwnd as _);
 
         if hwnd == HWND::default() {
             return Err(SurfaceCreationError::InvalidHandle);
@@ -95,24 +96,74 @@ impl Surface {
 
         Ok(Self {
             window: hwnd,
-            device_context
+            device_context,
+            orientation: SurfaceOrientation::default(),
         })
     }
 
+    /// Gets the order in which the rows of presented buffers are stored.
+    pub fn orientation(&self) -> SurfaceOrientation {
+        self.orientation
+    }
+
+    /// Sets the order in which the rows of presented buffers are stored.
+    pub fn set_orientation(&mut self, orientation: SurfaceOrientation) {
+        self.orientation = orientation;
+    }
+
     pub fn present(&mut self, buffer: &[Pixel], extent: swap_chain::Extent)
             -> Result<(), SurfacePresentationError> {
-        if extent.width > i32::max_value() as _ {
+        // Extents that don't fit in an `i32` are rejected before the
+        // rectangle is used.
+        let rect = bounds(extent);
+        self.present_region(buffer, extent, rect, rect)
+    }
+
+    /// Presents the source rectangle of the buffer, in pixels, stretched to
+    /// the destination rectangle of the window, e.g. to only update the part
+    /// that changed, or to scale the image. The rest of the window is left
+    /// as it is.
+    pub fn present_region(&mut self, buffer: &[Pixel], extent: swap_chain::Extent,
+            src_rect: Rectangle2D<i32>, dst_rect: Rectangle2D<i32>) -> Result<(), SurfacePresentationError> {
+        if extent.width > i32::MAX as _ {
             return Err(SurfacePresentationError::ImageTooLarge);
         }
 
-        if extent.height > i32::max_value() as _ {
+        if extent.height > i32::MAX as _ {
             return Err(SurfacePresentationError::ImageTooLarge);
         }
 
+        if extent.width.checked_mul(extent.height) != Some(buffer.len()) {
+            return Err(SurfacePresentationError::BufferSizeMismatch);
+        }
+
+        let bounds = bounds(extent);
+        let is_empty = |rect: &Rectangle2D<i32>| rect.width() <= 0 || rect.height() <= 0;
+        if is_empty(&src_rect) || is_empty(&dst_rect) {
+            return Ok(());
+        }
+        if bounds.intersect(&src_rect) != Some(src_rect) {
+            return Err(SurfacePresentationError::RegionOutOfBounds);
+        }
+
+        // The source rectangle starts at the bottom left corner of a
+        // bottom-up DIB.
+        let src_y = match self.orientation {
+            SurfaceOrientation::TopDown => src_rect.y(),
+            SurfaceOrientation::BottomUp => bounds.height() - src_rect.y_range().end,
+        };
+
+        // A negative height requests a top-down DIB, a positive height a
+        // bottom-up one.
+        let height = match self.orientation {
+            SurfaceOrientation::TopDown => -(extent.height as i32),
+            SurfaceOrientation::BottomUp => extent.height as i32,
+        };
+
         let bitmap_info_header = BITMAPINFOHEADER {
             biSize: std::mem::size_of::<BITMAPINFOHEADER>() as _,
             biWidth: extent.width as _,
-            biHeight: -(extent.height as i32),
+            biHeight: height,
             biPlanes: 1,
             biBitCount: 32,
             biCompression: BI_BITFIELDS,
@@ -131,14 +182,14 @@ impl Surface {
         let scan_lines = unsafe {
             StretchDIBits(
                 self.device_context,
-                0,
-                0,
-                extent.width as _,
-                extent.height as _,
-                0,
-                0,
-                extent.width as _,
-                extent.height as _,
+                dst_rect.x(),
+                dst_rect.y(),
+                dst_rect.width(),
+                dst_rect.height(),
+                src_rect.x(),
+                src_y,
+                src_rect.width(),
+                src_rect.height(),
                 Some(buffer.as_ptr().cast()),
                 &bitmap_info as *const BitmapColoredInfo as *const _,
                 DIB_RGB_COLORS,
@@ -161,8 +212,11 @@ impl Surface {
             // TODO return SurfacePresentationError instead
         }
 
+        // Only the drawn part is validated, so the rest of the client area
+        // keeps any pending repaint.
+        let rect = to_rect(dst_rect);
         unsafe {
-            let result = ValidateRect(self.window, None);
+            let result = ValidateRect(self.window, Some(&rect));
             assert!(result.as_bool(), "ValidateRect failed");
         };
 
@@ -170,3 +224,66 @@ impl Surface {
     }
 
 }
+
+/// Gets the rectangle covering the extent, which must fit in an `i32`.
+fn bounds(extent: swap_chain::Extent) -> Rectangle2D<i32> {
+    Rectangle2D::new(Vector2::new(0, 0), Vector2::new(extent.width as i32, extent.height as i32))
+}
+
+/// Converts the rectangle, in pixels, to a Win32 `RECT`, of which the right
+/// and bottom edges are exclusive as well.
+fn to_rect(rect: Rectangle2D<i32>) -> RECT {
+    RECT {
+        left: rect.x(),
+        top: rect.y(),
+        right: rect.x() + rect.width(),
+        bottom: rect.y() + rect.height(),
+    }
+}
+
+impl PresentationSurface for Surface {
+
+    fn present(&mut self, buffer: &[Pixel], extent: swap_chain::Extent)
+            -> Result<(), SurfacePresentationError> {
+        Surface::present(self, buffer, extent)
+    }
+
+    fn present_rect(&mut self, buffer: &[Pixel], extent: swap_chain::Extent, rect: Rectangle2D<i32>)
+            -> Result<(), SurfacePresentationError> {
+        self.present_region(buffer, extent, rect, rect)
+    }
+
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn color_masks_match_pixel_layout() {
+        let masks = BITMAP_COLOR_DESCRIPTORS.map(|quad| {
+            u32::from_le_bytes([quad.rgbBlue, quad.rgbGreen, quad.rgbRed, quad.rgbReserved])
+        });
+        assert_eq!(masks, [0x000000FF, 0x0000FF00, 0x00FF0000]);
+
+        // The DIB reads every pixel as a little-endian DWORD.
+        let channels = |pixel: Pixel| {
+            let value = u32::from_le_bytes(unsafe { std::mem::transmute::<Pixel, [u8; 4]>(pixel) });
+            masks.map(|mask| (value & mask) >> mask.trailing_zeros())
+        };
+        assert_eq!(channels(Pixel::RED), [0xFF, 0x00, 0x00]);
+        assert_eq!(channels(Pixel::GREEN), [0x00, 0xFF, 0x00]);
+        assert_eq!(channels(Pixel::BLUE), [0x00, 0x00, 0xFF]);
+    }
+
+    #[test]
+    fn rect_covers_destination() {
+        let rect = to_rect(Rectangle2D::new(Vector2::new(3, 4), Vector2::new(10, 6)));
+        assert_eq!((rect.left, rect.top, rect.right, rect.bottom), (3, 4, 10, 6));
+    }
+
+    #[test]
+    fn from_null_hwnd() {
+        assert!(matches!(Surface::from_hwnd(0), Err(SurfaceCreationError::InvalidHandle)));
+    }
+}
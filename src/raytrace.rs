@@ -0,0 +1,162 @@
+// Copyright (C) 2023 Tristan Gerritsen <tristan@thewoosh.org>
+// All Rights Reserved.
+
+//! Ray tracing as an alternative to rasterization, which shoots a ray
+//! through every pixel at a soup of 3D triangles, and shades the nearest
+//! triangle it hits.
+
+use crate::{
+    camera::Camera3D,
+    format::PixelFormat,
+    math::{RayHit, Triangle3D, Vector2, Vector2f, Vector3},
+    swap_chain::SwapChain,
+    Pixel,
+};
+
+/// Finds the triangle that the ray from the origin in the given direction
+/// hits first, together with its index in the slice.
+pub fn nearest_hit(triangles: &[Triangle3D], origin: Vector3, direction: Vector3) -> Option<(usize, RayHit)> {
+    triangles.iter()
+        .enumerate()
+        .filter_map(|(index, triangle)| triangle.ray_hit(origin, direction).map(|hit| (index, hit)))
+        .min_by(|(_, a), (_, b)| a.t.total_cmp(&b.t))
+}
+
+/// Renders triangles by tracing a ray per pixel, shading all of them with a
+/// single color.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct RayTracer {
+
+    /// The color of the triangles, specified with straight alpha.
+    pub color: Pixel,
+
+    /// The direction the light travels in, which doesn't have to be of unit
+    /// length. Without a light, the triangles are unlit, and get the color
+    /// as it is.
+    pub light: Option<Vector3>,
+
+}
+
+impl RayTracer {
+
+    /// Creates the ray tracer that draws the triangles unlit, in the color
+    /// specified with straight alpha.
+    pub fn new(color: Pixel) -> Self {
+        Self { color, light: None }
+    }
+
+    /// Traces a ray from the camera through every pixel of the swap chain,
+    /// writing the shaded color of the nearest triangle it hits like
+    /// [`SwapChain::put_pixel`]. Pixels of which the ray hits nothing are
+    /// left untouched. The rays go through the same points of the pixels
+    /// that the rasterizer samples, with the top row at the top of the view.
+    ///
+    /// With a light, the color is scaled by the cosine of the angle between
+    /// the light and the side of the triangle that is hit, so triangles that
+    /// face away from the light are black.
+    pub fn render<P: PixelFormat>(&self, swap_chain: &mut SwapChain<P>, triangles: &[Triangle3D],
+            camera: &Camera3D) {
+        let extent = swap_chain.extent();
+        if extent.is_empty() {
+            return;
+        }
+
+        let aspect_ratio = extent.width as f32 / extent.height as f32;
+        for y in 0..extent.height {
+            for x in 0..extent.width {
+                let point = Vector2f::new(
+                    2.0 * x as f32 / extent.width as f32 - 1.0,
+                    1.0 - 2.0 * y as f32 / extent.height as f32,
+                );
+                let direction = camera.ray_direction(point, aspect_ratio);
+                if let Some((index, hit)) = nearest_hit(triangles, camera.position, direction) {
+                    let color = self.shade(&triangles[index], &hit);
+                    swap_chain.put_pixel(Vector2::new(x as i32, y as i32), color);
+                }
+            }
+        }
+    }
+
+    fn shade(&self, triangle: &Triangle3D, hit: &RayHit) -> Pixel {
+        let Some(light) = self.light else {
+            return self.color;
+        };
+
+        let normal = triangle.normal();
+        let facing = if hit.front_facing { -1.0 } else { 1.0 };
+        let intensity = (normal.dot(light.normalized()) * facing).max(0.0);
+        let channel = |value: u8| (value as f32 * intensity).round() as u8;
+        Pixel::new(channel(self.color.red()), channel(self.color.green()), channel(self.color.blue()),
+            self.color.alpha())
+    }
+
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::swap_chain::Extent;
+
+    fn triangle() -> Triangle3D {
+        Triangle3D(
+            Vector3::new(-1.0, -1.0, 0.0),
+            Vector3::new(1.0, -1.0, 0.0),
+            Vector3::new(-1.0, 1.0, 0.0),
+        )
+    }
+
+    #[test]
+    fn render_single_triangle() {
+        // The view spans from -2 to 2 on both axes at the triangle, so the
+        // triangle covers the bottom left half of the middle of the buffer.
+        let camera = Camera3D::new(Vector3::new(0.0, 0.0, 2.0), Vector3::default());
+        let color = Pixel::new(0x20, 0xC0, 0x60, 0xFF);
+        let mut swap_chain = SwapChain::with_extent(Extent { width: 8, height: 8 });
+        RayTracer::new(color).render(&mut swap_chain, &[triangle()], &camera);
+
+        for (x, y) in [(3, 4), (3, 5), (4, 4), (2, 5)] {
+            assert_eq!(swap_chain.get_pixel(Vector2::new(x, y)), Some(color), "({x}, {y})");
+        }
+        for (x, y) in [(0, 0), (5, 3), (1, 4), (4, 7), (7, 7)] {
+            assert_eq!(swap_chain.get_pixel(Vector2::new(x, y)), Some(Pixel::BLACK), "({x}, {y})");
+        }
+        assert!(swap_chain.as_slice().iter().all(|pixel| *pixel == color || *pixel == Pixel::BLACK));
+    }
+
+    #[test]
+    fn nearest_triangle_is_in_front() {
+        let near = triangle();
+        let far = Triangle3D(
+            Vector3::new(-4.0, -4.0, -1.0),
+            Vector3::new(4.0, -4.0, -1.0),
+            Vector3::new(0.0, 4.0, -1.0),
+        );
+        let down = Vector3::new(0.0, 0.0, -1.0);
+
+        let (index, hit) = nearest_hit(&[far, near], Vector3::new(-0.5, -0.5, 2.0), down).unwrap();
+        assert_eq!(index, 1);
+        assert_eq!(hit.t, 2.0);
+        assert_eq!(nearest_hit(&[far, near], Vector3::new(2.0, -2.0, 2.0), down).map(|(index, _)| index),
+            Some(0));
+        assert_eq!(nearest_hit(&[far, near], Vector3::new(9.0, 0.0, 2.0), down), None);
+    }
+
+    #[test]
+    fn light_scales_color() {
+        let camera = Camera3D::new(Vector3::new(0.0, 0.0, 2.0), Vector3::default());
+        let mut ray_tracer = RayTracer::new(Pixel::WHITE);
+
+        // The light makes an angle of 60 degrees with the normal.
+        ray_tracer.light = Some(Vector3::new(0.0, -3.0f32.sqrt(), -1.0));
+        let mut swap_chain = SwapChain::with_extent(Extent { width: 8, height: 8 });
+        ray_tracer.render(&mut swap_chain, &[triangle()], &camera);
+        assert_eq!(swap_chain.get_pixel(Vector2::new(3, 4)), Some(Pixel::new(0x80, 0x80, 0x80, 0xFF)));
+
+        // The back of the triangle faces away from the light.
+        let behind = Camera3D::new(Vector3::new(0.0, 0.0, -2.0), Vector3::default());
+        swap_chain.clear(Pixel::WHITE);
+        ray_tracer.render(&mut swap_chain, &[triangle()], &behind);
+        assert_eq!(swap_chain.get_pixel(Vector2::new(4, 5)), Some(Pixel::BLACK));
+        assert_eq!(swap_chain.get_pixel(Vector2::new(0, 0)), Some(Pixel::WHITE));
+    }
+}
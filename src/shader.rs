@@ -3,17 +3,324 @@
 
 use crate::{
     Pixel,
-    math::Vector2f
+    interpolation::Interpolator,
+    math::{ClipVertex, Vector2f, Vector4},
 };
 
+/// The maximum amount of values a vertex shader can pass to the fragment
+/// shader.
+pub const MAX_VARYINGS: usize = 8;
+
+/// The values a vertex shader passes to the fragment shader, which are
+/// interpolated across the triangle.
+pub type Varyings = [f32; MAX_VARYINGS];
+
+/// The index of the first of the four varyings to which a
+/// [`VertexShader2D`] passes the color of a [`Vertex`], as normalized
+/// sRGB-encoded red, green, blue and straight alpha.
+pub const COLOR_VARYING: usize = 0;
+
+/// The index of the first of the two varyings to which a [`VertexShader2D`]
+/// passes the texture coordinates of a [`Vertex`].
+pub const UV_VARYING: usize = 4;
+
+/// The vertex of a triangle of which the varyings are used for the whole
+/// triangle in flat shading.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub enum ProvokingVertex {
+
+    /// The first vertex of the triangle.
+    #[default]
+    First,
+
+    /// The last vertex of the triangle.
+    Last,
+
+}
+
+impl ProvokingVertex {
+
+    /// Gets the index of the vertex within the triangle.
+    pub const fn index(&self) -> usize {
+        match self {
+            ProvokingVertex::First => 0,
+            ProvokingVertex::Last => 2,
+        }
+    }
+
+}
+
+/// The way the varyings of the vertices are passed to the fragment shader.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub enum ShadingMode {
+
+    /// Every pixel gets the varyings of the provoking vertex, without
+    /// interpolating them.
+    Flat(ProvokingVertex),
+
+    /// The varyings are interpolated across the triangle, also known as
+    /// Gouraud shading.
+    #[default]
+    Smooth,
+
+}
+
+/// The way the varyings are interpolated across a triangle in smooth shading.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Interpolation {
+
+    /// Interpolate linearly in screen space, which is cheaper, but warps
+    /// textures on triangles that are viewed at an angle.
+    Affine,
+
+    /// Interpolate linearly in clip space, by interpolating the varyings
+    /// divided by `w` together with `1 / w`, and dividing them per pixel.
+    #[default]
+    PerspectiveCorrect,
+
+}
+
+/// The output of a vertex shader for a single vertex.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct VertexOutput {
+
+    /// The position in homogeneous clip-space coordinates.
+    pub position: Vector4,
+
+    /// The values that are interpolated for the fragment shader.
+    pub varyings: Varyings,
+
+}
+
+impl VertexOutput {
+
+    /// Creates the output for a vertex without varyings.
+    pub fn new(position: Vector4) -> Self {
+        Self {
+            position,
+            varyings: [0.0; MAX_VARYINGS],
+        }
+    }
+
+    pub fn with_varyings(position: Vector4, varyings: Varyings) -> Self {
+        Self { position, varyings }
+    }
+
+}
+
+impl ClipVertex for VertexOutput {
+
+    fn clip_position(&self) -> Vector4 {
+        self.position
+    }
+
+    fn interpolate(a: Self, b: Self, t: f32) -> Self {
+        let mut varyings = a.varyings;
+        for (varying, b) in varyings.iter_mut().zip(b.varyings) {
+            *varying += (b - *varying) * t;
+        }
+
+        Self {
+            position: Vector4::lerp(a.position, b.position, t),
+            varyings,
+        }
+    }
+
+}
+
+/// The input of a fragment shader for a single pixel. The depth and
+/// varyings are only interpolated when they're requested.
+pub struct FragmentInput<'a> {
+    position: Vector2f,
+    interpolator: &'a Interpolator,
+}
+
+impl<'a> FragmentInput<'a> {
+
+    pub(crate) fn new(position: Vector2f, interpolator: &'a Interpolator) -> Self {
+        Self { position, interpolator }
+    }
+
+    /// Gets the depth in normalized device coordinates, between -1 at the
+    /// near plane and 1 at the far plane.
+    pub fn depth(&self) -> f32 {
+        self.interpolator.depth(self.position)
+    }
+
+    /// Gets the position of the pixel in the swap chain.
+    pub fn position(&self) -> Vector2f {
+        self.position
+    }
+
+    /// Gets a single interpolated varying.
+    ///
+    /// # Panics
+    /// Panics when the index isn't below [`MAX_VARYINGS`].
+    pub fn varying(&self, index: usize) -> f32 {
+        self.interpolator.varying(index, self.position)
+    }
+
+    /// Estimates how much the varying with the given index changes per pixel
+    /// in the `x` and `y` direction, from its value at the neighbouring
+    /// pixels, e.g. to select the level of a mipmapped texture.
+    ///
+    /// # Panics
+    /// Panics when the index isn't below [`MAX_VARYINGS`].
+    pub fn varying_derivatives(&self, index: usize) -> (f32, f32) {
+        self.interpolator.varying_derivatives(index, self.position)
+    }
+
+    /// Gets all of the interpolated varyings.
+    pub fn varyings(&self) -> Varyings {
+        std::array::from_fn(|index| self.varying(index))
+    }
+
+}
+
+/// A vertex shader that positions vertices in homogeneous clip space, and can
+/// pass varyings to the fragment shader. Every [`VertexShader2D`] is also a
+/// vertex shader, which places its vertices at `z = 0` and `w = 1`.
+///
+/// The uniforms `U` are the values that are the same for every vertex of a
+/// draw, such as a transformation matrix.
+pub trait VertexShader<U: ?Sized = ()> {
+
+    fn run(&self, position: Vector2f, uniforms: &U) -> VertexOutput;
+
+}
+
 pub trait VertexShader2D {
 
     fn run(&self, position: Vector2f) -> Vector2f;
 
 }
 
+/// The attributes of a single vertex of the arrays given to
+/// [`SwapChain::draw_arrays`](crate::swap_chain::SwapChain::draw_arrays).
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct Vertex {
+
+    /// The position of the vertex.
+    pub position: Vector2f,
+
+    /// The color of the vertex, specified with straight alpha, which is
+    /// white when the draw has no colors.
+    pub color: Pixel,
+
+    /// The texture coordinates of the vertex, which are zero when the draw
+    /// has none.
+    pub uv: Vector2f,
+
+}
+
+/// A vertex shader for the attributes of the vertices in
+/// [`SwapChain::draw_arrays`](crate::swap_chain::SwapChain::draw_arrays).
+/// Every [`VertexShader2D`] is also such a vertex shader, which transforms
+/// the position, and passes the color to the varyings from
+/// [`COLOR_VARYING`], and the texture coordinates to the varyings from
+/// [`UV_VARYING`].
+pub trait VertexArrayShader {
+
+    fn run(&self, vertex: &Vertex) -> VertexOutput;
+
+}
+
+impl<T: VertexShader2D + ?Sized> VertexArrayShader for T {
+
+    fn run(&self, vertex: &Vertex) -> VertexOutput {
+        let position = VertexShader2D::run(self, vertex.position);
+        let mut varyings = [0.0; MAX_VARYINGS];
+        varyings[COLOR_VARYING..COLOR_VARYING + 4].copy_from_slice(&vertex.color.to_normalized());
+        varyings[UV_VARYING] = vertex.uv.x;
+        varyings[UV_VARYING + 1] = vertex.uv.y;
+        VertexOutput::with_varyings(Vector4::new(position.x, position.y, 0.0, 1.0), varyings)
+    }
+
+}
+
+impl<T: VertexShader2D + ?Sized, U: ?Sized> VertexShader<U> for T {
+
+    fn run(&self, position: Vector2f, _uniforms: &U) -> VertexOutput {
+        let position = VertexShader2D::run(self, position);
+        VertexOutput::new(Vector4::new(position.x, position.y, 0.0, 1.0))
+    }
+
+}
+
+/// A fragment shader that can use the interpolated values of the pixel it
+/// runs for. Every [`FragmentShader2D`] is also a fragment shader, which
+/// ignores its input.
+///
+/// The uniforms `U` are the values that are the same for every fragment of a
+/// draw, such as the current time.
+pub trait FragmentShader<U: ?Sized = ()> {
+
+    fn run(&self, input: &FragmentInput, uniforms: &U) -> Pixel;
+
+}
+
 pub trait FragmentShader2D {
 
     fn run(&self) -> Pixel;
 
 }
+
+impl<T: FragmentShader2D + ?Sized, U: ?Sized> FragmentShader<U> for T {
+
+    #[inline]
+    fn run(&self, _input: &FragmentInput, _uniforms: &U) -> Pixel {
+        FragmentShader2D::run(self)
+    }
+
+}
+
+/// A vertex shader of which the closure transforms the positions, for one-off
+/// shaders such as `FnVertexShader(|position| position)`.
+#[derive(Copy, Clone)]
+pub struct FnVertexShader<F: Fn(Vector2f) -> Vector2f>(pub F);
+
+impl<F: Fn(Vector2f) -> Vector2f> VertexShader2D for FnVertexShader<F> {
+
+    #[inline]
+    fn run(&self, position: Vector2f) -> Vector2f {
+        (self.0)(position)
+    }
+
+}
+
+/// A fragment shader of which the closure gives the color of every fragment,
+/// for one-off shaders such as `FnFragmentShader(|| Pixel::RED)`.
+#[derive(Copy, Clone)]
+pub struct FnFragmentShader<F: Fn() -> Pixel>(pub F);
+
+impl<F: Fn() -> Pixel> FragmentShader2D for FnFragmentShader<F> {
+
+    #[inline]
+    fn run(&self) -> Pixel {
+        (self.0)()
+    }
+
+}
+
+/// The shaders of triangles of a single color, of which the vertex shader
+/// passes the positions through, and the fragment shader writes the color it
+/// gets as its uniforms.
+pub(crate) struct SolidColor;
+
+impl VertexShader2D for SolidColor {
+
+    #[inline]
+    fn run(&self, position: Vector2f) -> Vector2f {
+        position
+    }
+
+}
+
+impl FragmentShader<Pixel> for SolidColor {
+
+    #[inline]
+    fn run(&self, _input: &FragmentInput, color: &Pixel) -> Pixel {
+        *color
+    }
+
+}
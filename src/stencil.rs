@@ -0,0 +1,201 @@
+// Copyright (C) 2023 Tristan Gerritsen <tristan@thewoosh.org>
+// All Rights Reserved.
+
+//! The stencil test, which masks drawing using an 8-bit value stored for every
+//! pixel, or every sample when multisampling. Draws can both test against and
+//! update these values, which allows clipping subsequent draws to arbitrary
+//! shapes.
+
+/// The way a reference value is compared against a stored value.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub enum CompareFunction {
+
+    /// The comparison never passes.
+    Never,
+
+    /// Passes when the reference is less than the stored value.
+    Less,
+
+    /// Passes when the reference is equal to the stored value.
+    Equal,
+
+    /// Passes when the reference is less than or equal to the stored value.
+    LessOrEqual,
+
+    /// Passes when the reference is greater than the stored value.
+    Greater,
+
+    /// Passes when the reference is not equal to the stored value.
+    NotEqual,
+
+    /// Passes when the reference is greater than or equal to the stored value.
+    GreaterOrEqual,
+
+    /// The comparison always passes.
+    #[default]
+    Always,
+
+}
+
+impl CompareFunction {
+
+    /// Compares the reference value against the stored value.
+    #[inline]
+    pub fn compare<T: PartialOrd>(&self, reference: T, stored: T) -> bool {
+        match self {
+            CompareFunction::Never => false,
+            CompareFunction::Less => reference < stored,
+            CompareFunction::Equal => reference == stored,
+            CompareFunction::LessOrEqual => reference <= stored,
+            CompareFunction::Greater => reference > stored,
+            CompareFunction::NotEqual => reference != stored,
+            CompareFunction::GreaterOrEqual => reference >= stored,
+            CompareFunction::Always => true,
+        }
+    }
+
+}
+
+/// The way the stored stencil value is updated after the stencil test.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub enum StencilOperation {
+
+    /// The stored value is left as is.
+    #[default]
+    Keep,
+
+    /// The stored value is set to zero.
+    Zero,
+
+    /// The stored value is set to the reference value.
+    Replace,
+
+    /// The stored value is incremented, saturating at `0xFF`.
+    Increment,
+
+    /// The stored value is decremented, saturating at zero.
+    Decrement,
+
+    /// The bits of the stored value are inverted.
+    Invert,
+
+}
+
+impl StencilOperation {
+
+    /// Gets the new stored value.
+    #[inline]
+    pub fn apply(&self, reference: u8, stored: u8) -> u8 {
+        match self {
+            StencilOperation::Keep => stored,
+            StencilOperation::Zero => 0,
+            StencilOperation::Replace => reference,
+            StencilOperation::Increment => stored.saturating_add(1),
+            StencilOperation::Decrement => stored.saturating_sub(1),
+            StencilOperation::Invert => !stored,
+        }
+    }
+
+}
+
+/// Describes the stencil test for the pixels, or samples, covered by a draw.
+/// A fragment is only written when the test passes, and the stored value is
+/// updated with the operation for the outcome of the test.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct StencilTest {
+
+    /// The comparison of the reference value against the stored value.
+    pub compare: CompareFunction,
+
+    /// The reference value to compare against and to use for
+    /// [`StencilOperation::Replace`].
+    pub reference: u8,
+
+    /// The operation for when the test passes.
+    pub pass: StencilOperation,
+
+    /// The operation for when the test fails.
+    pub fail: StencilOperation,
+
+}
+
+impl StencilTest {
+
+    /// Creates a test that always passes, writing the reference value. This
+    /// is useful to draw a mask.
+    pub fn write(reference: u8) -> Self {
+        Self {
+            compare: CompareFunction::Always,
+            reference,
+            pass: StencilOperation::Replace,
+            fail: StencilOperation::Keep,
+        }
+    }
+
+    /// Creates a test that passes where the stored value equals the
+    /// reference, without updating it. This is useful to draw inside of a
+    /// mask.
+    pub fn equal(reference: u8) -> Self {
+        Self {
+            compare: CompareFunction::Equal,
+            reference,
+            pass: StencilOperation::Keep,
+            fail: StencilOperation::Keep,
+        }
+    }
+
+    /// Runs the test against the stored value, updating it, and returns if
+    /// the test passed.
+    #[inline]
+    pub fn apply(&self, stored: &mut u8) -> bool {
+        let passed = self.compare.compare(self.reference, *stored);
+        let operation = if passed { self.pass } else { self.fail };
+        *stored = operation.apply(self.reference, *stored);
+        passed
+    }
+
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn compare_functions() {
+        let results = |compare: CompareFunction| {
+            [compare.compare(1, 2), compare.compare(2, 2), compare.compare(3, 2)]
+        };
+        assert_eq!(results(CompareFunction::Never), [false, false, false]);
+        assert_eq!(results(CompareFunction::Less), [true, false, false]);
+        assert_eq!(results(CompareFunction::Equal), [false, true, false]);
+        assert_eq!(results(CompareFunction::LessOrEqual), [true, true, false]);
+        assert_eq!(results(CompareFunction::Greater), [false, false, true]);
+        assert_eq!(results(CompareFunction::NotEqual), [true, false, true]);
+        assert_eq!(results(CompareFunction::GreaterOrEqual), [false, true, true]);
+        assert_eq!(results(CompareFunction::Always), [true, true, true]);
+    }
+
+    #[test]
+    fn operations_saturate() {
+        assert_eq!(StencilOperation::Increment.apply(0, 0xFF), 0xFF);
+        assert_eq!(StencilOperation::Decrement.apply(0, 0), 0);
+        assert_eq!(StencilOperation::Invert.apply(0, 0x0F), 0xF0);
+        assert_eq!(StencilOperation::Replace.apply(7, 3), 7);
+    }
+
+    #[test]
+    fn apply_updates_stored_value() {
+        let test = StencilTest {
+            compare: CompareFunction::Greater,
+            reference: 2,
+            pass: StencilOperation::Increment,
+            fail: StencilOperation::Zero,
+        };
+
+        let mut stored = 1;
+        assert!(test.apply(&mut stored));
+        assert_eq!(stored, 2);
+        assert!(!test.apply(&mut stored));
+        assert_eq!(stored, 0);
+    }
+}
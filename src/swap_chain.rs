@@ -1,116 +1,5290 @@
 // Copyright (C) 2023 Tristan Gerritsen <tristan@thewoosh.org>
 // All Rights Reserved.
 
-use winit::dpi::LogicalSize;
+use std::{borrow::Cow, cmp::Ordering, collections::VecDeque, fmt, ops::{Add, AddAssign, Range}};
+
+use arrayvec::ArrayVec;
+#[cfg(feature = "winit")]
+use winit::dpi::{LogicalSize, PhysicalSize};
 
 use crate::{
+    blend::BlendMode,
+    filter::Filter,
+    font::{tint, BitmapFont},
+    format::PixelFormat,
+    multisample::{resolve_samples, SampleCount},
+    pixel::{linear_to_srgb, srgb_to_linear, AlphaMode, ColorSpace},
     platform::{
-        Surface, win32::SurfacePresentationError
+        PresentationSurface, SurfacePresentationError
     },
     Pixel,
+    interpolation::Interpolator,
+    stencil::{CompareFunction, StencilTest},
     shader::{
-        FragmentShader2D,
-        VertexShader2D,
-    }, TriangleVertices, math::{Triangle2D, Vector2f, Vector2}};
+        FragmentInput,
+        FragmentShader,
+        Interpolation,
+        ShadingMode,
+        SolidColor,
+        Vertex,
+        VertexArrayShader,
+        VertexOutput,
+        VertexShader,
+    }, TriangleVertices,
+    math::{
+        clip_triangle,
+        clip_triangle_near_plane,
+        is_convex_polygon,
+        EdgeFunction,
+        Matrix3,
+        MAX_CLIPPED_TRIANGLES,
+        MAX_NEAR_CLIPPED_TRIANGLES,
+        Rectangle2D,
+        Rounding,
+        Triangle2D,
+        Vector2f,
+        Vector2,
+    },
+};
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Extent {
     pub width: usize,
     pub height: usize,
 }
 
-pub struct SwapChain {
-    extent: Extent,
-    buffer: Vec<Pixel>,
+impl Extent {
+
+    /// Checks whether the extent doesn't contain any pixels, such as the one
+    /// of a minimized window.
+    pub fn is_empty(&self) -> bool {
+        self.width == 0 || self.height == 0
+    }
+
 }
 
-/// Creates a pixel buffer for the given size.
-fn create_pixel_buffer(width: usize, height: usize, color: Pixel) -> Vec<Pixel> {
-    let mut vec = Vec::new();
-    vec.resize(width * height, color);
-    vec
+/// The space of the positions of the vertices after the perspective divide,
+/// which determines how they are mapped onto the pixels of the swap chain.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub enum CoordinateSpace {
+
+    /// Normalized device coordinates, from -1 to 1 across the swap chain,
+    /// with the direction of `y` given by the [`YAxis`].
+    #[default]
+    NormalizedDevice,
+
+    /// Pixel coordinates, where `(0, 0)` is the top left pixel, and the
+    /// width and height of the extent are the bottom right corner.
+    Pixels,
+
 }
 
-impl SwapChain {
+/// The direction on screen in which the `y` coordinate of normalized device
+/// coordinates increases.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub enum YAxis {
 
-    pub fn new(size: LogicalSize<u32>) -> Self {
-        Self {
-            extent: Extent {
-                width: size.width as _,
-                height: size.height as _
+    /// `y = 1` is at the top of the swap chain, like in most graphics APIs.
+    Up,
+
+    /// `y = 1` is at the bottom of the swap chain, matching the order of the
+    /// rows in the buffer.
+    #[default]
+    Down,
+
+}
+
+/// The way the rasterizer decides whether a triangle covers a pixel.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub enum ConservativeRaster {
+
+    /// A pixel, or sample when multisampling, is covered when its sample
+    /// position lies inside of the triangle.
+    #[default]
+    Disabled,
+
+    /// A pixel is covered when any part of it overlaps the triangle, even
+    /// when the triangle only grazes it, such as for voxelization or
+    /// collision detection. This may also cover some pixels near the corners
+    /// of the triangle that it doesn't touch. When multisampling, a sample
+    /// is covered when any part of the pixel around it overlaps the triangle.
+    Overestimate,
+
+}
+
+/// The way the rasterizer finds the pixels of a row that a triangle covers.
+/// Both fill exactly the same pixels.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub enum FillAlgorithm {
+
+    /// Test every pixel of the bounding box of the triangle against its
+    /// edges.
+    #[default]
+    HalfSpace,
+
+    /// Compute the span of every row that lies inside of the edges, and only
+    /// visit the pixels of that span, which is faster for large triangles.
+    /// When multisampling, the samples are still tested one by one.
+    Scanline,
+
+}
+
+/// The winding order of the vertices of triangles that face the viewer, as
+/// they appear on screen, i.e. after mapping them onto the pixels of the
+/// swap chain with its [`YAxis`].
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub enum FrontFace {
+
+    /// Triangles of which the vertices go clockwise on screen face the
+    /// viewer.
+    Clockwise,
+
+    /// Triangles of which the vertices go counter-clockwise on screen face
+    /// the viewer.
+    #[default]
+    CounterClockwise,
+
+}
+
+/// The triangles that are discarded based on the way they face, as given by
+/// the [`FrontFace`].
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub enum CullMode {
+
+    /// Every triangle is drawn, regardless of its winding order.
+    #[default]
+    None,
+
+    /// Triangles that face the viewer are discarded.
+    Front,
+
+    /// Triangles that face away from the viewer are discarded, such as the
+    /// back sides of closed meshes, which are hidden anyway.
+    Back,
+
+}
+
+impl CullMode {
+
+    /// Checks whether a triangle is discarded, given whether its vertices go
+    /// clockwise on screen.
+    fn culls(&self, front_face: FrontFace, clockwise: bool) -> bool {
+        let front = clockwise == (front_face == FrontFace::Clockwise);
+        match self {
+            CullMode::None => false,
+            CullMode::Front => front,
+            CullMode::Back => !front,
+        }
+    }
+
+}
+
+/// Whether triangles that are entirely outside of the render area are culled
+/// before their vertices are shaded, which saves running the vertex shader
+/// for most of a large scene that is off-screen.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub enum ViewportCull {
+
+    /// Every vertex is shaded, and triangles are only clipped afterwards.
+    #[default]
+    Disabled,
+
+    /// The positions of the vertices, as given to the draw, are tested
+    /// against the render area in the [coordinate space](CoordinateSpace) of
+    /// the swap chain before the vertex shader runs. This is only correct for
+    /// vertex shaders that preserve the positions, i.e. when the vertices are
+    /// already transformed, since a shader that moves them could move a
+    /// culled triangle on-screen.
+    BeforeVertexShader,
+
+}
+
+/// The way the vertices of [`SwapChain::draw_arrays`] form triangles.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub enum PrimitiveTopology {
+
+    /// Every three vertices form a triangle, and a remainder of one or two
+    /// vertices is ignored.
+    #[default]
+    TriangleList,
+
+    /// Every vertex after the first two forms a triangle with the two
+    /// vertices before it. The first two vertices of every other triangle are
+    /// swapped, so all of the triangles have the winding of the first.
+    TriangleStrip,
+
+    /// Every vertex after the first two forms a triangle with the vertex
+    /// before it and the first vertex.
+    TriangleFan,
+
+}
+
+impl PrimitiveTopology {
+
+    /// Gets the indices of the vertices of the triangle with the given index.
+    pub fn triangle(&self, index: usize) -> [usize; 3] {
+        match self {
+            PrimitiveTopology::TriangleList => [index * 3, index * 3 + 1, index * 3 + 2],
+            PrimitiveTopology::TriangleStrip => match index % 2 {
+                0 => [index, index + 1, index + 2],
+                _ => [index + 1, index, index + 2],
             },
-            buffer: create_pixel_buffer(size.width as _, size.height as _, Pixel::BLACK),
+            PrimitiveTopology::TriangleFan => [0, index + 1, index + 2],
         }
     }
 
-    pub fn clear(&mut self, color: Pixel) {
-        self.buffer.fill(color)
+    /// Gets the amount of triangles that the amount of vertices form.
+    pub fn triangle_count(&self, vertex_count: usize) -> usize {
+        match self {
+            PrimitiveTopology::TriangleList => vertex_count / 3,
+            PrimitiveTopology::TriangleStrip | PrimitiveTopology::TriangleFan => {
+                vertex_count.saturating_sub(2)
+            }
+        }
     }
 
-    pub fn draw_rasterized(&mut self, vertices: &[TriangleVertices], vertex_shader: &dyn VertexShader2D,
-            fragment_shader: &dyn FragmentShader2D) {
+}
+
+/// The state that determines how triangles are rasterized and how their
+/// fragments are written, which is used by every draw of the swap chain.
+/// Every field can also be changed on its own with the setter of the swap
+/// chain, such as [`SwapChain::set_blend_mode`].
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct RasterizerState {
 
-        for vertex_triple in vertices {
-            let va = vertex_shader.run(vertex_triple.a);
-            let vb = vertex_shader.run(vertex_triple.b);
-            let vc = vertex_shader.run(vertex_triple.c);
+    /// The way fragments are combined with the pixels in the buffer.
+    pub blend_mode: BlendMode,
 
-            let coord_a = self.vertex_to_pixel_position(va);
-            let coord_b = self.vertex_to_pixel_position(vb);
-            let coord_c = self.vertex_to_pixel_position(vc);
+    /// The color space in which fragments are blended.
+    pub blend_space: ColorSpace,
 
-            println!("pixel position {:?} {:?} {:?}", coord_a, coord_b, coord_c);
+    /// The rectangle, in pixels, outside of which fragments are discarded.
+    /// `None` means the full extent.
+    pub scissor: Option<Rectangle2D<i32>>,
 
-            let triangle = Triangle2D(coord_a, coord_b, coord_c);
-            let enclosing_rect = triangle.encapsulating_rectangle();
+    /// The way the varyings are passed to the fragment shader.
+    pub shading_mode: ShadingMode,
 
-            for y in enclosing_rect.y_range() {
-                for x in enclosing_rect.x_range() {
-                    let point = Vector2::new(x, y);
-                    if triangle.hit_test(point) {
-                        if self.is_point_inside(point) {
-                            let color = fragment_shader.run();
-                            self.set_pixel(point, color);
-                        }
-                    }
-                }
+    /// The way the varyings are interpolated in smooth shading.
+    pub interpolation: Interpolation,
+
+    /// The space of the positions of the vertices.
+    pub coordinate_space: CoordinateSpace,
+
+    /// The direction in which `y` increases in normalized device
+    /// coordinates.
+    pub y_axis: YAxis,
+
+    /// The way the coverage of pixels is determined.
+    pub conservative_raster: ConservativeRaster,
+
+    /// Whether off-screen triangles are culled before vertex shading.
+    pub viewport_cull: ViewportCull,
+
+    /// The way the covered pixels of every row are found.
+    pub fill_algorithm: FillAlgorithm,
+
+    /// The way the positions of vertices are snapped to subpixels.
+    pub vertex_rounding: Rounding,
+
+    /// The triangles that are discarded based on the way they face.
+    pub cull_mode: CullMode,
+
+    /// The winding order of triangles that face the viewer.
+    pub front_face: FrontFace,
+
+    /// The alpha below which fragments of triangles are discarded, if any.
+    pub alpha_test: Option<u8>,
+
+    /// The stencil test that draws are masked with, if any.
+    pub stencil_test: Option<StencilTest>,
+
+    /// The comparison of the depth of fragments against the depth buffer, if
+    /// any.
+    pub depth_test: Option<CompareFunction>,
+
+    /// Whether fragments passing the depth test write their depth.
+    pub depth_write: bool,
+
+    /// Whether fragments are written to the color buffer.
+    pub color_write: bool,
+
+}
+
+impl Default for RasterizerState {
+
+    fn default() -> Self {
+        Self {
+            blend_mode: BlendMode::default(),
+            blend_space: ColorSpace::default(),
+            scissor: None,
+            shading_mode: ShadingMode::default(),
+            interpolation: Interpolation::default(),
+            coordinate_space: CoordinateSpace::default(),
+            y_axis: YAxis::default(),
+            conservative_raster: ConservativeRaster::default(),
+            viewport_cull: ViewportCull::default(),
+            vertex_rounding: Rounding::default(),
+            fill_algorithm: FillAlgorithm::default(),
+            cull_mode: CullMode::default(),
+            front_face: FrontFace::default(),
+            alpha_test: None,
+            stencil_test: None,
+            depth_test: None,
+            depth_write: true,
+            color_write: true,
+        }
+    }
+
+}
+
+/// The amount of work the swap chain did while drawing triangles, which is
+/// counted once [enabled](SwapChain::set_stats), to profile scenes.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct RenderStats {
+
+    /// The amount of triangles passed to the draws, before culling.
+    pub triangles_submitted: u64,
+
+    /// The amount of submitted triangles of which nothing is rasterized,
+    /// since they are culled by their winding order or the viewport, lie
+    /// outside of the render area after clipping, or have no area.
+    pub triangles_culled: u64,
+
+    /// The amount of pixels to which a fragment was written, i.e. that passed
+    /// the alpha, stencil and depth tests. When multisampling, a pixel is
+    /// counted once per triangle, however many of its samples are written.
+    pub pixels_shaded: u64,
+
+    /// The amount of times that the fragment shader ran.
+    pub fragment_shader_invocations: u64,
+
+}
+
+impl Add for RenderStats {
+
+    type Output = Self;
+
+    fn add(mut self, other: Self) -> Self {
+        self += other;
+        self
+    }
+
+}
+
+impl AddAssign for RenderStats {
+
+    fn add_assign(&mut self, other: Self) {
+        self.triangles_submitted += other.triangles_submitted;
+        self.triangles_culled += other.triangles_culled;
+        self.pixels_shaded += other.pixels_shaded;
+        self.fragment_shader_invocations += other.fragment_shader_invocations;
+    }
+
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum SwapChainError {
+
+    /// The width or the height of the requested extent is zero.
+    ZeroExtent,
+
+    /// The size of the pixel buffer for the requested extent doesn't fit in
+    /// the address space.
+    TooLarge,
+
+    /// The pixel buffer couldn't be allocated.
+    AllocationFailed,
+
+}
+
+impl fmt::Display for SwapChainError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SwapChainError::ZeroExtent => write!(f, "the extent must not be zero"),
+            SwapChainError::TooLarge => write!(f, "the pixel buffer doesn't fit in the address space"),
+            SwapChainError::AllocationFailed => write!(f, "the pixel buffer couldn't be allocated"),
+        }
+    }
+}
+
+impl std::error::Error for SwapChainError {}
+
+/// The buffer that is rendered into, of which the pixels are stored in the
+/// given [`PixelFormat`].
+pub struct SwapChain<P: PixelFormat = Pixel> {
+    /// The size of the buffer in physical pixels.
+    extent: Extent,
+
+    /// The number of physical pixels per logical pixel.
+    scale_factor: f64,
+
+    /// The back buffer, which is drawn into.
+    buffer: Vec<P>,
+
+    /// The frames completed by [`SwapChain::acquire`], of which the last one
+    /// is presented. Empty when there is a single buffer.
+    front_buffers: VecDeque<Vec<P>>,
+    sample_count: SampleCount,
+
+    /// The samples of every pixel when multisampling, stored consecutively
+    /// per pixel. Empty when there is a single sample per pixel.
+    samples: Vec<P>,
+    alpha_mode: AlphaMode,
+    state: RasterizerState,
+
+    /// The stencil value of every sample, which is only created once it's
+    /// used.
+    stencil: Option<Vec<u8>>,
+
+    /// The depth of every sample in normalized device coordinates, which is
+    /// only created once it's used.
+    depth: Option<Vec<f32>>,
+
+    /// The index of the triangle that was drawn last to every pixel, which
+    /// only exists while picking is enabled.
+    ids: Option<Vec<Option<u32>>>,
+
+    /// The rectangle around the pixels that may have changed since the dirty
+    /// rectangle was last reset, if any.
+    dirty: Option<Rectangle2D<i32>>,
+
+    /// The color of every sample as normalized sRGB-encoded values with
+    /// straight alpha, which fragments are blended into instead of the
+    /// buffer, while float accumulation is enabled.
+    accumulation: Option<Vec<[f32; 4]>>,
+
+    /// The work done since the statistics were last taken, which is only
+    /// counted while they are enabled.
+    stats: Option<RenderStats>,
+}
+
+/// The state that determines how a fragment is written to the buffer.
+#[derive(Copy, Clone, Debug)]
+struct OutputState {
+    alpha_test: Option<u8>,
+    blend_mode: BlendMode,
+    blend_space: ColorSpace,
+    alpha_mode: AlphaMode,
+    stencil_test: Option<StencilTest>,
+    depth_test: Option<CompareFunction>,
+    depth_write: bool,
+    color_write: bool,
+}
+
+impl OutputState {
+
+    /// Runs the stencil test and then the depth test for the sample with the
+    /// given index in the row. The depth of the fragment is only computed
+    /// when there is a depth test.
+    #[inline]
+    fn test(&self, row: &mut RowTarget<impl PixelFormat>, index: usize, depth: impl FnOnce() -> f32) -> bool {
+        self.stencil_test(row.stencil.as_deref_mut(), index)
+            && self.depth_test(row.depth.as_deref_mut(), index, depth)
+    }
+
+    /// Compares the depth of the fragment against the sample with the given
+    /// index in the row of depths, which only exists when there is a depth
+    /// test, and stores it when it passes and depth writes are enabled.
+    #[inline]
+    fn depth_test(&self, depths: Option<&mut [f32]>, index: usize, depth: impl FnOnce() -> f32) -> bool {
+        let (Some(compare), Some(depths)) = (self.depth_test, depths) else {
+            return true;
+        };
+
+        let depth = depth();
+        let passed = compare.compare(depth, depths[index]);
+        if passed && self.depth_write {
+            depths[index] = depth;
+        }
+        passed
+    }
+
+    /// Runs the stencil test for the sample with the given index in the row
+    /// of stencil values, which only exists when there is a stencil test.
+    #[inline]
+    fn stencil_test(&self, stencil: Option<&mut [u8]>, index: usize) -> bool {
+        match (self.stencil_test, stencil) {
+            (Some(test), Some(stencil)) => test.apply(&mut stencil[index]),
+            _ => true,
+        }
+    }
+
+    /// Writes the color, specified with straight alpha, to the destination,
+    /// blending it with the existing value. With an accumulated color for
+    /// the destination, the color is blended into that one instead, and the
+    /// destination gets the result rounded to its format.
+    #[inline]
+    fn write<P: PixelFormat>(&self, destination: &mut P, accumulated: Option<&mut [f32; 4]>, color: Pixel) {
+        let Some(accumulated) = accumulated else {
+            *destination = P::from_pixel(self.blend(destination.to_pixel(), color));
+            return;
+        };
+
+        *accumulated = match self.blend_space {
+            ColorSpace::Srgb => self.blend_mode.blend_f32(color.to_normalized(), *accumulated),
+            ColorSpace::Linear => {
+                let decode = |[red, green, blue, alpha]: [f32; 4]| {
+                    [srgb_to_linear(red), srgb_to_linear(green), srgb_to_linear(blue), alpha]
+                };
+                let blended = self.blend_mode.blend_f32(color.to_linear(), decode(*accumulated));
+                let [red, green, blue, alpha] = blended;
+                [linear_to_srgb(red), linear_to_srgb(green), linear_to_srgb(blue), alpha]
+            }
+        };
+        *destination = P::from_pixel(self.alpha_mode.encode(Pixel::from_normalized(*accumulated)));
+    }
+
+    /// Blends the color, specified with straight alpha, with the destination
+    /// pixel.
+    #[inline]
+    fn blend(&self, destination: Pixel, color: Pixel) -> Pixel {
+        match (self.alpha_mode, self.blend_space) {
+            (AlphaMode::Straight, blend_space) => self.blend_mode.blend_in(blend_space, color, destination),
+            (AlphaMode::Premultiplied, ColorSpace::Srgb) => {
+                self.blend_mode.blend_premultiplied(color.premultiplied(), destination)
+            }
+            (AlphaMode::Premultiplied, ColorSpace::Linear) => {
+                let destination = destination.unpremultiplied();
+                self.blend_mode.blend_in(ColorSpace::Linear, color, destination).premultiplied()
             }
         }
     }
 
-    pub fn present(&self, surface: &mut Surface) -> Result<(), SurfacePresentationError> {
-        surface.present(&self.buffer, self.extent)
+}
+
+/// A triangle in subpixel coordinates that is ready to be rasterized,
+/// together with the interpolator for its attributes.
+#[derive(Clone, Debug)]
+pub(crate) struct AssembledTriangle {
+    pub(crate) triangle: Triangle2D<i32>,
+    pub(crate) interpolator: Interpolator,
+
+    /// The index of the triangle within its draw.
+    pub(crate) index: u32,
+}
+
+impl AssembledTriangle {
+
+    /// Gets the average depth of the vertices, which is the depth at the
+    /// centroid, given the number of subpixel steps per pixel.
+    fn average_depth(&self, precision: i32) -> f32 {
+        let Triangle2D(a, b, c) = self.triangle;
+        let scale = 3.0 * precision as f32;
+        let centroid = Vector2f::new((a.x + b.x + c.x) as f32 / scale, (a.y + b.y + c.y) as f32 / scale);
+        self.interpolator.depth(centroid)
+    }
+
+}
+
+/// The ordered dither thresholds of a 4x4 Bayer matrix, in sixteenths, which
+/// are repeated over the buffer by [`SwapChain::dither`].
+const BAYER_4X4: [[u8; 4]; 4] = [
+    [0, 8, 2, 10],
+    [12, 4, 14, 6],
+    [3, 11, 1, 9],
+    [15, 7, 13, 5],
+];
+
+/// The maximum amount of triangles a single triangle can be split into by
+/// clipping it against the near plane and the render area.
+const MAX_ASSEMBLED_TRIANGLES: usize = MAX_NEAR_CLIPPED_TRIANGLES * MAX_CLIPPED_TRIANGLES;
+
+/// The buffers that triangles are rasterized into, with the stencil values
+/// and depths of their elements when there is a stencil or depth test, the
+/// triangle indices of the pixels while picking is enabled, and the
+/// accumulated colors of the elements while float accumulation is enabled.
+struct RenderTarget<'a, P> {
+    colors: &'a mut [P],
+    stencil: Option<&'a mut [u8]>,
+    depth: Option<&'a mut [f32]>,
+    ids: Option<&'a mut [Option<u32>]>,
+    accumulation: Option<&'a mut [[f32; 4]]>,
+
+    /// The number of elements that make up a row.
+    row_length: usize,
+
+    /// The number of pixels that make up a row.
+    width: usize,
+}
+
+impl<'a, P> RenderTarget<'a, P> {
+
+    /// Splits the given rows of the target.
+    fn rows(self, rows: Range<usize>) -> impl Iterator<Item = RowTarget<'a, P>> {
+        let range = rows.start * self.row_length..rows.end * self.row_length;
+        let stencil = split_rows(self.stencil, range.clone(), self.row_length);
+        let depth = split_rows(self.depth, range.clone(), self.row_length);
+        let ids = split_rows(self.ids, rows.start * self.width..rows.end * self.width, self.width);
+        let accumulation = split_rows(self.accumulation, range.clone(), self.row_length);
+
+        self.colors[range].chunks_mut(self.row_length).zip(stencil).zip(depth).zip(ids).zip(accumulation)
+            .map(|((((colors, stencil), depth), ids), accumulation)| {
+                RowTarget { colors, stencil, depth, ids, accumulation }
+            })
+    }
+
+}
+
+/// A single row of the [`RenderTarget`].
+struct RowTarget<'a, P> {
+    colors: &'a mut [P],
+    stencil: Option<&'a mut [u8]>,
+    depth: Option<&'a mut [f32]>,
+    ids: Option<&'a mut [Option<u32>]>,
+    accumulation: Option<&'a mut [[f32; 4]]>,
+}
+
+impl<P: PixelFormat> RowTarget<'_, P> {
+
+    /// Writes the color, specified with straight alpha, to the element with
+    /// the given index in the row, see [`OutputState::write`].
+    #[inline]
+    fn write(&mut self, state: &OutputState, index: usize, color: Pixel) {
+        let accumulated = self.accumulation.as_deref_mut().map(|accumulation| &mut accumulation[index]);
+        state.write(&mut self.colors[index], accumulated, color);
     }
 
-    /// Checks if a given point is inside the render area.
+    /// Stores the index of the triangle that covers the pixel at the given
+    /// column, if picking is enabled.
     #[inline]
-    fn is_point_inside(&self, point: Vector2<i32>) -> bool {
-        point.x >= 0 && point.y >= 0
-            && (point.x as usize) < self.extent.width
-            && (point.y as usize) < self.extent.height
+    fn write_id(&mut self, x: usize, id: u32) {
+        if let Some(ids) = self.ids.as_deref_mut() {
+            ids[x] = Some(id);
+        }
     }
 
-    /// Resizes the swap chain image, using the specified color as the clear
-    /// color. To actually resize the swap chain and it's colors inside (without
-    /// clearing them) would be a waste, since we can just redraw instead.
-    pub fn resize_with_clear_color(&mut self, size: LogicalSize<u32>, color: Pixel) {
-        self.extent = Extent {
-            width: size.width as _,
-            height: size.height as _,
+}
+
+/// Splits the range of the buffer into rows, or gives `None` for every row
+/// when there is no buffer.
+fn split_rows<T>(buffer: Option<&mut [T]>, range: Range<usize>, row_length: usize)
+        -> impl Iterator<Item = Option<&mut [T]>> {
+    buffer.into_iter()
+        .flat_map(move |buffer| buffer[range.clone()].chunks_mut(row_length).map(Some))
+        .chain(std::iter::repeat_with(|| None))
+}
+
+/// Everything that is needed to fill the rows of a single triangle.
+struct RowRasterizer<'a, U: ?Sized, F: FragmentShader<U> + ?Sized> {
+    edges: [EdgeFunction; 3],
+    interpolator: &'a Interpolator,
+    index: u32,
+    state: OutputState,
+    fragment_shader: &'a F,
+    uniforms: &'a U,
+    sample_count: SampleCount,
+    fill_algorithm: FillAlgorithm,
+}
+
+impl<'a, U: ?Sized, F: FragmentShader<U> + ?Sized> RowRasterizer<'a, U, F> {
+
+    /// Fills the pixels or samples of a single row, depending on the sample
+    /// count and the fill algorithm, counting the pixels that are shaded.
+    #[inline]
+    fn rasterize<P: PixelFormat>(&self, row: RowTarget<P>, y: i32, bounds: &Rectangle2D<i32>) -> RenderStats {
+        match (self.sample_count, self.fill_algorithm) {
+            (SampleCount::X1, FillAlgorithm::HalfSpace) => self.rasterize_row(row, y, bounds),
+            (SampleCount::X1, FillAlgorithm::Scanline) => self.rasterize_span(row, y, bounds),
+            _ => self.rasterize_row_multisampled(row, y, bounds),
+        }
+    }
+
+    /// Fills the pixels of a single row of the buffer that are covered by the
+    /// triangle described by the edge functions. The edge functions are
+    /// evaluated once at the start of the row, and then stepped
+    /// incrementally.
+    fn rasterize_row<P: PixelFormat>(&self, mut row: RowTarget<P>, y: i32, bounds: &Rectangle2D<i32>)
+            -> RenderStats {
+        let mut values = self.edges.map(|edge| edge.evaluate(Vector2::new(bounds.x(), y)));
+        let mut stats = RenderStats::default();
+
+        for x in bounds.x_range() {
+            if values.iter().all(|value| *value >= 0) {
+                self.write_fragment(&mut row, x, y, &mut stats);
+            }
+
+            for (value, edge) in values.iter_mut().zip(&self.edges) {
+                *value += edge.step_x();
+            }
+        }
+        stats
+    }
+
+    /// Fills the pixels of a single row of the buffer that are covered by the
+    /// triangle, by solving the edge functions for the columns where they
+    /// become negative, so only the pixels of the covered span are visited.
+    fn rasterize_span<P: PixelFormat>(&self, mut row: RowTarget<P>, y: i32, bounds: &Rectangle2D<i32>)
+            -> RenderStats {
+        let mut stats = RenderStats::default();
+        for x in self.span(y, bounds) {
+            self.write_fragment(&mut row, x, y, &mut stats);
+        }
+        stats
+    }
+
+    /// Computes the columns of the row within the bounds at which all of the
+    /// edge functions are non-negative, which are consecutive since the
+    /// triangle is convex.
+    fn span(&self, y: i32, bounds: &Rectangle2D<i32>) -> Range<i32> {
+        let origin = bounds.x() as i64;
+        let (mut start, mut end) = (origin, bounds.x_range().end as i64);
+        for edge in &self.edges {
+            // Along the row, the function is `value + step * (x - origin)`.
+            let value = edge.evaluate(Vector2::new(bounds.x(), y));
+            let step = edge.step_x();
+            match step.cmp(&0) {
+                Ordering::Greater => start = start.max(origin - value.div_euclid(step)),
+                Ordering::Less => end = end.min(origin + value.div_euclid(-step) + 1),
+                Ordering::Equal if value < 0 => return 0..0,
+                Ordering::Equal => (),
+            }
+        }
+        start as i32..end.max(start) as i32
+    }
+
+    /// Writes the fragment of the triangle at the pixel, which the triangle
+    /// covers, when it passes the alpha, stencil and depth tests.
+    #[inline]
+    fn write_fragment<P: PixelFormat>(&self, row: &mut RowTarget<P>, x: i32, y: i32, stats: &mut RenderStats) {
+        let depth = || self.interpolator.depth(Vector2f::new(x as f32, y as f32));
+        let mut color = None;
+        if self.alpha_test(&mut color, x, y) && self.state.test(row, x as usize, depth) {
+            row.write_id(x as usize, self.index);
+            if self.state.color_write {
+                let color = *color.get_or_insert_with(|| self.shade(x, y));
+                row.write(&self.state, x as usize, color);
+                stats.pixels_shaded += 1;
+            }
+        }
+        stats.fragment_shader_invocations += color.is_some() as u64;
+    }
+
+    /// Fills the samples of a single row of the multisample buffer that are
+    /// covered by the triangle described by the edge functions, which are in
+    /// subpixel coordinates. The fragment shader runs once for every pixel of
+    /// which at least one sample is covered.
+    fn rasterize_row_multisampled<P: PixelFormat>(&self, mut row: RowTarget<P>, y: i32,
+            bounds: &Rectangle2D<i32>) -> RenderStats {
+        let precision = self.sample_count.precision();
+        let offsets = self.sample_count.offsets();
+        let origin = Vector2::new(bounds.x() * precision, y * precision);
+
+        let mut values = [[0; 3]; 4];
+        for (sample_values, offset) in values.iter_mut().zip(offsets) {
+            let position = Vector2::new(origin.x + offset.x, origin.y + offset.y);
+            *sample_values = self.edges.map(|edge| edge.evaluate(position));
+        }
+        let values = &mut values[..offsets.len()];
+        let mut stats = RenderStats::default();
+
+        for x in bounds.x_range() {
+            let mut color = None;
+            let mut written = false;
+
+            for (sample, (sample_values, offset)) in values.iter_mut().zip(offsets).enumerate() {
+                let index = x as usize * offsets.len() + sample;
+                let covered = sample_values.iter().all(|value| *value >= 0);
+                let depth = || {
+                    let position = Vector2f::new(
+                        x as f32 + offset.x as f32 / precision as f32,
+                        y as f32 + offset.y as f32 / precision as f32,
+                    );
+                    self.interpolator.depth(position)
+                };
+                if covered && self.alpha_test(&mut color, x, y) && self.state.test(&mut row, index, depth) {
+                    row.write_id(x as usize, self.index);
+                    if self.state.color_write {
+                        let color = *color.get_or_insert_with(|| self.shade(x, y));
+                        row.write(&self.state, index, color);
+                        written = true;
+                    }
+                }
+
+                for (value, edge) in sample_values.iter_mut().zip(&self.edges) {
+                    *value += edge.step_x() * precision as i64;
+                }
+            }
+
+            stats.pixels_shaded += written as u64;
+            stats.fragment_shader_invocations += color.is_some() as u64;
+        }
+        stats
+    }
+
+    /// Runs the alpha test for the pixel, if there is one, shading the pixel
+    /// into the color when it hasn't been shaded yet. Since the fragment
+    /// shader has to run before the stencil and depth tests, a discarded
+    /// fragment doesn't change the stencil or depth buffer.
+    #[inline]
+    fn alpha_test(&self, color: &mut Option<Pixel>, x: i32, y: i32) -> bool {
+        let Some(threshold) = self.state.alpha_test else {
+            return true;
         };
-        self.buffer = create_pixel_buffer(size.width as _, size.height as _, color)
+        color.get_or_insert_with(|| self.shade(x, y)).alpha() >= threshold
     }
 
+    /// Runs the fragment shader for the pixel.
     #[inline]
-    fn set_pixel(&mut self, point: Vector2<i32>, color: Pixel) {
-        let point = Vector2::new(point.x as usize, point.y as usize);
-        self.buffer[point.y * self.extent.width + point.x] = color;
+    fn shade(&self, x: i32, y: i32) -> Pixel {
+        let input = FragmentInput::new(Vector2f::new(x as f32, y as f32), self.interpolator);
+        self.fragment_shader.run(&input, self.uniforms)
+    }
+
+}
+
+/// Rounds the position, in pixels, to a subpixel of the given precision.
+#[inline]
+fn round_to_subpixel(position: Vector2f, precision: i32, rounding: Rounding) -> Vector2<i32> {
+    let precision = precision as f32;
+    Vector2f::new(position.x * precision, position.y * precision).to_i32(rounding)
+}
+
+/// Creates a buffer of pixels, or other values stored per pixel, for the given
+/// size.
+fn create_buffer<T: Clone>(width: usize, height: usize, value: T)
+        -> Result<Vec<T>, SwapChainError> {
+    let length = width.checked_mul(height).ok_or(SwapChainError::TooLarge)?;
+    let size_in_bytes = length.checked_mul(std::mem::size_of::<T>()).ok_or(SwapChainError::TooLarge)?;
+    if size_in_bytes > isize::MAX as usize {
+        return Err(SwapChainError::TooLarge);
+    }
+
+    let mut vec = Vec::new();
+    vec.try_reserve_exact(length).map_err(|_| SwapChainError::AllocationFailed)?;
+    vec.resize(length, value);
+    Ok(vec)
+}
+
+/// Creates the buffer holding the samples of every pixel, which is empty when
+/// there is only a single sample per pixel.
+fn create_sample_buffer<P: PixelFormat>(extent: Extent, sample_count: SampleCount, color: P)
+        -> Result<Vec<P>, SwapChainError> {
+    match sample_count {
+        SampleCount::X1 => Ok(Vec::new()),
+        sample_count => {
+            let row_length = extent.width.checked_mul(sample_count.count()).ok_or(SwapChainError::TooLarge)?;
+            create_buffer(row_length, extent.height, color)
+        }
+    }
+}
+
+/// Creates a buffer holding the value for every sample, such as the stencil
+/// buffer. Unlike the sample buffer, it also has a value for every pixel
+/// when there is a single sample per pixel.
+fn create_per_sample_buffer<T: Clone>(extent: Extent, sample_count: SampleCount, value: T)
+        -> Result<Vec<T>, SwapChainError> {
+    let row_length = extent.width.checked_mul(sample_count.count()).ok_or(SwapChainError::TooLarge)?;
+    create_buffer(row_length, extent.height, value)
+}
+
+impl SwapChain {
+
+    /// Creates a swap chain of the given size, with 8-bit RGBA pixels.
+    ///
+    /// # Panics
+    /// Panics when the width or height is zero, or when the pixel buffer is
+    /// too large to be allocated. Use [`SwapChain::try_new`] to handle these
+    /// cases gracefully.
+    #[cfg(feature = "winit")]
+    pub fn new(size: LogicalSize<u32>) -> Self {
+        Self::with_format(size)
+    }
+
+    /// Creates a swap chain of the given size, with 8-bit RGBA pixels,
+    /// failing when the width or height is zero, or when the pixel buffer
+    /// can't be allocated.
+    #[cfg(feature = "winit")]
+    pub fn try_new(size: LogicalSize<u32>) -> Result<Self, SwapChainError> {
+        Self::try_with_format(size)
     }
 
-    fn vertex_to_pixel_position(&self, vertex: Vector2f) -> Vector2<i32> {
-        let x = ((vertex.x + 1.0) / 2.0 * self.extent.width as f32).round() as _;
-        let y = ((vertex.y + 1.0) / 2.0 * self.extent.height as f32).round() as _;
-        Vector2::new(x, y)
+    /// Creates a swap chain of which the buffer has the given extent in
+    /// physical pixels, with 8-bit RGBA pixels and a scale factor of 1.
+    ///
+    /// # Panics
+    /// Panics when the width or height is zero, or when the pixel buffer is
+    /// too large to be allocated. Use [`SwapChain::try_with_extent`] to
+    /// handle these cases gracefully.
+    pub fn with_extent(extent: Extent) -> Self {
+        match Self::try_with_extent(extent) {
+            Ok(swap_chain) => swap_chain,
+            Err(error) => {
+                panic!("failed to create swap chain of {}x{}: {:?}", extent.width, extent.height, error)
+            }
+        }
     }
 
+    /// Creates a swap chain of which the buffer has the given extent in
+    /// physical pixels, with 8-bit RGBA pixels, failing when the width or
+    /// height is zero, or when the pixel buffer can't be allocated.
+    pub fn try_with_extent(extent: Extent) -> Result<Self, SwapChainError> {
+        Self::try_with_format_extent(extent)
+    }
+
+    /// Creates a swap chain of the given logical size, with 8-bit RGBA
+    /// pixels, of which the buffer has the size in physical pixels for the
+    /// scale factor of the display, such as the one of
+    /// [`Window::scale_factor`](winit::window::Window::scale_factor).
+    ///
+    /// # Panics
+    /// Panics when the scale factor isn't a positive number, when the
+    /// physical width or height is zero, or when the pixel buffer is too
+    /// large to be allocated.
+    #[cfg(feature = "winit")]
+    pub fn with_scale_factor(size: LogicalSize<u32>, scale_factor: f64) -> Self {
+        match Self::try_with_scale_factor(size, scale_factor) {
+            Ok(swap_chain) => swap_chain,
+            Err(error) => panic!("failed to create swap chain of {}x{} at scale {}: {:?}",
+                size.width, size.height, scale_factor, error),
+        }
+    }
+
+}
+
+impl<P: PixelFormat> SwapChain<P> {
+
+    /// Creates a swap chain of the given size, of which the pixels are stored
+    /// in the pixel format `P`.
+    ///
+    /// # Panics
+    /// Panics when the width or height is zero, or when the pixel buffer is
+    /// too large to be allocated. Use [`SwapChain::try_with_format`] to
+    /// handle these cases gracefully.
+    #[cfg(feature = "winit")]
+    pub fn with_format(size: LogicalSize<u32>) -> Self {
+        match Self::try_with_format(size) {
+            Ok(swap_chain) => swap_chain,
+            Err(error) => panic!("failed to create swap chain of {}x{}: {:?}", size.width, size.height, error),
+        }
+    }
+
+    /// Creates a swap chain of the given size, of which the pixels are stored
+    /// in the pixel format `P`, failing when the width or height is zero, or
+    /// when the pixel buffer can't be allocated.
+    #[cfg(feature = "winit")]
+    pub fn try_with_format(size: LogicalSize<u32>) -> Result<Self, SwapChainError> {
+        Self::try_with_scale_factor(size, 1.0)
+    }
+
+    /// Creates a swap chain of the given logical size, of which the buffer
+    /// has the size in physical pixels for the scale factor, failing when the
+    /// physical width or height is zero, or when the pixel buffer can't be
+    /// allocated.
+    ///
+    /// # Panics
+    /// Panics when the scale factor isn't a positive number.
+    #[cfg(feature = "winit")]
+    pub fn try_with_scale_factor(size: LogicalSize<u32>, scale_factor: f64) -> Result<Self, SwapChainError> {
+        let size: PhysicalSize<u32> = size.to_physical(scale_factor);
+        let extent = Extent {
+            width: size.width as _,
+            height: size.height as _,
+        };
+        Self::try_with_extent_and_scale_factor(extent, scale_factor)
+    }
+
+    /// Creates a swap chain of which the buffer has the given extent in
+    /// physical pixels, and of which the pixels are stored in the pixel
+    /// format `P`, failing when the width or height is zero, or when the
+    /// pixel buffer can't be allocated. The scale factor is 1.
+    pub fn try_with_format_extent(extent: Extent) -> Result<Self, SwapChainError> {
+        Self::try_with_extent_and_scale_factor(extent, 1.0)
+    }
+
+    fn try_with_extent_and_scale_factor(extent: Extent, scale_factor: f64) -> Result<Self, SwapChainError> {
+        if extent.is_empty() {
+            return Err(SwapChainError::ZeroExtent);
+        }
+
+        Ok(Self {
+            extent,
+            scale_factor,
+            buffer: create_buffer(extent.width, extent.height, P::from_pixel(Pixel::BLACK))?,
+            front_buffers: VecDeque::new(),
+            sample_count: SampleCount::default(),
+            samples: Vec::new(),
+            alpha_mode: AlphaMode::default(),
+            state: RasterizerState::default(),
+            stencil: None,
+            depth: None,
+            ids: None,
+            dirty: None,
+            accumulation: None,
+            stats: None,
+        })
+    }
+
+    /// Gets the way in which the alpha channel is stored in the buffer.
+    pub fn alpha_mode(&self) -> AlphaMode {
+        self.alpha_mode
+    }
+
+    /// Gets the alpha below which fragments of triangles are discarded, if
+    /// any.
+    pub fn alpha_test(&self) -> Option<u8> {
+        self.state.alpha_test
+    }
+
+    /// Gets the pixels of the back buffer row by row, starting at the top,
+    /// as they are stored, i.e. in the [alpha mode](SwapChain::alpha_mode)
+    /// of the swap chain. When multisampling, the pixels are only up to date
+    /// after [resolving](SwapChain::resolve) the samples.
+    pub fn as_slice(&self) -> &[P] {
+        &self.buffer
+    }
+
+    /// Gets the blend mode used when writing fragments to the swap chain.
+    pub fn blend_mode(&self) -> BlendMode {
+        self.state.blend_mode
+    }
+
+    /// Gets the color space in which fragments are blended.
+    pub fn blend_space(&self) -> ColorSpace {
+        self.state.blend_space
+    }
+
+    /// Gets the number of buffers that the frames cycle through.
+    pub fn buffer_count(&self) -> usize {
+        self.front_buffers.len() + 1
+    }
+
+    /// Gets the way the coverage of pixels is determined.
+    pub fn conservative_raster(&self) -> ConservativeRaster {
+        self.state.conservative_raster
+    }
+
+    /// Gets the space of the positions of the vertices.
+    pub fn coordinate_space(&self) -> CoordinateSpace {
+        self.state.coordinate_space
+    }
+
+    /// Checks whether fragments are written to the color buffer.
+    pub fn color_write(&self) -> bool {
+        self.state.color_write
+    }
+
+    /// Gets the triangles that are discarded based on the way they face.
+    pub fn cull_mode(&self) -> CullMode {
+        self.state.cull_mode
+    }
+
+    /// Gets the rectangle, in pixels, around the pixels that may have changed
+    /// since the dirty rectangle was last [reset](SwapChain::reset_dirty), or
+    /// `None` when nothing changed.
+    pub fn dirty_rect(&self) -> Option<Rectangle2D<i32>> {
+        self.dirty
+    }
+
+    /// Gets the comparison of the depth of fragments against the depth buffer,
+    /// if any.
+    pub fn depth_test(&self) -> Option<CompareFunction> {
+        self.state.depth_test
+    }
+
+    /// Checks whether the depth of fragments passing the depth test is
+    /// written to the depth buffer.
+    pub fn depth_write(&self) -> bool {
+        self.state.depth_write
+    }
+
+    /// Gets the size of the buffer in physical pixels.
+    pub fn extent(&self) -> Extent {
+        self.extent
+    }
+
+    /// Gets the way the covered pixels of every row are found.
+    pub fn fill_algorithm(&self) -> FillAlgorithm {
+        self.state.fill_algorithm
+    }
+
+    /// Checks whether fragments are blended into colors with a float per
+    /// channel, see [`SwapChain::set_float_accumulation`].
+    pub fn float_accumulation(&self) -> bool {
+        self.accumulation.is_some()
+    }
+
+    /// Gets the winding order of triangles that face the viewer.
+    pub fn front_face(&self) -> FrontFace {
+        self.state.front_face
+    }
+
+    /// Reads back the color of the pixel at the point with straight alpha,
+    /// or `None` when the point lies outside of the extent. When
+    /// multisampling, the samples of the pixel are resolved.
+    pub fn get_pixel(&self, point: Vector2<i32>) -> Option<Pixel> {
+        let x = usize::try_from(point.x).ok().filter(|x| *x < self.extent.width)?;
+        let y = usize::try_from(point.y).ok().filter(|y| *y < self.extent.height)?;
+        Some(self.resolved_pixel(y * self.extent.width + x))
+    }
+
+    /// Gets the way the varyings are interpolated in smooth shading.
+    pub fn interpolation(&self) -> Interpolation {
+        self.state.interpolation
+    }
+
+    /// Gets the number of physical pixels per logical pixel.
+    pub fn scale_factor(&self) -> f64 {
+        self.scale_factor
+    }
+
+    /// Gets the number of samples stored for every pixel.
+    pub fn sample_count(&self) -> SampleCount {
+        self.sample_count
+    }
+
+    /// Checks whether the index of the triangle covering every pixel is kept
+    /// for [picking](SwapChain::pick).
+    pub fn picking(&self) -> bool {
+        self.ids.is_some()
+    }
+
+    /// Iterates over every pixel with its position, row by row from the top
+    /// left, so the position of the pixel at index `i` of
+    /// [`SwapChain::as_slice`] is `(i % width, i / width)`. The colors are
+    /// read back just like [`SwapChain::get_pixel`].
+    pub fn pixels(&self) -> impl Iterator<Item = (Vector2<i32>, Pixel)> + '_ {
+        let width = self.extent.width;
+        (0..self.buffer.len()).map(move |index| {
+            (Vector2::new((index % width) as i32, (index / width) as i32), self.resolved_pixel(index))
+        })
+    }
+
+    /// Gets the scissor rectangle, in pixels. `None` means the full extent.
+    pub fn scissor(&self) -> Option<Rectangle2D<i32>> {
+        self.state.scissor
+    }
+
+    /// Gets the way the varyings are passed to the fragment shader.
+    pub fn shading_mode(&self) -> ShadingMode {
+        self.state.shading_mode
+    }
+
+    /// Gets the state that draws are rasterized with.
+    pub fn state(&self) -> &RasterizerState {
+        &self.state
+    }
+
+    /// Gets the work done since the statistics were last
+    /// [taken](SwapChain::take_stats), or `None` when they are disabled.
+    pub fn stats(&self) -> Option<RenderStats> {
+        self.stats
+    }
+
+    /// Gets the stencil test that draws are masked with, if any.
+    pub fn stencil_test(&self) -> Option<StencilTest> {
+        self.state.stencil_test
+    }
+
+    /// Gets the way the positions of vertices are snapped to subpixels.
+    pub fn vertex_rounding(&self) -> Rounding {
+        self.state.vertex_rounding
+    }
+
+    /// Gets whether off-screen triangles are culled before vertex shading.
+    pub fn viewport_cull(&self) -> ViewportCull {
+        self.state.viewport_cull
+    }
+
+    /// Gets the direction in which `y` increases in normalized device
+    /// coordinates.
+    pub fn y_axis(&self) -> YAxis {
+        self.state.y_axis
+    }
+
+    /// Completes the frame drawn into the back buffer, which becomes the one
+    /// that is presented, and acquires the oldest buffer to draw the next
+    /// frame into. That buffer still holds an older frame, so it's usually
+    /// cleared first.
+    ///
+    /// This does nothing with a single buffer, where the frame is presented
+    /// while it's being drawn.
+    pub fn acquire(&mut self) {
+        let Some(next) = self.front_buffers.pop_front() else {
+            return;
+        };
+
+        self.resolve();
+        let completed = std::mem::replace(&mut self.buffer, next);
+        self.front_buffers.push_back(completed);
+        self.sync_accumulation();
+        self.mark_all_dirty();
+    }
+
+    /// Copies the image of the given extent into the destination rectangle,
+    /// in pixels, scaling it using the filter. The pixels of the image are
+    /// specified with straight alpha, and are blended like fragments. Only
+    /// the part of the rectangle inside of the render area is written.
+    ///
+    /// # Panics
+    /// Panics when the length of the image doesn't match its extent.
+    pub fn blit(&mut self, src: &[Pixel], src_extent: Extent, dst_rect: Rectangle2D<i32>, filter: Filter) {
+        assert_eq!(src.len(), src_extent.width * src_extent.height, "image doesn't match its extent");
+        if src.is_empty() {
+            return;
+        }
+
+        let Some(bounds) = self.render_area().and_then(|area| area.intersect(&dst_rect)) else {
+            return;
+        };
+
+        // Maps the centers of the destination pixels onto the image.
+        let scale_x = src_extent.width as f32 / dst_rect.width() as f32;
+        let scale_y = src_extent.height as f32 / dst_rect.height() as f32;
+        let state = self.output_state();
+
+        for y in bounds.y_range() {
+            let source_y = (y - dst_rect.y()) as f32 * scale_y + (scale_y - 1.0) / 2.0;
+            for x in bounds.x_range() {
+                let source_x = (x - dst_rect.x()) as f32 * scale_x + (scale_x - 1.0) / 2.0;
+                let color = filter.sample(src, src_extent, Vector2f::new(source_x, source_y));
+                self.write_pixel(&state, x as usize, y as usize, color);
+            }
+        }
+    }
+
+    /// Clears the buffer with the given color, specified with straight alpha.
+    /// The pixels of the buffer are sRGB-encoded, so the channels of the
+    /// color are stored as they are, see [`SwapChain::clear_linear`] to clear
+    /// with a color in linear light.
+    pub fn clear(&mut self, color: Pixel) {
+        let value = P::from_pixel(self.alpha_mode.encode(color));
+        P::fill(&mut self.buffer, value);
+        P::fill(&mut self.samples, value);
+        if let Some(ids) = &mut self.ids {
+            ids.fill(None);
+        }
+        self.sync_accumulation();
+        self.mark_all_dirty();
+    }
+
+    /// Clears the buffer with the color given as normalized linear `[red,
+    /// green, blue, alpha]` values, with straight alpha, which is encoded to
+    /// sRGB first, see [`Pixel::from_linear`]. Clearing with a linear value
+    /// of 0.5 thus stores a lighter pixel than clearing with `0x80`.
+    pub fn clear_linear(&mut self, color: [f32; 4]) {
+        self.clear(Pixel::from_linear(color));
+    }
+
+    /// Clears the rectangle, in pixels, with the color specified with straight
+    /// alpha, just like [`SwapChain::clear`] clears the whole buffer. Only the
+    /// part of the rectangle inside of the extent is cleared, regardless of
+    /// the scissor rectangle, and only that part is marked as dirty, so that
+    /// a partial redraw only needs to [present](SwapChain::present_dirty)
+    /// that part.
+    pub fn clear_rect(&mut self, rect: Rectangle2D<i32>, color: Pixel) {
+        let width = self.extent.width.min(i32::MAX as usize) as i32;
+        let height = self.extent.height.min(i32::MAX as usize) as i32;
+        let extent = Rectangle2D::new(Vector2::new(0, 0), Vector2::new(width, height));
+        let Some(bounds) = extent.intersect(&rect) else {
+            return;
+        };
+
+        let value = P::from_pixel(self.alpha_mode.encode(color));
+        let normalized = self.alpha_mode.decode(value.to_pixel()).to_normalized();
+        let count = self.sample_count.count();
+        for y in bounds.y_range() {
+            let start = y as usize * self.extent.width + bounds.x_range().start as usize;
+            let pixels = start..start + bounds.width() as usize;
+            let samples = pixels.start * count..pixels.end * count;
+            P::fill(&mut self.buffer[pixels.clone()], value);
+            if self.sample_count != SampleCount::X1 {
+                P::fill(&mut self.samples[samples.clone()], value);
+            }
+            if let Some(ids) = &mut self.ids {
+                ids[pixels.clone()].fill(None);
+            }
+            if let Some(accumulation) = &mut self.accumulation {
+                let elements = match self.sample_count {
+                    SampleCount::X1 => pixels,
+                    _ => samples,
+                };
+                accumulation[elements].fill(normalized);
+            }
+        }
+        self.mark_dirty(bounds);
+    }
+
+    /// Clears the depth buffer to the given depth, in normalized device
+    /// coordinates, creating it when it doesn't exist yet. The far plane is
+    /// at a depth of 1.
+    ///
+    /// # Panics
+    /// Panics when the depth buffer is too large to be allocated.
+    pub fn clear_depth(&mut self, depth: f32) {
+        self.depth_buffer().fill(depth);
+    }
+
+    /// Clears the stencil buffer to the given value, creating it when it
+    /// doesn't exist yet.
+    ///
+    /// # Panics
+    /// Panics when the stencil buffer is too large to be allocated.
+    pub fn clear_stencil(&mut self, value: u8) {
+        self.stencil_buffer().fill(value);
+    }
+
+    /// Scales the pixels down to the target extent with the filter, e.g. to
+    /// anti-alias the edges of a frame that was rendered at twice the size,
+    /// and returns the pixels, row by row, with straight alpha. The centers
+    /// of the target pixels are mapped onto the buffer like
+    /// [`SwapChain::blit`] does, so halving the size with
+    /// [`Filter::Bilinear`] averages every block of 2x2 pixels. When
+    /// multisampling, the samples are resolved first.
+    ///
+    /// The bilinear filter only reads the four pixels around every center,
+    /// so scaling down by more than half skips pixels, which is why larger
+    /// factors should be applied in multiple steps.
+    pub fn downscale(&self, target: Extent, filter: Filter) -> Vec<Pixel> {
+        if self.extent.is_empty() {
+            return vec![Pixel::TRANSPARENT; target.width * target.height];
+        }
+
+        let pixels: Vec<Pixel> = (0..self.buffer.len()).map(|index| self.resolved_pixel(index)).collect();
+        let scale_x = self.extent.width as f32 / target.width as f32;
+        let scale_y = self.extent.height as f32 / target.height as f32;
+        (0..target.height)
+            .flat_map(|y| (0..target.width).map(move |x| (x, y)))
+            .map(|(x, y)| {
+                let source = Vector2f::new(
+                    x as f32 * scale_x + (scale_x - 1.0) / 2.0,
+                    y as f32 * scale_y + (scale_y - 1.0) / 2.0,
+                );
+                filter.sample(&pixels, self.extent, source)
+            })
+            .collect()
+    }
+
+    /// Draws the triangles. The vertex shader can either be a [`VertexShader`]
+    /// or a [`VertexShader2D`](crate::shader::VertexShader2D), and the
+    /// fragment shader a [`FragmentShader`] or a
+    /// [`FragmentShader2D`](crate::shader::FragmentShader2D).
+    ///
+    /// The vertex shader outputs positions in clip space, which are in the
+    /// [coordinate space](SwapChain::set_coordinate_space) of the swap chain
+    /// after the perspective divide. By default, these are normalized device
+    /// coordinates, from -1 to 1 across the swap chain.
+    pub fn draw_rasterized(&mut self, vertices: &[TriangleVertices], vertex_shader: &dyn VertexShader,
+            fragment_shader: &dyn FragmentShader) {
+        self.draw_rasterized_with_uniforms(vertices, vertex_shader, fragment_shader, &());
+    }
+
+    /// Draws the triangles just like [`SwapChain::draw_rasterized`], but with
+    /// the positions of the vertices in [pixels](CoordinateSpace::Pixels),
+    /// regardless of the coordinate space of the swap chain.
+    pub fn draw_rasterized_px(&mut self, vertices: &[TriangleVertices], vertex_shader: &dyn VertexShader,
+            fragment_shader: &dyn FragmentShader) {
+        let coordinate_space = std::mem::replace(&mut self.state.coordinate_space, CoordinateSpace::Pixels);
+        self.draw_rasterized(vertices, vertex_shader, fragment_shader);
+        self.state.coordinate_space = coordinate_space;
+    }
+
+    /// Draws the triangles just like [`SwapChain::draw_rasterized`], passing
+    /// the uniforms to every invocation of the shaders.
+    pub fn draw_rasterized_with_uniforms<U: ?Sized>(&mut self, vertices: &[TriangleVertices],
+            vertex_shader: &dyn VertexShader<U>, fragment_shader: &dyn FragmentShader<U>, uniforms: &U) {
+        let Some(clip_rectangle) = self.clip_rectangle() else {
+            return;
+        };
+
+        for (index, vertex_triple) in vertices.iter().enumerate() {
+            let triangles = self.assemble_triangles(vertex_triple, index, vertex_shader, uniforms,
+                &clip_rectangle);
+            for triangle in triangles {
+                self.rasterize_triangle(&triangle, fragment_shader, uniforms);
+            }
+        }
+    }
+
+    /// Draws the triangles that the vertices form with the topology, of which
+    /// the attributes are taken from the arrays at the same index, like the
+    /// vertex buffers of a GPU. Without colors, every vertex is white, and
+    /// without texture coordinates, they are zero. The triangles are drawn
+    /// just like [`SwapChain::draw_rasterized`], and numbered in the order
+    /// of the topology.
+    ///
+    /// # Panics
+    /// Panics when the colors or the texture coordinates don't have an
+    /// element for every position.
+    pub fn draw_arrays(&mut self, positions: &[Vector2f], colors: Option<&[Pixel]>, uvs: Option<&[Vector2f]>,
+            topology: PrimitiveTopology, vertex_shader: &dyn VertexArrayShader,
+            fragment_shader: &dyn FragmentShader) {
+        if let Some(colors) = colors {
+            assert_eq!(colors.len(), positions.len(), "every position must have a color");
+        }
+        if let Some(uvs) = uvs {
+            assert_eq!(uvs.len(), positions.len(), "every position must have texture coordinates");
+        }
+        let Some(clip_rectangle) = self.clip_rectangle() else {
+            return;
+        };
+
+        let vertex = |index: usize| Vertex {
+            position: positions[index],
+            color: colors.map_or(Pixel::WHITE, |colors| colors[index]),
+            uv: uvs.map_or(Vector2f::default(), |uvs| uvs[index]),
+        };
+        for index in 0..topology.triangle_count(positions.len()) {
+            let [a, b, c] = topology.triangle(index);
+            let vertex_triple = TriangleVertices::new(positions[a], positions[b], positions[c]);
+            let triangles = self.assemble_shaded(&vertex_triple, index, &clip_rectangle, || {
+                [a, b, c].map(|index| vertex_shader.run(&vertex(index)))
+            });
+            for triangle in triangles {
+                self.rasterize_triangle(&triangle, fragment_shader, &());
+            }
+        }
+    }
+
+    /// Draws the triangles once for every instance, of which the vertices are
+    /// transformed by the transform of that instance before they are passed
+    /// to the vertex shader.
+    pub fn draw_instanced(&mut self, vertices: &[TriangleVertices], instance_transforms: &[Matrix3],
+            vertex_shader: &dyn VertexShader, fragment_shader: &dyn FragmentShader) {
+        let Some(clip_rectangle) = self.clip_rectangle() else {
+            return;
+        };
+
+        for transform in instance_transforms {
+            for (index, vertex_triple) in vertices.iter().enumerate() {
+                let vertex_triple = TriangleVertices::new(
+                    transform.transform_point(vertex_triple.a),
+                    transform.transform_point(vertex_triple.b),
+                    transform.transform_point(vertex_triple.c),
+                );
+
+                let triangles = self.assemble_triangles(&vertex_triple, index, vertex_shader, &(),
+                    &clip_rectangle);
+                for triangle in triangles {
+                    self.rasterize_triangle(&triangle, fragment_shader, &());
+                }
+            }
+        }
+    }
+
+    /// Draws the triangles just like [`SwapChain::draw_rasterized`], but
+    /// splits the rows of every triangle across threads.
+    #[cfg(feature = "rayon")]
+    pub fn draw_rasterized_parallel(&mut self, vertices: &[TriangleVertices],
+            vertex_shader: &dyn VertexShader, fragment_shader: &(dyn FragmentShader + Sync)) {
+        let Some(clip_rectangle) = self.clip_rectangle() else {
+            return;
+        };
+
+        for (index, vertex_triple) in vertices.iter().enumerate() {
+            let triangles = self.assemble_triangles(vertex_triple, index, vertex_shader, &(),
+                &clip_rectangle);
+            for triangle in triangles {
+                self.rasterize_triangle_parallel(&triangle, fragment_shader);
+            }
+        }
+    }
+
+    /// Draws the triangles just like [`SwapChain::draw_rasterized`], but from
+    /// back to front, i.e. sorted by the average depth of their vertices,
+    /// starting at the one farthest away. This is the painter's algorithm,
+    /// which blends overlapping transparent triangles correctly as long as
+    /// they don't intersect. Triangles at the same depth are drawn in the
+    /// order they are given.
+    pub fn draw_sorted(&mut self, vertices: &[TriangleVertices], vertex_shader: &dyn VertexShader,
+            fragment_shader: &dyn FragmentShader) {
+        let Some(clip_rectangle) = self.clip_rectangle() else {
+            return;
+        };
+
+        let precision = self.sample_count.precision();
+        let mut triangles: Vec<(f32, AssembledTriangle)> = vertices.iter()
+            .enumerate()
+            .flat_map(|(index, vertex_triple)| {
+                self.assemble_triangles(vertex_triple, index, vertex_shader, &(), &clip_rectangle)
+            })
+            .map(|triangle| (triangle.average_depth(precision), triangle))
+            .collect();
+        triangles.sort_by(|(a, _), (b, _)| b.total_cmp(a));
+
+        for (_, triangle) in &triangles {
+            self.rasterize_triangle(triangle, fragment_shader, &());
+        }
+    }
+
+    /// Maps every pixel of the buffer in place, given its position and its
+    /// color with straight alpha, e.g. to invert the colors or to apply a
+    /// vignette before presenting. When multisampling, the samples are
+    /// resolved first, and every sample of a pixel is set to its new color.
+    pub fn post_process(&mut self, f: impl Fn(Vector2<i32>, Pixel) -> Pixel) {
+        self.resolve();
+
+        let width = self.extent.width;
+        for (index, value) in self.buffer.iter_mut().enumerate() {
+            let position = Vector2::new((index % width) as i32, (index / width) as i32);
+            let color = f(position, self.alpha_mode.decode(value.to_pixel()));
+            *value = P::from_pixel(self.alpha_mode.encode(color));
+        }
+
+        let count = self.sample_count.count();
+        if count > 1 {
+            for (samples, pixel) in self.samples.chunks_exact_mut(count).zip(&self.buffer) {
+                samples.fill(*pixel);
+            }
+        }
+        self.sync_accumulation();
+        self.mark_all_dirty();
+    }
+
+    /// Iterates mutably over every pixel of the buffer with its position, in
+    /// the same order as [`SwapChain::pixels`]. The values are as they are
+    /// stored, in the pixel format and the alpha mode of the swap chain, and
+    /// the whole extent is marked as dirty.
+    ///
+    /// Returns `None` when multisampling or when float accumulation is
+    /// enabled, since the buffer is then replaced by the samples or the
+    /// accumulated colors, which would discard the changes. Use
+    /// [`SwapChain::post_process`] in those cases, which updates them.
+    pub fn pixels_mut(&mut self) -> Option<impl Iterator<Item = (Vector2<i32>, &mut P)>> {
+        if self.sample_count != SampleCount::X1 || self.accumulation.is_some() {
+            return None;
+        }
+
+        self.mark_all_dirty();
+        let width = self.extent.width;
+        Some(self.buffer.iter_mut().enumerate().map(move |(index, value)| {
+            (Vector2::new((index % width) as i32, (index / width) as i32), value)
+        }))
+    }
+
+    /// Reduces the red, green and blue channels of every pixel to the given
+    /// number of bits with ordered dithering, so a smooth gradient becomes a
+    /// pattern of the neighbouring levels instead of hard bands, e.g. before
+    /// presenting to a display with fewer bits per channel. The channels are
+    /// stored with 8 bits, of which only the levels of the given number of
+    /// bits are used, so they survive storing them in a format of that depth,
+    /// such as [`Rgb565`](crate::format::Rgb565) with 5 bits. The pattern
+    /// only depends on the position of the pixel, so the result is the same
+    /// every frame. Alpha isn't dithered, and nothing changes with 8 or more
+    /// bits. See [`SwapChain::post_process`].
+    ///
+    /// # Panics
+    /// Panics when the number of bits is zero.
+    pub fn dither(&mut self, bits_per_channel: u8) {
+        assert!(bits_per_channel > 0, "a channel must have at least one bit");
+        if bits_per_channel >= 8 {
+            return;
+        }
+
+        let max = ((1u32 << bits_per_channel) - 1) as f32;
+        self.post_process(|position, pixel| {
+            let threshold = BAYER_4X4[position.y as usize % 4][position.x as usize % 4];
+            let offset = (threshold as f32 + 0.5) / 16.0 - 0.5;
+            let channel = |value: u8| {
+                let level = (value as f32 / 255.0 * max + offset).round().clamp(0.0, max);
+                (level * 255.0 / max).round() as u8
+            };
+            Pixel::new(channel(pixel.red()), channel(pixel.green()), channel(pixel.blue()), pixel.alpha())
+        });
+    }
+
+    /// Adjusts the colors of every pixel in linear light, for simple color
+    /// grading: the contrast scales every channel around `0.5`, the
+    /// brightness is added to it, and the result is clamped and raised to
+    /// the power of the gamma, so a gamma above 1 darkens the midtones. A
+    /// brightness of 0, a contrast of 1 and a gamma of 1 leave the pixels as
+    /// they are. Alpha isn't adjusted. See [`SwapChain::post_process`].
+    pub fn adjust(&mut self, brightness: f32, contrast: f32, gamma: f32) {
+        self.post_process(|_, pixel| {
+            let [red, green, blue, alpha] = pixel.to_linear();
+            let channel = |value: f32| {
+                ((value - 0.5) * contrast + 0.5 + brightness).clamp(0.0, 1.0).powf(gamma)
+            };
+            Pixel::from_linear([channel(red), channel(green), channel(blue), alpha])
+        });
+    }
+
+    /// Gets the index, within its draw, of the triangle that was rasterized
+    /// last to the pixel at the point, which is the front one when using a
+    /// depth test. This is `None` when no triangle covers the pixel since the
+    /// last clear, when the point is outside of the extent, or when picking
+    /// isn't enabled. When multisampling, a triangle covers the pixel when
+    /// it covers any of its samples.
+    pub fn pick(&self, point: Vector2<i32>) -> Option<u32> {
+        let x = usize::try_from(point.x).ok().filter(|x| *x < self.extent.width)?;
+        let y = usize::try_from(point.y).ok().filter(|y| *y < self.extent.height)?;
+        self.ids.as_ref()?[y * self.extent.width + x]
+    }
+
+    /// Presents the buffer to the surface. A premultiplied buffer gets
+    /// converted back to straight alpha first, so the colors on the surface
+    /// don't depend on the alpha mode. When multisampling, the samples are
+    /// resolved first, without modifying the buffer of the swap chain.
+    ///
+    /// With multiple buffers, the frame completed last by
+    /// [`SwapChain::acquire`] is presented instead of the back buffer.
+    ///
+    /// Nothing is presented while the swap chain is resized to an empty
+    /// extent.
+    pub fn present(&self, surface: &mut impl PresentationSurface) -> Result<(), SurfacePresentationError> {
+        if self.extent.is_empty() {
+            return Ok(());
+        }
+
+        surface.present(&self.presented_pixels(), self.extent)
+    }
+
+    /// Presents only the part of the frame inside of the [dirty
+    /// rectangle](SwapChain::dirty_rect), assuming the surface still shows
+    /// the frame that was presented when the dirty rectangle was last
+    /// [reset](SwapChain::reset_dirty). Nothing is presented when nothing
+    /// changed. Surfaces that can't update part of their image present the
+    /// whole frame.
+    pub fn present_dirty(&self, surface: &mut impl PresentationSurface)
+            -> Result<(), SurfacePresentationError> {
+        let Some(dirty) = self.dirty else {
+            return Ok(());
+        };
+        if self.extent.is_empty() {
+            return Ok(());
+        }
+
+        surface.present_rect(&self.presented_pixels(), self.extent, dirty)
+    }
+
+    /// Presents the frame just like [`SwapChain::present`], with the overlay
+    /// image, such as a cursor, alpha blended on top of it at the given
+    /// position. The overlay is composited onto a copy of the frame, so the
+    /// buffer itself is left untouched.
+    ///
+    /// # Panics
+    /// Panics when the length of the overlay doesn't match its extent.
+    pub fn present_with_overlay(&self, surface: &mut impl PresentationSurface, overlay: &[Pixel],
+            overlay_extent: Extent, position: Vector2<i32>) -> Result<(), SurfacePresentationError> {
+        assert_eq!(overlay.len(), overlay_extent.width * overlay_extent.height,
+            "overlay doesn't match its extent");
+        if self.extent.is_empty() {
+            return Ok(());
+        }
+
+        let mut pixels = self.presented_pixels().into_owned();
+        let width = self.extent.width.min(i32::MAX as usize) as i32;
+        let height = self.extent.height.min(i32::MAX as usize) as i32;
+        let extent = Rectangle2D::new(Vector2::new(0, 0), Vector2::new(width, height));
+        let overlay_rect = Rectangle2D::new(position, Vector2::new(
+            position.x.saturating_add(overlay_extent.width.min(i32::MAX as usize) as i32),
+            position.y.saturating_add(overlay_extent.height.min(i32::MAX as usize) as i32),
+        ));
+
+        if let Some(bounds) = extent.intersect(&overlay_rect) {
+            for y in bounds.y_range() {
+                let overlay_row = (y - position.y) as usize * overlay_extent.width;
+                for x in bounds.x_range() {
+                    let source = overlay[overlay_row + (x - position.x) as usize];
+                    let pixel = &mut pixels[y as usize * self.extent.width + x as usize];
+                    *pixel = BlendMode::AlphaBlend.blend(source, *pixel);
+                }
+            }
+        }
+
+        surface.present(&pixels, self.extent)
+    }
+
+    /// Writes the color, specified with straight alpha, to the pixel at the
+    /// point, blending it like a fragment, and honouring the stencil test.
+    /// Returns whether the point is inside of the render area, i.e. inside
+    /// of the extent and the scissor rectangle; points outside of it are
+    /// ignored.
+    pub fn put_pixel(&mut self, point: Vector2<i32>, color: Pixel) -> bool {
+        if !self.render_area().is_some_and(|area| area.contains(point)) {
+            return false;
+        }
+
+        let state = self.output_state();
+        self.write_pixel(&state, point.x as usize, point.y as usize, color);
+        true
+    }
+
+    /// Averages the samples of every pixel into the buffer. This only has
+    /// an effect when multisampling, since the buffer holds the final pixels
+    /// otherwise.
+    pub fn resolve(&mut self) {
+        if self.sample_count == SampleCount::X1 {
+            return;
+        }
+
+        let samples = self.samples.chunks_exact(self.sample_count.count());
+        for (pixel, samples) in self.buffer.iter_mut().zip(samples) {
+            *pixel = P::from_pixel(resolve_samples(samples, self.alpha_mode));
+        }
+    }
+
+    /// Draws the cubic Bézier curve from `p0` to `p3`, bent towards the
+    /// control points `p1` and `p2`, just like
+    /// [`SwapChain::draw_bezier_quadratic`].
+    pub fn draw_bezier_cubic(&mut self, p0: Vector2f, p1: Vector2f, p2: Vector2f, p3: Vector2f, color: Pixel,
+            segments: usize) {
+        self.draw_curve_aa(segments, color, |t| {
+            let (a, b, c) = (Vector2f::lerp(p0, p1, t), Vector2f::lerp(p1, p2, t), Vector2f::lerp(p2, p3, t));
+            Vector2f::lerp(Vector2f::lerp(a, b, t), Vector2f::lerp(b, c, t), t)
+        });
+    }
+
+    /// Draws the quadratic Bézier curve from `p0` to `p2`, bent towards the
+    /// control point `p1`, in pixels, with the color specified with straight
+    /// alpha. The curve is flattened into the amount of segments of equal
+    /// steps along the curve, at least one, which are drawn like
+    /// [`SwapChain::draw_line_aa`]. The pixels where two segments meet are
+    /// written by only one of them, so they aren't blended twice.
+    pub fn draw_bezier_quadratic(&mut self, p0: Vector2f, p1: Vector2f, p2: Vector2f, color: Pixel,
+            segments: usize) {
+        self.draw_curve_aa(segments, color, |t| {
+            Vector2f::lerp(Vector2f::lerp(p0, p1, t), Vector2f::lerp(p1, p2, t), t)
+        });
+    }
+
+    /// Draws the curve of which the function gives the point at every step
+    /// from 0 to 1, flattened into the amount of segments.
+    fn draw_curve_aa(&mut self, segments: usize, color: Pixel, point: impl Fn(f32) -> Vector2f) {
+        let segments = segments.max(1);
+        let mut from = point(0.0);
+        for index in 1..=segments {
+            let to = point(index as f32 / segments as f32);
+            self.draw_line_aa_joined(from, to, color, index != 1, index != segments);
+            from = to;
+        }
+    }
+
+    /// Draws the one pixel wide line between the points, in pixels, with
+    /// smooth edges, using Xiaolin Wu's algorithm. Along the major axis of
+    /// the line, every column or row gets the two pixels that straddle the
+    /// line, of which the alpha of the color, specified with straight alpha,
+    /// is scaled by how close their centers are to it. The pixels at the
+    /// endpoints are scaled by the part of them that the line covers as well.
+    /// The pixels are blended like a fragment, so use a blend mode such as
+    /// [`BlendMode::AlphaBlend`] to get smooth edges.
+    pub fn draw_line_aa(&mut self, a: Vector2f, b: Vector2f, color: Pixel) {
+        self.draw_line_aa_joined(a, b, color, false, false);
+    }
+
+    /// Draws the line like [`SwapChain::draw_line_aa`], of which the ends
+    /// that are joined to another line don't get their partial coverage. The
+    /// column of a joined start gets full coverage, while the column of a
+    /// joined end is skipped, since the next line writes it.
+    fn draw_line_aa_joined(&mut self, a: Vector2f, b: Vector2f, color: Pixel, mut joined_a: bool,
+            mut joined_b: bool) {
+        let Some(render_area) = self.render_area() else {
+            return;
+        };
+        if ![a.x, a.y, b.x, b.y].iter().all(|value| value.is_finite()) {
+            return;
+        }
+
+        // Walk along X, swapping the axes of steep lines.
+        let steep = (b.y - a.y).abs() > (b.x - a.x).abs();
+        let swap_axes = |point: Vector2f| if steep { Vector2f::new(point.y, point.x) } else { point };
+        let (mut a, mut b) = (swap_axes(a), swap_axes(b));
+        if a.x > b.x {
+            std::mem::swap(&mut a, &mut b);
+            std::mem::swap(&mut joined_a, &mut joined_b);
+        }
+
+        let dx = b.x - a.x;
+        let gradient = if dx == 0.0 { 0.0 } else { (b.y - a.y) / dx };
+        let (first_x, last_x) = ((a.x + 0.5).floor(), (b.x + 0.5).floor());
+        let state = self.output_state();
+
+        // Writes the pixels above and below the line at the column, of which
+        // the coverage is scaled by the part of the column the line covers.
+        let plot_column = |swap_chain: &mut Self, x: f32, gap: f32| {
+            let y = a.y + gradient * (x - a.x);
+            let row = y.floor();
+            for (row, coverage) in [(row, 1.0 - (y - row)), (row + 1.0, y - row)] {
+                let point = Vector2::new(x as i32, row as i32);
+                let point = if steep { Vector2::new(point.y, point.x) } else { point };
+                let alpha = (color.alpha() as f32 * coverage * gap).round() as u8;
+                if alpha != 0 && render_area.contains(point) {
+                    let color = color.with_alpha(alpha);
+                    swap_chain.write_pixel(&state, point.x as usize, point.y as usize, color);
+                }
+            }
+        };
+
+        let first_gap = if joined_a { 1.0 } else { 1.0 - (a.x + 0.5 - first_x) };
+        let last_gap = b.x + 0.5 - last_x;
+        if first_x == last_x {
+            if !joined_b {
+                plot_column(self, first_x, first_gap + last_gap - 1.0);
+            }
+            return;
+        }
+
+        plot_column(self, first_x, first_gap);
+        if !joined_b {
+            plot_column(self, last_x, last_gap);
+        }
+
+        let (min, end) = if steep {
+            (render_area.y(), render_area.y_range().end)
+        } else {
+            (render_area.x(), render_area.x_range().end)
+        };
+        for x in (first_x as i32 + 1).max(min)..(last_x as i32).min(end) {
+            plot_column(self, x as f32, 1.0);
+        }
+    }
+
+    /// Draws the one pixel wide outline of the rectangle, in pixels, with the
+    /// color specified with straight alpha. Every pixel of the outline is
+    /// written once, so the corners aren't blended twice.
+    pub fn draw_rect(&mut self, rect: Rectangle2D<i32>, color: Pixel) {
+        if rect.width() <= 0 || rect.height() <= 0 {
+            return;
+        }
+
+        let (left, top) = (rect.x(), rect.y());
+        let (right, bottom) = (rect.x_range().end, rect.y_range().end);
+
+        let mut fill = |min_x, min_y, max_x, max_y| {
+            self.fill_rect(Rectangle2D::new(Vector2::new(min_x, min_y), Vector2::new(max_x, max_y)), color);
+        };
+
+        fill(left, top, right, top + 1);
+        if bottom - top > 1 {
+            fill(left, bottom - 1, right, bottom);
+        }
+
+        fill(left, top + 1, left + 1, bottom - 1);
+        if right - left > 1 {
+            fill(right - 1, top + 1, right, bottom - 1);
+        }
+    }
+
+    /// Draws the text with the font, tinting its glyphs with the color
+    /// specified with straight alpha. The top left corner of the first glyph
+    /// is at the position, in pixels, and every character advances the width
+    /// of a glyph, with newlines starting at the next line. Characters
+    /// without a glyph leave a gap, and only the part of the text inside of
+    /// the render area is written.
+    ///
+    /// The transparent pixels of the glyphs are skipped, and the others are
+    /// blended like a fragment.
+    pub fn draw_text(&mut self, font: &BitmapFont, text: &str, position: Vector2<i32>, color: Pixel) {
+        let Some(render_area) = self.render_area() else {
+            return;
+        };
+
+        let glyph_extent = font.glyph_extent();
+        let advance = Vector2::new(
+            glyph_extent.width.min(i32::MAX as usize) as i32,
+            glyph_extent.height.min(i32::MAX as usize) as i32,
+        );
+        let state = self.output_state();
+
+        for (line_index, line) in text.split('\n').enumerate() {
+            let y = position.y.saturating_add(advance.y.saturating_mul(line_index as i32));
+            for (character_index, character) in line.chars().enumerate() {
+                let x = position.x.saturating_add(advance.x.saturating_mul(character_index as i32));
+                let glyph = Rectangle2D::new(Vector2::new(x, y),
+                    Vector2::new(x.saturating_add(advance.x), y.saturating_add(advance.y)));
+                if !font.has_glyph(character) {
+                    continue;
+                }
+                let Some(bounds) = render_area.intersect(&glyph) else {
+                    continue;
+                };
+
+                for pixel_y in bounds.y_range() {
+                    for pixel_x in bounds.x_range() {
+                        let (glyph_x, glyph_y) = ((pixel_x - x) as usize, (pixel_y - y) as usize);
+                        let Some(pixel) = font.glyph_pixel(character, glyph_x, glyph_y) else {
+                            continue;
+                        };
+
+                        let pixel = tint(pixel, color);
+                        if pixel.alpha() != 0 {
+                            self.write_pixel(&state, pixel_x as usize, pixel_y as usize, pixel);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Fills the circle with the center and radius in pixels, with the color
+    /// specified with straight alpha. A pixel is filled when its center lies
+    /// within the circle. The circle is drawn row by row, writing the span of
+    /// pixels that lies inside of it, clipped to the render area.
+    pub fn fill_circle(&mut self, center: Vector2f, radius: f32, color: Pixel) {
+        self.fill_circle_spans(center, radius, 0.0, |_| color);
+    }
+
+    /// Fills the circle just like [`SwapChain::fill_circle`], but with smooth
+    /// edges. The alpha of the color is scaled by the part of every pixel
+    /// that is covered, approximated by the distance of its center to the
+    /// edge of the circle.
+    pub fn fill_circle_antialiased(&mut self, center: Vector2f, radius: f32, color: Pixel) {
+        self.fill_circle_spans(center, radius, 0.5, |position| {
+            let distance = (position.x - center.x).hypot(position.y - center.y);
+            let coverage = (radius - distance + 0.5).clamp(0.0, 1.0);
+            color.with_alpha((color.alpha() as f32 * coverage).round() as u8)
+        });
+    }
+
+    /// Writes the spans of the rows of the circle, extended by the margin, in
+    /// pixels, with the color the function gives for the pixel.
+    fn fill_circle_spans(&mut self, center: Vector2f, radius: f32, margin: f32,
+            color: impl Fn(Vector2f) -> Pixel) {
+        let Some(render_area) = self.render_area() else {
+            return;
+        };
+        if radius.is_nan() || radius <= 0.0 {
+            return;
+        }
+
+        let outer = radius + margin;
+        let first_row = ((center.y - outer).ceil() as i32).max(render_area.y());
+        let last_row = ((center.y + outer).floor() as i32).min(render_area.y_range().end - 1);
+        let state = self.output_state();
+
+        for y in first_row..=last_row {
+            let offset = y as f32 - center.y;
+            let half_width = (outer * outer - offset * offset).max(0.0).sqrt();
+            let first_column = ((center.x - half_width).ceil() as i32).max(render_area.x());
+            let last_column = ((center.x + half_width).floor() as i32).min(render_area.x_range().end - 1);
+
+            for x in first_column..=last_column {
+                let color = color(Vector2f::new(x as f32, y as f32));
+                self.write_pixel(&state, x as usize, y as usize, color);
+            }
+        }
+    }
+
+    /// Fills the convex polygon with the points, in pixels, in either winding
+    /// order, with the color specified with straight alpha, blending it like
+    /// a fragment. A pixel is filled when its center lies inside of the
+    /// polygon, or on its left or top edge, so polygons that share an edge
+    /// don't overlap. Polygons that aren't [convex](is_convex_polygon) aren't
+    /// filled, since their rows may consist of multiple spans.
+    pub fn fill_convex_polygon(&mut self, points: &[Vector2f], color: Pixel) {
+        let Some(render_area) = self.render_area() else {
+            return;
+        };
+        if !is_convex_polygon(points) {
+            return;
+        }
+
+        let edges = || points.iter().zip(points.iter().cycle().skip(1));
+        let min_y = points.iter().map(|point| point.y).fold(f32::INFINITY, f32::min);
+        let max_y = points.iter().map(|point| point.y).fold(f32::NEG_INFINITY, f32::max);
+        let first_row = (min_y.ceil() as i32).max(render_area.y());
+        let end_row = (max_y.ceil() as i32).min(render_area.y_range().end);
+        let state = self.output_state();
+
+        for y in first_row..end_row {
+            let row = y as f32;
+            let (mut left, mut right) = (f32::INFINITY, f32::NEG_INFINITY);
+            for (from, to) in edges() {
+                if (from.y <= row) == (to.y <= row) {
+                    continue;
+                }
+                let x = from.x + (row - from.y) * (to.x - from.x) / (to.y - from.y);
+                left = left.min(x);
+                right = right.max(x);
+            }
+
+            let first_column = (left.ceil() as i32).max(render_area.x());
+            let end_column = (right.ceil() as i32).min(render_area.x_range().end);
+            for x in first_column..end_column {
+                self.write_pixel(&state, x as usize, y as usize, color);
+            }
+        }
+    }
+
+    /// Fills the rectangle, in pixels, with the color specified with straight
+    /// alpha, blending it like a fragment. Only the part of the rectangle
+    /// inside of the render area is written.
+    pub fn fill_rect(&mut self, rect: Rectangle2D<i32>, color: Pixel) {
+        let Some(bounds) = self.render_area().and_then(|area| area.intersect(&rect)) else {
+            return;
+        };
+
+        let state = self.output_state();
+        for y in bounds.y_range() {
+            for x in bounds.x_range() {
+                self.write_pixel(&state, x as usize, y as usize, color);
+            }
+        }
+    }
+
+    /// Fills the triangle with the color specified with straight alpha,
+    /// without having to implement the shader traits. The vertices are used
+    /// as they are, just like [`SwapChain::draw_rasterized`] with a vertex
+    /// shader that passes them through, so the triangle goes through the
+    /// whole pipeline, including the blend mode and the stencil test.
+    pub fn fill_triangle(&mut self, vertices: TriangleVertices, color: Pixel) {
+        self.draw_rasterized_with_uniforms(&[vertices], &SolidColor, &SolidColor, &color);
+    }
+
+    /// Replaces the region around the seed with the color, specified with
+    /// straight alpha, like the paint bucket of an image editor. The region
+    /// consists of the pixels of which every channel is within the tolerance
+    /// of the color of the seed, that are connected to it horizontally or
+    /// vertically, inside of the render area. The pixels are replaced
+    /// regardless of the blend mode and the stencil test.
+    ///
+    /// The region is found span by span, with a stack instead of recursion,
+    /// so large regions don't overflow the stack.
+    pub fn flood_fill(&mut self, seed: Vector2<i32>, fill: Pixel, tolerance: u8) {
+        let Some(area) = self.render_area().filter(|area| area.contains(seed)) else {
+            return;
+        };
+        let Some(target) = self.get_pixel(seed) else {
+            return;
+        };
+
+        let width = area.width() as usize;
+        let mut region = vec![false; width * area.height() as usize];
+        let index = |x: i32, y: i32| (y - area.y()) as usize * width + (x - area.x()) as usize;
+        let matches = |x: i32, y: i32| {
+            self.get_pixel(Vector2::new(x, y)).is_some_and(|pixel| pixel.approx_eq(target, tolerance))
+        };
+
+        let mut stack = vec![seed];
+        while let Some(Vector2 { x, y }) = stack.pop() {
+            if region[index(x, y)] || !matches(x, y) {
+                continue;
+            }
+
+            let mut start = x;
+            while start > area.x() && !region[index(start - 1, y)] && matches(start - 1, y) {
+                start -= 1;
+            }
+            let mut end = x + 1;
+            while end < area.x_range().end && !region[index(end, y)] && matches(end, y) {
+                end += 1;
+            }
+            for x in start..end {
+                region[index(x, y)] = true;
+            }
+
+            // Continue at the first pixel of every run in the span of the
+            // neighbouring rows that still has to be filled.
+            for y in [y - 1, y + 1] {
+                if !area.y_range().contains(&y) {
+                    continue;
+                }
+
+                let mut in_run = false;
+                for x in start..end {
+                    let open = !region[index(x, y)] && matches(x, y);
+                    if open && !in_run {
+                        stack.push(Vector2::new(x, y));
+                    }
+                    in_run = open;
+                }
+            }
+        }
+
+        let state = OutputState {
+            alpha_test: None,
+            blend_mode: BlendMode::Opaque,
+            stencil_test: None,
+            depth_test: None,
+            color_write: true,
+            ..self.output_state()
+        };
+        for (index, _) in region.iter().enumerate().filter(|(_, filled)| **filled) {
+            let (x, y) = (area.x() as usize + index % width, area.y() as usize + index / width);
+            self.write_pixel(&state, x, y, fill);
+        }
+    }
+
+    /// Runs the vertex shader on the vertices of the triangle with the given
+    /// index in its draw, clips it against the near plane, and converts it to
+    /// subpixel coordinates, clipped against the clip rectangle. The triangle
+    /// is counted in the statistics, as culled when nothing is left of it.
+    pub(crate) fn assemble_triangles<U: ?Sized>(&mut self, vertex_triple: &TriangleVertices, index: usize,
+            vertex_shader: &dyn VertexShader<U>, uniforms: &U, clip_rectangle: &Rectangle2D<f32>)
+            -> ArrayVec<AssembledTriangle, MAX_ASSEMBLED_TRIANGLES> {
+        self.assemble_shaded(vertex_triple, index, clip_rectangle, || [
+            vertex_shader.run(vertex_triple.a, uniforms),
+            vertex_shader.run(vertex_triple.b, uniforms),
+            vertex_shader.run(vertex_triple.c, uniforms),
+        ])
+    }
+
+    /// Assembles the triangle just like [`SwapChain::assemble_triangles`],
+    /// of which the function runs the vertex shader on its vertices.
+    fn assemble_shaded(&mut self, vertex_triple: &TriangleVertices, index: usize,
+            clip_rectangle: &Rectangle2D<f32>, shade: impl FnOnce() -> [VertexOutput; 3])
+            -> ArrayVec<AssembledTriangle, MAX_ASSEMBLED_TRIANGLES> {
+        let triangles = self.assemble(vertex_triple, index, clip_rectangle, shade);
+        self.record_stats(RenderStats {
+            triangles_submitted: 1,
+            triangles_culled: triangles.is_empty() as u64,
+            ..RenderStats::default()
+        });
+        triangles
+    }
+
+    fn assemble(&self, vertex_triple: &TriangleVertices, index: usize, clip_rectangle: &Rectangle2D<f32>,
+            shade: impl FnOnce() -> [VertexOutput; 3])
+            -> ArrayVec<AssembledTriangle, MAX_ASSEMBLED_TRIANGLES> {
+        if self.state.viewport_cull == ViewportCull::BeforeVertexShader
+                && self.is_outside(vertex_triple, clip_rectangle) {
+            return ArrayVec::new();
+        }
+
+        let outputs = shade();
+
+        let precision = self.sample_count.precision();
+        let mut triangles = ArrayVec::new();
+
+        for vertices in clip_triangle_near_plane(&outputs) {
+            let positions = vertices.map(|vertex| vertex.position.perspective_divide());
+            let coords = positions.map(|position| self.vertex_to_pixel_position(position.xy()));
+            let depths = positions.map(|position| position.z);
+            let varyings = [&vertices[0].varyings, &vertices[1].varyings, &vertices[2].varyings];
+            let mut interpolator = match self.state.interpolation {
+                Interpolation::Affine => Interpolator::new(coords, depths, varyings),
+                Interpolation::PerspectiveCorrect => {
+                    let inverse_w = vertices.map(|vertex| 1.0 / vertex.position.w);
+                    Interpolator::perspective_correct(coords, depths, inverse_w, varyings)
+                }
+            };
+
+            // The provoking vertex is taken from the original triangle, since
+            // clipping changes the vertices.
+            if let ShadingMode::Flat(provoking_vertex) = self.state.shading_mode {
+                interpolator = interpolator.with_flat_varyings(&outputs[provoking_vertex.index()].varyings);
+            }
+
+            // Since `y` goes down in pixels, a positive cross product of the
+            // edges means the vertices go clockwise on screen.
+            let [a, b, c] = coords;
+            let cross = Vector2f::new(b.x - a.x, b.y - a.y).cross(Vector2f::new(c.x - a.x, c.y - a.y));
+            if self.state.cull_mode.culls(self.state.front_face, cross > 0.0) {
+                continue;
+            }
+
+            let triangle = Triangle2D(coords[0], coords[1], coords[2]);
+            for clipped in clip_triangle(&triangle, clip_rectangle) {
+                let triangle = Triangle2D(
+                    round_to_subpixel(clipped.0, precision, self.state.vertex_rounding),
+                    round_to_subpixel(clipped.1, precision, self.state.vertex_rounding),
+                    round_to_subpixel(clipped.2, precision, self.state.vertex_rounding),
+                );
+
+                // The edge functions of a triangle without area are zero on the
+                // line through its vertices, which would otherwise be filled.
+                if triangle.is_degenerate() {
+                    continue;
+                }
+
+                triangles.push(AssembledTriangle {
+                    triangle,
+                    interpolator: interpolator.clone(),
+                    index: index as u32,
+                });
+            }
+        }
+
+        triangles
+    }
+
+    /// Tests whether the bounding box of the vertices, before they are shaded,
+    /// lies entirely outside of the clip rectangle. A pixel of margin keeps
+    /// triangles that touch the pixels at the edges, such as in conservative
+    /// rasterization.
+    fn is_outside(&self, vertex_triple: &TriangleVertices, clip_rectangle: &Rectangle2D<f32>) -> bool {
+        let triangle = Triangle2D(
+            self.vertex_to_pixel_position(vertex_triple.a),
+            self.vertex_to_pixel_position(vertex_triple.b),
+            self.vertex_to_pixel_position(vertex_triple.c),
+        );
+
+        triangle.max_x() < clip_rectangle.x() - 1.0
+            || triangle.min_x() > clip_rectangle.x_range().end + 1.0
+            || triangle.max_y() < clip_rectangle.y() - 1.0
+            || triangle.min_y() > clip_rectangle.y_range().end + 1.0
+    }
+
+    /// Gets the render area as a rectangle to clip triangles against, or
+    /// `None` if there is nothing to render to.
+    pub(crate) fn clip_rectangle(&self) -> Option<Rectangle2D<f32>> {
+        let render_area = self.render_area()?;
+        Some(Rectangle2D::new(
+            Vector2f::new(render_area.x() as f32, render_area.y() as f32),
+            Vector2f::new(render_area.x_range().end as f32, render_area.y_range().end as f32),
+        ))
+    }
+
+    /// Gets the state that determines how fragments are written.
+    fn output_state(&self) -> OutputState {
+        OutputState {
+            alpha_test: self.state.alpha_test,
+            blend_mode: self.state.blend_mode,
+            blend_space: self.state.blend_space,
+            alpha_mode: self.alpha_mode,
+            stencil_test: self.state.stencil_test,
+            depth_test: self.state.depth_test,
+            depth_write: self.state.depth_write,
+            color_write: self.state.color_write,
+        }
+    }
+
+    /// Fills the pixels covered by the triangle.
+    fn rasterize_triangle<U: ?Sized>(&mut self, triangle: &AssembledTriangle,
+            fragment_shader: &dyn FragmentShader<U>, uniforms: &U) {
+        if let Some(bounds) = self.rasterization_bounds(&triangle.triangle) {
+            self.rasterize_triangle_bounded(triangle, &bounds, fragment_shader, uniforms);
+        }
+    }
+
+    /// Fills the pixels covered by the triangle that lie inside the bounds,
+    /// which must be inside of the render area.
+    pub(crate) fn rasterize_triangle_bounded<U: ?Sized>(&mut self, triangle: &AssembledTriangle,
+            bounds: &Rectangle2D<i32>, fragment_shader: &dyn FragmentShader<U>, uniforms: &U) {
+        self.mark_dirty(*bounds);
+        let rasterizer = self.row_rasterizer(triangle, fragment_shader, uniforms);
+        let rows = self.render_target().rows(bounds.y() as usize..bounds.y_range().end as usize);
+
+        let mut stats = RenderStats::default();
+        for (y, row) in bounds.y_range().zip(rows) {
+            stats += rasterizer.rasterize(row, y, bounds);
+        }
+        self.record_stats(stats);
+    }
+
+    /// Fills the pixels covered by the triangle, with the rows divided over
+    /// multiple threads.
+    #[cfg(feature = "rayon")]
+    fn rasterize_triangle_parallel(&mut self, triangle: &AssembledTriangle,
+            fragment_shader: &(dyn FragmentShader + Sync)) {
+        use rayon::prelude::*;
+
+        let Some(bounds) = self.rasterization_bounds(&triangle.triangle) else {
+            return;
+        };
+
+        self.mark_dirty(bounds);
+        let rasterizer = self.row_rasterizer(triangle, fragment_shader, &());
+        let rows = bounds.y() as usize..bounds.y_range().end as usize;
+        let rows: Vec<_> = self.render_target().rows(rows).collect();
+
+        // Every row is a distinct slice of the buffers, so the threads never
+        // write to the same pixels.
+        let stats = rows.into_par_iter()
+            .enumerate()
+            .map(|(i, row)| rasterizer.rasterize(row, bounds.y() + i as i32, &bounds))
+            .reduce(RenderStats::default, Add::add);
+        self.record_stats(stats);
+    }
+
+    /// Adds the work to the statistics, when they are enabled.
+    fn record_stats(&mut self, stats: RenderStats) {
+        if let Some(total) = &mut self.stats {
+            *total += stats;
+        }
+    }
+
+    /// Sets up the rasterization of the rows of the triangle.
+    fn row_rasterizer<'a, U: ?Sized, F: FragmentShader<U> + ?Sized>(&self, triangle: &'a AssembledTriangle,
+            fragment_shader: &'a F, uniforms: &'a U) -> RowRasterizer<'a, U, F> {
+        let edges = EdgeFunction::for_triangle(&triangle.triangle);
+        let edges = match self.state.conservative_raster {
+            ConservativeRaster::Disabled => edges.map(EdgeFunction::with_fill_rule),
+            ConservativeRaster::Overestimate => {
+                let width = self.sample_count.precision() as i64;
+                edges.map(|edge| edge.dilated(width))
+            }
+        };
+
+        RowRasterizer {
+            edges,
+            interpolator: &triangle.interpolator,
+            index: triangle.index,
+            state: self.output_state(),
+            fragment_shader,
+            uniforms,
+            sample_count: self.sample_count,
+            fill_algorithm: self.state.fill_algorithm,
+        }
+    }
+
+    /// Gets the area of the triangle's bounding box that is inside of the
+    /// render area, or `None` if the triangle is completely outside of it.
+    /// When multisampling, these are the pixels of which the samples might
+    /// be covered by the triangle.
+    pub(crate) fn rasterization_bounds(&self, triangle: &Triangle2D<i32>) -> Option<Rectangle2D<i32>> {
+        let bounds = triangle.encapsulating_rectangle();
+        let precision = self.sample_count.precision();
+        let conservative = self.state.conservative_raster == ConservativeRaster::Overestimate;
+        if precision == 1 && !conservative {
+            return self.render_area()?.intersect(&bounds);
+        }
+
+        // The samples are at most half a pixel away from the sample position
+        // of the pixel without multisampling, and conservative rasterization
+        // covers pixels up to a pixel further away.
+        let margin = precision / 2 + if conservative { precision } else { 0 };
+        let bounds = Rectangle2D::new(
+            Vector2::new(
+                (bounds.x() - margin).div_euclid(precision),
+                (bounds.y() - margin).div_euclid(precision),
+            ),
+            Vector2::new(
+                (bounds.x_range().end + margin).div_euclid(precision) + 1,
+                (bounds.y_range().end + margin).div_euclid(precision) + 1,
+            ),
+        );
+        self.render_area()?.intersect(&bounds)
+    }
+
+    /// Gets the buffer that triangles are rasterized into, together with the
+    /// stencil values and depths of its elements when there is a stencil or
+    /// depth test.
+    fn render_target(&mut self) -> RenderTarget<'_, P> {
+        let stencil = match self.state.stencil_test {
+            Some(_) => self.stencil.as_deref_mut(),
+            None => None,
+        };
+        let depth = match self.state.depth_test {
+            Some(_) => self.depth.as_deref_mut(),
+            None => None,
+        };
+
+        let (colors, row_length) = match self.sample_count {
+            SampleCount::X1 => (&mut self.buffer, self.extent.width),
+            sample_count => (&mut self.samples, self.extent.width * sample_count.count()),
+        };
+        let ids = self.ids.as_deref_mut();
+        let accumulation = self.accumulation.as_deref_mut();
+        RenderTarget { colors, stencil, depth, ids, accumulation, row_length, width: self.extent.width }
+    }
+
+    /// Gets the depth buffer, creating it when it doesn't exist yet, with
+    /// every sample at the far plane.
+    ///
+    /// # Panics
+    /// Panics when the depth buffer is too large to be allocated.
+    fn depth_buffer(&mut self) -> &mut Vec<f32> {
+        let (extent, sample_count) = (self.extent, self.sample_count);
+        self.depth.get_or_insert_with(|| match create_per_sample_buffer(extent, sample_count, 1.0) {
+            Ok(depth) => depth,
+            Err(error) => panic!("failed to allocate depth buffer: {:?}", error),
+        })
+    }
+
+    /// Gets the stencil buffer, creating it when it doesn't exist yet.
+    ///
+    /// # Panics
+    /// Panics when the stencil buffer is too large to be allocated.
+    fn stencil_buffer(&mut self) -> &mut Vec<u8> {
+        let (extent, sample_count) = (self.extent, self.sample_count);
+        self.stencil.get_or_insert_with(|| match create_per_sample_buffer(extent, sample_count, 0) {
+            Ok(stencil) => stencil,
+            Err(error) => panic!("failed to allocate stencil buffer: {:?}", error),
+        })
+    }
+
+    /// Writes the color to the pixel, or to all of its samples when
+    /// multisampling, blending it with what is already there. Only the
+    /// samples that pass the stencil test are written, and nothing is
+    /// written when color writes are disabled. The depth test doesn't apply,
+    /// since the pixel has no depth.
+    ///
+    /// The callers clip the pixel to the render area. A pixel past the end of
+    /// a row would otherwise silently be written to the next row, so this is
+    /// checked in debug builds.
+    fn write_pixel(&mut self, state: &OutputState, x: usize, y: usize, color: Pixel) {
+        debug_assert!(x < self.extent.width && y < self.extent.height,
+            "pixel ({x}, {y}) is outside of the extent");
+        let point = Vector2::new(x as i32, y as i32);
+        debug_assert!(self.render_area().is_some_and(|area| area.contains(point)),
+            "pixel ({x}, {y}) is outside of the render area");
+        self.mark_dirty(Rectangle2D::new(point, Vector2::new(point.x + 1, point.y + 1)));
+
+        let count = self.sample_count.count();
+        let start = (y * self.extent.width + x) * count;
+        let target = self.render_target();
+        let mut row = RowTarget {
+            colors: target.colors,
+            stencil: target.stencil,
+            depth: None,
+            ids: None,
+            accumulation: target.accumulation,
+        };
+
+        for index in start..start + count {
+            if state.stencil_test(row.stencil.as_deref_mut(), index) && state.color_write {
+                row.write(state, index, color);
+            }
+        }
+    }
+
+    /// Sets the accumulated colors, if float accumulation is enabled, to the
+    /// colors of the elements of the buffer, after they were replaced
+    /// without blending.
+    fn sync_accumulation(&mut self) {
+        let Some(accumulation) = &mut self.accumulation else {
+            return;
+        };
+
+        let colors = match self.sample_count {
+            SampleCount::X1 => &self.buffer,
+            _ => &self.samples,
+        };
+        accumulation.clear();
+        let alpha_mode = self.alpha_mode;
+        accumulation.extend(colors.iter().map(|value| alpha_mode.decode(value.to_pixel()).to_normalized()));
+    }
+
+    /// Adds the rectangle, in pixels, to the dirty rectangle.
+    #[inline]
+    fn mark_dirty(&mut self, rect: Rectangle2D<i32>) {
+        self.dirty = Some(match self.dirty {
+            Some(dirty) => dirty.union(&rect),
+            None => rect,
+        });
+    }
+
+    /// Marks the whole extent as dirty, unless it's empty.
+    fn mark_all_dirty(&mut self) {
+        let width = self.extent.width.min(i32::MAX as usize) as i32;
+        let height = self.extent.height.min(i32::MAX as usize) as i32;
+        self.dirty = (!self.extent.is_empty()).then(|| Rectangle2D::new(Vector2::new(0, 0),
+            Vector2::new(width, height)));
+    }
+
+    /// Reads back the color of the pixel at the index with straight alpha,
+    /// resolving its samples when multisampling.
+    fn resolved_pixel(&self, index: usize) -> Pixel {
+        let pixel = match self.sample_count {
+            SampleCount::X1 => self.buffer[index].to_pixel(),
+            sample_count => {
+                let count = sample_count.count();
+                resolve_samples(&self.samples[index * count..(index + 1) * count], self.alpha_mode)
+            }
+        };
+        self.alpha_mode.decode(pixel)
+    }
+
+    /// Gets the area that can be rendered to, which is the extent of the swap
+    /// chain limited by the scissor rectangle.
+    pub(crate) fn render_area(&self) -> Option<Rectangle2D<i32>> {
+        let width = self.extent.width.min(i32::MAX as usize) as i32;
+        let height = self.extent.height.min(i32::MAX as usize) as i32;
+        let extent = Rectangle2D::new(Vector2::new(0, 0), Vector2::new(width, height));
+
+        extent.intersect(&self.state.scissor.unwrap_or(extent))
+    }
+
+    /// Resizes the swap chain image to the logical size, scaled by the
+    /// current [scale factor](SwapChain::scale_factor), keeping the pixels
+    /// that both the old and the new extent cover just like
+    /// [`SwapChain::resize_preserving_extent`].
+    ///
+    /// # Panics
+    /// Panics when the pixel buffer is too large to be allocated.
+    #[cfg(feature = "winit")]
+    pub fn resize_preserving(&mut self, size: LogicalSize<u32>, fill: Pixel) {
+        let size: PhysicalSize<u32> = size.to_physical(self.scale_factor);
+        self.resize_preserving_extent(Extent { width: size.width as _, height: size.height as _ }, fill);
+    }
+
+    /// Resizes the swap chain image to the given extent in physical pixels
+    /// just like [`SwapChain::resize`], but keeps the pixels in the top left
+    /// region that both the old and the new extent cover, e.g. when
+    /// extending the canvas of an image editor. The rest of the buffer is
+    /// filled with the given color, specified with straight alpha.
+    ///
+    /// When multisampling, the samples are resolved first, so every sample
+    /// of a kept pixel gets its resolved color. With multiple buffers, the
+    /// older frames get the kept pixels as well. The stencil and depth
+    /// buffers are reset.
+    ///
+    /// # Panics
+    /// Panics when the pixel buffer is too large to be allocated.
+    pub fn resize_preserving_extent(&mut self, extent: Extent, fill: Pixel) {
+        self.resolve();
+        let old_extent = self.extent;
+        let old_buffer = std::mem::take(&mut self.buffer);
+        self.resize(extent, fill);
+
+        let width = old_extent.width.min(self.extent.width);
+        for y in 0..old_extent.height.min(self.extent.height) {
+            let (old_row, new_row) = (y * old_extent.width, y * self.extent.width);
+            self.buffer[new_row..new_row + width].copy_from_slice(&old_buffer[old_row..old_row + width]);
+        }
+
+        let count = self.sample_count.count();
+        if count > 1 {
+            for (samples, pixel) in self.samples.chunks_exact_mut(count).zip(&self.buffer) {
+                samples.fill(*pixel);
+            }
+        }
+        for front_buffer in &mut self.front_buffers {
+            front_buffer.clone_from(&self.buffer);
+        }
+        self.sync_accumulation();
+    }
+
+    /// Resizes the swap chain image, using the specified color as the clear
+    /// color. To actually resize the swap chain and it's colors inside (without
+    /// clearing them) would be a waste, since we can just redraw instead.
+    ///
+    /// The size is scaled by the current [scale factor](SwapChain::scale_factor).
+    /// The size may be zero, e.g. when the window is minimized, in which case
+    /// drawing and presenting do nothing until the next resize.
+    ///
+    /// # Panics
+    /// Panics when the pixel buffer is too large to be allocated.
+    #[cfg(feature = "winit")]
+    pub fn resize_with_clear_color(&mut self, size: LogicalSize<u32>, color: Pixel) {
+        self.resize_with_scale_factor(size, self.scale_factor, color);
+    }
+
+    /// Resizes the swap chain image just like
+    /// [`SwapChain::resize_with_clear_color`], but for a new scale factor,
+    /// e.g. when the window moved to another display.
+    ///
+    /// # Panics
+    /// Panics when the scale factor isn't a positive number, or when the
+    /// pixel buffer is too large to be allocated.
+    #[cfg(feature = "winit")]
+    pub fn resize_with_scale_factor(&mut self, size: LogicalSize<u32>, scale_factor: f64, color: Pixel) {
+        let size: PhysicalSize<u32> = size.to_physical(scale_factor);
+        self.scale_factor = scale_factor;
+        self.resize(Extent { width: size.width as _, height: size.height as _ }, color);
+    }
+
+    /// Resizes the swap chain image to the given extent in physical pixels,
+    /// clearing it with the color, specified with straight alpha, just like
+    /// [`SwapChain::resize_with_clear_color`]. The scale factor is kept.
+    ///
+    /// # Panics
+    /// Panics when the pixel buffer is too large to be allocated.
+    pub fn resize(&mut self, extent: Extent, color: Pixel) {
+        self.extent = extent;
+        let color = P::from_pixel(self.alpha_mode.encode(color));
+        let buffers = create_buffer(extent.width, extent.height, color).and_then(|buffer| {
+            let samples = create_sample_buffer(self.extent, self.sample_count, color)?;
+            Ok((buffer, samples))
+        });
+        (self.buffer, self.samples) = match buffers {
+            Ok(buffers) => buffers,
+            Err(error) => {
+                panic!("failed to resize swap chain to {}x{}: {:?}", extent.width, extent.height, error)
+            }
+        };
+
+        for front_buffer in &mut self.front_buffers {
+            front_buffer.clone_from(&self.buffer);
+        }
+
+        if self.stencil.take().is_some() {
+            self.clear_stencil(0);
+        }
+        if self.depth.take().is_some() {
+            self.clear_depth(1.0);
+        }
+        if self.ids.take().is_some() {
+            self.set_picking(true);
+        }
+        self.sync_accumulation();
+        self.mark_all_dirty();
+    }
+
+    /// Marks nothing as changed, e.g. after presenting the
+    /// [dirty rectangle](SwapChain::dirty_rect).
+    pub fn reset_dirty(&mut self) {
+        self.dirty = None;
+    }
+
+    /// Sets the way in which the alpha channel is stored in the buffer. The
+    /// current contents of the buffer are converted to the new mode.
+    pub fn set_alpha_mode(&mut self, alpha_mode: AlphaMode) {
+        if alpha_mode == self.alpha_mode {
+            return;
+        }
+
+        let convert = match alpha_mode {
+            AlphaMode::Straight => Pixel::unpremultiplied,
+            AlphaMode::Premultiplied => Pixel::premultiplied,
+        };
+
+        let front_buffers = self.front_buffers.iter_mut().flatten();
+        for value in self.buffer.iter_mut().chain(&mut self.samples).chain(front_buffers) {
+            *value = P::from_pixel(convert(&value.to_pixel()));
+        }
+
+        self.alpha_mode = alpha_mode;
+        self.mark_all_dirty();
+    }
+
+    /// Sets the alpha, with straight alpha, below which the fragments of
+    /// triangles are discarded, such as for cutout textures of foliage. A
+    /// discarded fragment isn't written at all, not to the color buffer, nor
+    /// to the stencil and depth buffers. `None` disables the alpha test,
+    /// which is the default.
+    ///
+    /// With an alpha test, the fragment shader runs before the stencil and
+    /// depth tests, even without [color writes](SwapChain::set_color_write).
+    pub fn set_alpha_test(&mut self, alpha_test: Option<u8>) {
+        self.state.alpha_test = alpha_test;
+    }
+
+    /// Sets the blend mode used when writing fragments to the swap chain.
+    pub fn set_blend_mode(&mut self, blend_mode: BlendMode) {
+        self.state.blend_mode = blend_mode;
+    }
+
+    /// Sets the color space in which fragments are blended.
+    pub fn set_blend_space(&mut self, blend_space: ColorSpace) {
+        self.state.blend_space = blend_space;
+    }
+
+    /// Sets the way the coverage of pixels is determined, which is by their
+    /// sample positions by default.
+    pub fn set_conservative_raster(&mut self, conservative_raster: ConservativeRaster) {
+        self.state.conservative_raster = conservative_raster;
+    }
+
+    /// Sets the space of the positions of the vertices, after the perspective
+    /// divide.
+    pub fn set_coordinate_space(&mut self, coordinate_space: CoordinateSpace) {
+        self.state.coordinate_space = coordinate_space;
+    }
+
+    /// Sets the triangles that are discarded based on the way they face, as
+    /// given by the [front face](SwapChain::set_front_face). By default, no
+    /// triangles are culled.
+    pub fn set_cull_mode(&mut self, cull_mode: CullMode) {
+        self.state.cull_mode = cull_mode;
+    }
+
+    /// Sets whether fragments are written to the color buffer, which is the
+    /// case by default. Without color writes, the fragment shader doesn't
+    /// run, while the stencil and depth buffers are still updated, e.g. for
+    /// a depth-only pass.
+    pub fn set_color_write(&mut self, color_write: bool) {
+        self.state.color_write = color_write;
+    }
+
+    /// Sets the comparison of the depth of fragments against the depth
+    /// buffer, creating the depth buffer when it doesn't exist yet. Fragments
+    /// that fail the test are discarded. `None` disables the depth test,
+    /// which also leaves the depth buffer untouched.
+    ///
+    /// # Panics
+    /// Panics when the depth buffer is too large to be allocated.
+    pub fn set_depth_test(&mut self, depth_test: Option<CompareFunction>) {
+        if depth_test.is_some() {
+            self.depth_buffer();
+        }
+        self.state.depth_test = depth_test;
+    }
+
+    /// Sets whether the depth of fragments passing the depth test is written
+    /// to the depth buffer, which is the case by default.
+    pub fn set_depth_write(&mut self, depth_write: bool) {
+        self.state.depth_write = depth_write;
+    }
+
+    /// Sets the way the covered pixels of every row are found, which doesn't
+    /// change which pixels are filled, only how fast. By default, every pixel
+    /// of the bounding box of a triangle is tested.
+    pub fn set_fill_algorithm(&mut self, fill_algorithm: FillAlgorithm) {
+        self.state.fill_algorithm = fill_algorithm;
+    }
+
+    /// Enables or disables blending fragments into colors with a float per
+    /// channel, which is disabled by default. Blending many translucent
+    /// layers in 8 bits rounds every layer, which adds up to visible banding;
+    /// with float accumulation the buffer only gets the rounded result, and
+    /// is never read back for blending. The accumulated colors start as the
+    /// current buffer, and are reset along with it by operations that
+    /// replace its contents, such as [`SwapChain::clear`].
+    pub fn set_float_accumulation(&mut self, float_accumulation: bool) {
+        if !float_accumulation {
+            self.accumulation = None;
+        } else if self.accumulation.is_none() {
+            self.accumulation = Some(Vec::new());
+            self.sync_accumulation();
+        }
+    }
+
+    /// Sets the winding order, on screen, of triangles that face the viewer,
+    /// which is counter-clockwise by default.
+    pub fn set_front_face(&mut self, front_face: FrontFace) {
+        self.state.front_face = front_face;
+    }
+
+    /// Sets the way the varyings are interpolated in smooth shading.
+    pub fn set_interpolation(&mut self, interpolation: Interpolation) {
+        self.state.interpolation = interpolation;
+    }
+
+    /// Sets the number of buffers that the frames cycle through, which is one
+    /// by default. With two or three buffers, a frame can be drawn while the
+    /// previous one is presented, see [`SwapChain::acquire`]. Added buffers
+    /// start out with the contents of the back buffer, and the oldest frames
+    /// are dropped when the count is lowered.
+    ///
+    /// # Panics
+    /// Panics when the count is zero.
+    pub fn set_buffer_count(&mut self, count: usize) {
+        assert!(count != 0, "a swap chain needs at least one buffer");
+
+        self.resolve();
+        while self.front_buffers.len() >= count {
+            self.front_buffers.pop_front();
+        }
+        while self.front_buffers.len() < count - 1 {
+            self.front_buffers.push_front(self.buffer.clone());
+        }
+    }
+
+    /// Sets the number of samples stored for every pixel. The samples of a
+    /// pixel start out as the current value of that pixel; when disabling
+    /// multisampling, the samples are resolved into the buffer first. The
+    /// stencil buffer, if any, is cleared to zero.
+    ///
+    /// # Panics
+    /// Panics when the sample or stencil buffer is too large to be allocated.
+    pub fn set_sample_count(&mut self, sample_count: SampleCount) {
+        if sample_count == self.sample_count {
+            return;
+        }
+
+        self.resolve();
+        let color = P::from_pixel(Pixel::TRANSPARENT);
+        self.samples = match create_sample_buffer(self.extent, sample_count, color) {
+            Ok(samples) => samples,
+            Err(error) => panic!("failed to allocate {:?} samples: {:?}", sample_count, error),
+        };
+        self.sample_count = sample_count;
+
+        for (samples, pixel) in self.samples.chunks_exact_mut(sample_count.count()).zip(&self.buffer) {
+            samples.fill(*pixel);
+        }
+        self.sync_accumulation();
+
+        if self.stencil.take().is_some() {
+            self.clear_stencil(0);
+        }
+        if self.depth.take().is_some() {
+            self.clear_depth(1.0);
+        }
+    }
+
+    /// Enables or disables keeping the index of the triangle that was
+    /// rasterized last to every pixel, which can be queried with
+    /// [`SwapChain::pick`]. The indices are reset by [`SwapChain::clear`].
+    ///
+    /// # Panics
+    /// Panics when the buffer of indices is too large to be allocated.
+    pub fn set_picking(&mut self, picking: bool) {
+        if !picking {
+            self.ids = None;
+        } else if self.ids.is_none() {
+            self.ids = match create_buffer(self.extent.width, self.extent.height, None) {
+                Ok(ids) => Some(ids),
+                Err(error) => panic!("failed to allocate picking buffer: {:?}", error),
+            };
+        }
+    }
+
+    /// Sets the scissor rectangle, in pixels. Fragments outside of this
+    /// rectangle are discarded. `None` means the full extent.
+    pub fn set_scissor(&mut self, scissor: Option<Rectangle2D<i32>>) {
+        self.state.scissor = scissor;
+    }
+
+    /// Sets the way the varyings are passed to the fragment shader.
+    pub fn set_shading_mode(&mut self, shading_mode: ShadingMode) {
+        self.state.shading_mode = shading_mode;
+    }
+
+    /// Sets the state that subsequent draws are rasterized with, creating the
+    /// stencil and depth buffers when they are used and don't exist yet.
+    ///
+    /// # Panics
+    /// Panics when the stencil or depth buffer is too large to be allocated.
+    pub fn set_state(&mut self, state: RasterizerState) {
+        if state.stencil_test.is_some() {
+            self.stencil_buffer();
+        }
+        if state.depth_test.is_some() {
+            self.depth_buffer();
+        }
+        self.state = state;
+    }
+
+    /// Enables or disables counting the work done for draws in
+    /// [`RenderStats`], which is disabled by default. Enabling them while
+    /// they are enabled keeps the counts.
+    pub fn set_stats(&mut self, enabled: bool) {
+        if !enabled {
+            self.stats = None;
+        } else if self.stats.is_none() {
+            self.stats = Some(RenderStats::default());
+        }
+    }
+
+    /// Sets the stencil test that draws are masked with, creating the stencil
+    /// buffer when it doesn't exist yet. `None` disables the stencil test.
+    ///
+    /// # Panics
+    /// Panics when the stencil buffer is too large to be allocated.
+    pub fn set_stencil_test(&mut self, stencil_test: Option<StencilTest>) {
+        if stencil_test.is_some() {
+            self.stencil_buffer();
+        }
+        self.state.stencil_test = stencil_test;
+    }
+
+    /// Sets the way the positions of vertices, in pixels, are snapped to
+    /// subpixels, or to whole pixels without multisampling. By default, they
+    /// are rounded to the nearest one.
+    pub fn set_vertex_rounding(&mut self, vertex_rounding: Rounding) {
+        self.state.vertex_rounding = vertex_rounding;
+    }
+
+    /// Sets whether triangles that are entirely outside of the render area
+    /// are culled before vertex shading, which is disabled by default, since
+    /// it is only correct for vertex shaders that preserve the positions.
+    pub fn set_viewport_cull(&mut self, viewport_cull: ViewportCull) {
+        self.state.viewport_cull = viewport_cull;
+    }
+
+    /// Sets the direction in which `y` increases in normalized device
+    /// coordinates. Positions in [pixels](CoordinateSpace::Pixels) always go
+    /// down.
+    pub fn set_y_axis(&mut self, y_axis: YAxis) {
+        self.state.y_axis = y_axis;
+    }
+
+    /// Gets the work done since the statistics were last taken, and resets
+    /// them, or `None` when they are [disabled](SwapChain::set_stats).
+    pub fn take_stats(&mut self) -> Option<RenderStats> {
+        self.stats.as_mut().map(std::mem::take)
+    }
+
+    /// Gets the pixels of the frame that is presented, with straight alpha.
+    fn presented_pixels(&self) -> Cow<'_, [Pixel]> {
+        let buffer: Cow<[Pixel]> = match (self.front_buffers.back(), self.sample_count) {
+            (Some(front_buffer), _) => P::to_pixels(front_buffer),
+            (None, SampleCount::X1) => P::to_pixels(&self.buffer),
+            (None, sample_count) => Cow::Owned(self.samples.chunks_exact(sample_count.count())
+                .map(|samples| resolve_samples(samples, self.alpha_mode))
+                .collect()),
+        };
+
+        match self.alpha_mode {
+            AlphaMode::Straight => buffer,
+            AlphaMode::Premultiplied => Cow::Owned(buffer.iter().map(Pixel::unpremultiplied).collect()),
+        }
+    }
+
+    fn vertex_to_pixel_position(&self, vertex: Vector2f) -> Vector2f {
+        match self.state.coordinate_space {
+            CoordinateSpace::NormalizedDevice => {
+                let x = (vertex.x + 1.0) / 2.0 * self.extent.width as f32;
+                let y = match self.state.y_axis {
+                    YAxis::Up => 1.0 - vertex.y,
+                    YAxis::Down => vertex.y + 1.0,
+                };
+                Vector2::new(x, y / 2.0 * self.extent.height as f32)
+            }
+            CoordinateSpace::Pixels => vertex,
+        }
+    }
+
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        format::Rgb565,
+        math::{Matrix4, Vector4},
+        platform::MemorySurface,
+        stencil::{CompareFunction, StencilTest},
+        texture::{MipmapFilter, MipmappedTexture, Sampler},
+        shader::{
+            FnFragmentShader, FnVertexShader, FragmentShader2D, Interpolation, ProvokingVertex, VertexOutput,
+            VertexShader2D, COLOR_VARYING, MAX_VARYINGS, UV_VARYING,
+        },
+    };
+
+    struct HalfWhiteShader;
+
+    impl VertexShader2D for HalfWhiteShader {
+        fn run(&self, position: Vector2f) -> Vector2f {
+            position
+        }
+    }
+
+    impl FragmentShader2D for HalfWhiteShader {
+        fn run(&self) -> Pixel {
+            Pixel::new(0xFF, 0xFF, 0xFF, 0x80)
+        }
+    }
+
+    struct TransparentShader;
+
+    impl FragmentShader2D for TransparentShader {
+        fn run(&self) -> Pixel {
+            Pixel::TRANSPARENT
+        }
+    }
+
+    struct WhiteShader;
+
+    impl FragmentShader2D for WhiteShader {
+        fn run(&self) -> Pixel {
+            Pixel::WHITE
+        }
+    }
+
+    fn draw_half_white(blend_mode: BlendMode, blend_space: ColorSpace) -> Pixel {
+        let mut swap_chain = SwapChain::with_extent(Extent { width: 4, height: 4 });
+        swap_chain.clear(Pixel::BLACK);
+        swap_chain.set_blend_mode(blend_mode);
+        swap_chain.set_blend_space(blend_space);
+
+        let vertices = [
+            TriangleVertices::new(
+                Vector2f::new(-1.0, -1.0),
+                Vector2f::new(1.0, -1.0),
+                Vector2f::new(-1.0, 1.0),
+            )
+        ];
+        swap_chain.draw_rasterized(&vertices, &HalfWhiteShader, &HalfWhiteShader);
+
+        swap_chain.buffer[swap_chain.extent.width + 1]
+    }
+
+    #[test]
+    fn with_extent() {
+        let extent = Extent { width: 5, height: 3 };
+        let mut swap_chain = SwapChain::with_extent(extent);
+        assert_eq!(swap_chain.extent(), extent);
+        assert_eq!(swap_chain.scale_factor(), 1.0);
+        assert_eq!(swap_chain.as_slice(), vec![Pixel::BLACK; 15]);
+
+        swap_chain.resize(Extent { width: 2, height: 4 }, Pixel::WHITE);
+        assert_eq!(swap_chain.extent(), Extent { width: 2, height: 4 });
+        assert_eq!(swap_chain.as_slice(), vec![Pixel::WHITE; 8]);
+
+        assert!(matches!(SwapChain::try_with_extent(Extent { width: 0, height: 3 }),
+            Err(SwapChainError::ZeroExtent)));
+        assert!(matches!(SwapChain::try_with_extent(Extent { width: usize::MAX, height: 2 }),
+            Err(SwapChainError::TooLarge)));
+
+        let rgb565 = SwapChain::<Rgb565>::try_with_format_extent(extent).unwrap();
+        assert_eq!(rgb565.as_slice(), vec![Rgb565(0); 15]);
+    }
+
+    #[test]
+    #[cfg(feature = "winit")]
+    fn try_new_rejects_zero_extent() {
+        assert_eq!(SwapChain::try_new(LogicalSize::new(0, 4)).err(), Some(SwapChainError::ZeroExtent));
+        assert_eq!(SwapChain::try_new(LogicalSize::new(4, 0)).err(), Some(SwapChainError::ZeroExtent));
+        assert_eq!(SwapChain::try_new(LogicalSize::new(0, 0)).err(), Some(SwapChainError::ZeroExtent));
+    }
+
+    #[test]
+    #[cfg(feature = "winit")]
+    fn try_new_rejects_overflow() {
+        let size = LogicalSize::new(u32::MAX, u32::MAX);
+        assert_eq!(SwapChain::try_new(size).err(), Some(SwapChainError::TooLarge));
+
+        assert_eq!(create_buffer(usize::MAX, 2, Pixel::BLACK), Err(SwapChainError::TooLarge));
+        assert_eq!(create_buffer(usize::MAX / 4, 2, Pixel::BLACK), Err(SwapChainError::TooLarge));
+    }
+
+    #[test]
+    #[cfg(feature = "winit")]
+    fn try_new() {
+        let swap_chain = SwapChain::try_new(LogicalSize::new(3, 2)).unwrap();
+        assert_eq!(swap_chain.extent, Extent { width: 3, height: 2 });
+        assert_eq!(swap_chain.buffer.len(), 6);
+    }
+
+    #[test]
+    #[cfg(feature = "winit")]
+    #[should_panic]
+    fn new_panics_on_zero_extent() {
+        SwapChain::new(LogicalSize::new(0, 0));
+    }
+
+    #[test]
+    fn acquire_cycles_through_buffers() {
+        let mut swap_chain = SwapChain::with_extent(Extent { width: 2, height: 2 });
+        swap_chain.set_buffer_count(3);
+        assert_eq!(swap_chain.buffer_count(), 3);
+
+        let mut surface = MemorySurface::new();
+        swap_chain.present(&mut surface).unwrap();
+        assert_eq!(surface.pixels(), vec![Pixel::BLACK; 4]);
+
+        let colors = [Pixel::RED, Pixel::GREEN, Pixel::BLUE, Pixel::WHITE];
+        for (frame, color) in colors.into_iter().enumerate() {
+            swap_chain.clear(color);
+
+            // The back buffer isn't presented until it's completed.
+            swap_chain.present(&mut surface).unwrap();
+            let previous = if frame == 0 { Pixel::BLACK } else { colors[frame - 1] };
+            assert_eq!(surface.pixels(), vec![previous; 4]);
+
+            swap_chain.acquire();
+            swap_chain.present(&mut surface).unwrap();
+            assert_eq!(surface.pixels(), vec![color; 4]);
+
+            // The acquired buffer holds the frame before the previous one.
+            let oldest = if frame < 2 { Pixel::BLACK } else { colors[frame - 2] };
+            assert_eq!(swap_chain.as_slice(), &[oldest; 4]);
+        }
+
+        swap_chain.set_buffer_count(2);
+        swap_chain.present(&mut surface).unwrap();
+        assert_eq!(surface.pixels(), vec![Pixel::WHITE; 4]);
+
+        // With a single buffer, the back buffer is presented.
+        swap_chain.set_buffer_count(1);
+        swap_chain.acquire();
+        swap_chain.present(&mut surface).unwrap();
+        assert_eq!(surface.pixels(), vec![Pixel::GREEN; 4]);
+    }
+
+    #[test]
+    fn resize_preserving_keeps_overlap() {
+        let extent = Extent { width: 3, height: 2 };
+        let image = test_image(extent);
+        let fill = Pixel::new(0x00, 0x00, 0xFF, 0xFF);
+        let mut swap_chain = SwapChain::with_extent(Extent { width: 3, height: 2 });
+        swap_chain.buffer = image.clone();
+
+        swap_chain.resize_preserving_extent(Extent { width: 5, height: 4 }, fill);
+        assert_eq!(swap_chain.extent(), Extent { width: 5, height: 4 });
+        for y in 0..4 {
+            for x in 0..5 {
+                let expected = if x < 3 && y < 2 { image[y * 3 + x] } else { fill };
+                assert_eq!(swap_chain.buffer[y * 5 + x], expected, "({x}, {y})");
+            }
+        }
+
+        // Shrinking keeps the top left corner.
+        swap_chain.resize_preserving_extent(Extent { width: 2, height: 1 }, fill);
+        assert_eq!(swap_chain.buffer, vec![image[0], image[1]]);
+
+        // So does multisampling, after resolving the samples.
+        swap_chain.set_sample_count(SampleCount::X4);
+        swap_chain.resize_preserving_extent(Extent { width: 3, height: 1 }, fill);
+        swap_chain.resolve();
+        assert_eq!(swap_chain.buffer, vec![image[0], image[1], fill]);
+    }
+
+    #[test]
+    #[cfg(feature = "winit")]
+    fn resize_preserving_scales_size() {
+        let mut swap_chain = SwapChain::with_scale_factor(LogicalSize::new(2, 1), 2.0);
+        swap_chain.clear(Pixel::RED);
+        swap_chain.resize_preserving(LogicalSize::new(3, 1), Pixel::BLUE);
+        assert_eq!(swap_chain.extent(), Extent { width: 6, height: 2 });
+        assert_eq!(swap_chain.as_slice()[3..7], [Pixel::RED, Pixel::BLUE, Pixel::BLUE, Pixel::RED]);
+    }
+
+    #[test]
+    fn resize_to_zero_and_back() {
+        let mut swap_chain = SwapChain::with_extent(Extent { width: 4, height: 4 });
+        swap_chain.set_sample_count(SampleCount::X4);
+        swap_chain.clear_stencil(0);
+
+        let mut surface = MemorySurface::new();
+        swap_chain.present(&mut surface).unwrap();
+
+        swap_chain.resize(Extent { width: 0, height: 0 }, Pixel::BLACK);
+        assert!(swap_chain.extent.is_empty());
+        assert_eq!(swap_chain.render_area(), None);
+
+        let vertices = [
+            TriangleVertices::new(
+                Vector2f::new(-1.0, -1.0),
+                Vector2f::new(3.0, -1.0),
+                Vector2f::new(-1.0, 3.0),
+            )
+        ];
+        swap_chain.draw_rasterized(&vertices, &HalfWhiteShader, &WhiteShader);
+        let mut tiled = crate::tiled::TiledRasterizer::with_tile_size(2);
+        tiled.draw(&mut swap_chain, &vertices, &HalfWhiteShader, &WhiteShader);
+        swap_chain.fill_rect(Rectangle2D::new(Vector2::new(0, 0), Vector2::new(2, 2)), Pixel::WHITE);
+        swap_chain.fill_circle(Vector2f::new(1.0, 1.0), 2.0, Pixel::WHITE);
+        swap_chain.blit(&[Pixel::WHITE], Extent { width: 1, height: 1 },
+            Rectangle2D::new(Vector2::new(0, 0), Vector2::new(2, 2)), Filter::Nearest);
+        swap_chain.resolve();
+
+        // The surface keeps showing the last image.
+        swap_chain.present(&mut surface).unwrap();
+        assert_eq!(surface.extent(), Extent { width: 4, height: 4 });
+
+        swap_chain.resize(Extent { width: 0, height: 3 }, Pixel::BLACK);
+        swap_chain.set_sample_count(SampleCount::X1);
+        swap_chain.draw_rasterized(&vertices, &HalfWhiteShader, &WhiteShader);
+
+        swap_chain.resize(Extent { width: 2, height: 3 }, Pixel::BLACK);
+        swap_chain.draw_rasterized(&vertices, &HalfWhiteShader, &WhiteShader);
+        swap_chain.present(&mut surface).unwrap();
+        assert_eq!(surface.extent(), Extent { width: 2, height: 3 });
+        assert_eq!(surface.pixels(), vec![Pixel::WHITE; 6]);
+    }
+
+    #[test]
+    #[cfg(feature = "winit")]
+    fn scale_factor_scales_buffer() {
+        let swap_chain = SwapChain::with_scale_factor(LogicalSize::new(40, 30), 2.0);
+        assert_eq!(swap_chain.extent, Extent { width: 80, height: 60 });
+        assert_eq!(swap_chain.buffer.len(), 80 * 60);
+        assert_eq!(swap_chain.scale_factor(), 2.0);
+
+        let mut swap_chain = SwapChain::new(LogicalSize::new(40, 30));
+        assert_eq!(swap_chain.scale_factor(), 1.0);
+        assert_eq!(swap_chain.extent, Extent { width: 40, height: 30 });
+
+        swap_chain.resize_with_scale_factor(LogicalSize::new(10, 5), 1.5, Pixel::BLACK);
+        assert_eq!(swap_chain.extent, Extent { width: 15, height: 8 });
+
+        // Resizing keeps the scale factor.
+        swap_chain.resize_with_clear_color(LogicalSize::new(20, 10), Pixel::BLACK);
+        assert_eq!(swap_chain.extent, Extent { width: 30, height: 15 });
+        assert_eq!(swap_chain.buffer.len(), 30 * 15);
+    }
+
+    #[test]
+    fn clear() {
+        let mut swap_chain = SwapChain::with_extent(Extent { width: 13, height: 7 });
+        let color = Pixel::new(0x30, 0xA7, 0xF8, 0xFF);
+        swap_chain.clear(color);
+        assert!(swap_chain.buffer.iter().all(|pixel| *pixel == color));
+    }
+
+    #[test]
+    fn clear_linear() {
+        let mut swap_chain = SwapChain::with_extent(Extent { width: 2, height: 2 });
+        swap_chain.clear_linear([0.5, 0.5, 0.5, 0.5]);
+        assert_eq!(swap_chain.as_slice(), [Pixel::new(0xBC, 0xBC, 0xBC, 0x80); 4]);
+
+        swap_chain.clear_linear([1.0, 0.0, 0.0, 1.0]);
+        assert_eq!(swap_chain.as_slice(), [Pixel::RED; 4]);
+
+        swap_chain.set_alpha_mode(AlphaMode::Premultiplied);
+        swap_chain.clear_linear([0.5, 0.5, 0.5, 0.5]);
+        assert_eq!(swap_chain.as_slice(), [Pixel::new(0xBC, 0xBC, 0xBC, 0x80).premultiplied(); 4]);
+    }
+
+    #[test]
+    fn clear_rect_leaves_surrounding_pixels() {
+        let mut swap_chain = SwapChain::with_extent(Extent { width: 6, height: 4 });
+        swap_chain.set_sample_count(SampleCount::X4);
+        swap_chain.set_scissor(Some(Rectangle2D::new(Vector2::new(0, 0), Vector2::new(1, 1))));
+        swap_chain.clear(Pixel::BLUE);
+        swap_chain.reset_dirty();
+
+        swap_chain.clear_rect(Rectangle2D::new(Vector2::new(4, -2), Vector2::new(9, 2)), Pixel::RED);
+        assert_eq!(swap_chain.dirty_rect(), Some(Rectangle2D::new(Vector2::new(4, 0), Vector2::new(6, 2))));
+        swap_chain.resolve();
+        for y in 0..4 {
+            for x in 0..6 {
+                let expected = if x >= 4 && y < 2 { Pixel::RED } else { Pixel::BLUE };
+                assert_eq!(swap_chain.get_pixel(Vector2::new(x, y)), Some(expected), "({x}, {y})");
+            }
+        }
+
+        swap_chain.reset_dirty();
+        swap_chain.clear_rect(Rectangle2D::new(Vector2::new(-5, -5), Vector2::new(0, 9)), Pixel::GREEN);
+        assert_eq!(swap_chain.dirty_rect(), None);
+    }
+
+    #[test]
+    fn downscale_averages_blocks() {
+        let mut swap_chain = SwapChain::with_extent(Extent { width: 4, height: 2 });
+        let colors = [Pixel::RED, Pixel::GREEN, Pixel::BLUE, Pixel::WHITE];
+        for (index, color) in colors.into_iter().enumerate() {
+            swap_chain.put_pixel(Vector2::new(index as i32 % 2, index as i32 / 2), color);
+        }
+        swap_chain.fill_rect(Rectangle2D::new(Vector2::new(2, 0), Vector2::new(4, 2)), Pixel::BLUE);
+        swap_chain.put_pixel(Vector2::new(3, 1), Pixel::TRANSPARENT);
+
+        let extent = Extent { width: 2, height: 1 };
+        assert_eq!(swap_chain.downscale(extent, Filter::Bilinear),
+            [Pixel::new(0x80, 0x80, 0x80, 0xFF), Pixel::new(0x00, 0x00, 0xFF, 0xBF)]);
+        assert_eq!(swap_chain.downscale(extent, Filter::Nearest), [Pixel::WHITE, Pixel::TRANSPARENT]);
+        assert_eq!(swap_chain.downscale(swap_chain.extent(), Filter::Bilinear), swap_chain.as_slice());
+        assert!(swap_chain.downscale(Extent { width: 0, height: 0 }, Filter::Bilinear).is_empty());
+    }
+
+    #[test]
+    fn draw_with_blend_modes() {
+        let srgb = ColorSpace::Srgb;
+        assert_eq!(draw_half_white(BlendMode::Opaque, srgb), Pixel::new(0xFF, 0xFF, 0xFF, 0x80));
+        assert_eq!(draw_half_white(BlendMode::AlphaBlend, srgb), Pixel::new(0x80, 0x80, 0x80, 0xFF));
+        assert_eq!(draw_half_white(BlendMode::Additive, srgb), Pixel::new(0x80, 0x80, 0x80, 0xFF));
+    }
+
+    #[test]
+    fn draw_with_premultiplied_alpha() {
+        let mut swap_chain = SwapChain::with_extent(Extent { width: 4, height: 4 });
+        swap_chain.set_alpha_mode(AlphaMode::Premultiplied);
+        swap_chain.clear(Pixel::new(0xFF, 0x00, 0x00, 0x80));
+        assert_eq!(swap_chain.buffer[0], Pixel::new(0x80, 0x00, 0x00, 0x80));
+
+        swap_chain.set_blend_mode(BlendMode::AlphaBlend);
+        let color = Pixel::new(0x00, 0x00, 0xFF, 0x80);
+        swap_chain.output_state().write(&mut swap_chain.buffer[0], None, color);
+        assert_eq!(swap_chain.buffer[0], Pixel::new(0x40, 0x00, 0x80, 0xC0));
+
+        // Straight blending of the same colors gives the same result, apart
+        // from rounding.
+        swap_chain.set_alpha_mode(AlphaMode::Straight);
+        assert_eq!(swap_chain.buffer[0], Pixel::new(0x55, 0x00, 0xAA, 0xC0));
+        swap_chain.clear(Pixel::new(0xFF, 0x00, 0x00, 0x80));
+        swap_chain.output_state().write(&mut swap_chain.buffer[0], None, color);
+        assert_eq!(swap_chain.buffer[0], Pixel::new(0x55, 0x00, 0xAA, 0xC0));
+    }
+
+    #[test]
+    fn float_accumulation_avoids_banding() {
+        struct Faint;
+
+        impl FragmentShader2D for Faint {
+            fn run(&self) -> Pixel {
+                Pixel::new(0xFF, 0xFF, 0xFF, 0x03)
+            }
+        }
+
+        let full_screen = [
+            TriangleVertices::new(
+                Vector2f::new(-1.0, -1.0),
+                Vector2f::new(3.0, -1.0),
+                Vector2f::new(-1.0, 3.0),
+            ),
+        ];
+        let draw_layers = |float_accumulation| {
+            let mut swap_chain = SwapChain::with_extent(Extent { width: 4, height: 4 });
+            swap_chain.set_float_accumulation(float_accumulation);
+            swap_chain.set_blend_mode(BlendMode::AlphaBlend);
+            for _ in 0..100 {
+                swap_chain.draw_rasterized(&full_screen, &HalfWhiteShader, &Faint);
+            }
+            swap_chain.get_pixel(Vector2::new(1, 1)).unwrap().red()
+        };
+
+        // Every layer covers 3/255 of what's left, which rounds to a single
+        // step or none at all in 8 bits once the pixel gets brighter.
+        let exact = (255.0 * (1.0 - (1.0 - 3.0 / 255.0f64).powi(100))).round() as u8;
+        let accumulated = draw_layers(true);
+        let rounded = draw_layers(false);
+        assert!(accumulated.abs_diff(exact) <= 1, "{accumulated} != {exact}");
+        assert!(rounded.abs_diff(exact) > 3, "{rounded} == {exact}");
+    }
+
+    #[test]
+    fn float_accumulation_follows_clear() {
+        let mut swap_chain = SwapChain::with_extent(Extent { width: 2, height: 2 });
+        swap_chain.set_sample_count(SampleCount::X4);
+        swap_chain.set_float_accumulation(true);
+        assert!(swap_chain.float_accumulation());
+        swap_chain.set_blend_mode(BlendMode::AlphaBlend);
+        swap_chain.clear(Pixel::new(0x00, 0x00, 0xFF, 0xFF));
+        swap_chain.put_pixel(Vector2::new(1, 0), Pixel::new(0xFF, 0x00, 0x00, 0x80));
+        assert_eq!(swap_chain.get_pixel(Vector2::new(1, 0)), Some(Pixel::new(0x80, 0x00, 0x7F, 0xFF)));
+        assert_eq!(swap_chain.get_pixel(Vector2::new(0, 0)), Some(Pixel::new(0x00, 0x00, 0xFF, 0xFF)));
+
+        swap_chain.set_float_accumulation(false);
+        assert!(!swap_chain.float_accumulation());
+    }
+
+    #[test]
+    fn draw_with_scissor() {
+        let mut swap_chain = SwapChain::with_extent(Extent { width: 8, height: 8 });
+        swap_chain.clear(Pixel::BLACK);
+        swap_chain.set_scissor(Some(Rectangle2D::new(Vector2::new(2, 3), Vector2::new(5, 7))));
+
+        let vertices = [
+            TriangleVertices::new(
+                Vector2f::new(-1.0, -1.0),
+                Vector2f::new(3.0, -1.0),
+                Vector2f::new(-1.0, 3.0),
+            )
+        ];
+        swap_chain.draw_rasterized(&vertices, &HalfWhiteShader, &HalfWhiteShader);
+
+        for y in 0..8 {
+            for x in 0..8 {
+                let expected = if (2..5).contains(&x) && (3..7).contains(&y) {
+                    Pixel::new(0xFF, 0xFF, 0xFF, 0x80)
+                } else {
+                    Pixel::BLACK
+                };
+                assert_eq!(swap_chain.buffer[y * 8 + x], expected, "pixel at ({x}, {y})");
+            }
+        }
+
+        swap_chain.set_scissor(None);
+        swap_chain.clear(Pixel::BLACK);
+        swap_chain.draw_rasterized(&vertices, &HalfWhiteShader, &HalfWhiteShader);
+        assert!(swap_chain.buffer.iter().all(|pixel| *pixel == Pixel::new(0xFF, 0xFF, 0xFF, 0x80)));
+    }
+
+    #[test]
+    fn rasterization_bounds_are_clamped() {
+        let mut swap_chain = SwapChain::with_extent(Extent { width: 8, height: 8 });
+        let triangle = Triangle2D(Vector2::new(-5_000_000, -3), Vector2::new(4_000_000, 2),
+            Vector2::new(3, 9_000_000));
+        assert_eq!(swap_chain.rasterization_bounds(&triangle),
+            Some(Rectangle2D::new(Vector2::new(0, 0), Vector2::new(8, 8))));
+
+        swap_chain.set_scissor(Some(Rectangle2D::new(Vector2::new(2, 3), Vector2::new(5, 7))));
+        assert_eq!(swap_chain.rasterization_bounds(&triangle),
+            Some(Rectangle2D::new(Vector2::new(2, 3), Vector2::new(5, 7))));
+
+        let triangle = Triangle2D(Vector2::new(-50, -30), Vector2::new(-20, -20), Vector2::new(-40, -10));
+        assert_eq!(swap_chain.rasterization_bounds(&triangle), None);
+    }
+
+    #[test]
+    fn draw_far_off_screen_triangle() {
+        let mut swap_chain = SwapChain::with_extent(Extent { width: 8, height: 8 });
+        swap_chain.clear(Pixel::BLACK);
+
+        // Without clamping to the viewport, this would visit over a billion
+        // pixels.
+        let vertices = [
+            TriangleVertices::new(
+                Vector2f::new(-1.0, -1.0),
+                Vector2f::new(10_000.0, -1.0),
+                Vector2f::new(-1.0, 10_000.0),
+            )
+        ];
+        swap_chain.draw_rasterized(&vertices, &HalfWhiteShader, &HalfWhiteShader);
+        assert!(swap_chain.buffer.iter().all(|pixel| *pixel == Pixel::new(0xFF, 0xFF, 0xFF, 0x80)));
+    }
+
+    #[test]
+    fn draw_clipped_triangle() {
+        let mut swap_chain = SwapChain::with_extent(Extent { width: 8, height: 8 });
+        swap_chain.clear(Pixel::BLACK);
+
+        // The triangle covers the left half of the swap chain, but most of it
+        // lies beyond the left edge.
+        let vertices = [
+            TriangleVertices::new(
+                Vector2f::new(-3.0, -1.0),
+                Vector2f::new(0.0, -1.0),
+                Vector2f::new(0.0, 1.0),
+            )
+        ];
+        swap_chain.draw_rasterized(&vertices, &HalfWhiteShader, &HalfWhiteShader);
+
+        // The clipped polygon spans from (0, 5.33) over (0, 0) and (4, 0) to
+        // (4, 8) in pixel coordinates, and (0, 5.33) gets rounded to (0, 5).
+        for y in 0..8 {
+            for x in 0..8 {
+                let inside = x < 4 && 4 * y <= 20 + 3 * x;
+                let expected = if inside { Pixel::new(0xFF, 0xFF, 0xFF, 0x80) } else { Pixel::BLACK };
+                assert_eq!(swap_chain.buffer[y * 8 + x], expected, "pixel at ({x}, {y})");
+            }
+        }
+    }
+
+    #[test]
+    fn draw_quad_covers_shared_edge_once() {
+        let vertices = [
+            TriangleVertices::new(
+                Vector2f::new(-1.0, -1.0),
+                Vector2f::new(1.0, -1.0),
+                Vector2f::new(1.0, 1.0),
+            ),
+            TriangleVertices::new(
+                Vector2f::new(-1.0, -1.0),
+                Vector2f::new(1.0, 1.0),
+                Vector2f::new(-1.0, 1.0),
+            ),
+        ];
+
+        let mut swap_chain = SwapChain::with_extent(Extent { width: 8, height: 8 });
+        swap_chain.set_blend_mode(BlendMode::Additive);
+        swap_chain.draw_rasterized(&vertices, &HalfWhiteShader, &HalfWhiteShader);
+
+        // Every pixel of the quad, including those on the diagonal, is
+        // covered exactly once.
+        let expected = swap_chain.buffer[0];
+        assert_ne!(expected, Pixel::BLACK);
+        assert!(swap_chain.buffer.iter().all(|pixel| *pixel == expected));
+
+        // The samples along the border of the swap chain lie partly outside
+        // of the quad, but none of them is covered twice either.
+        swap_chain.clear(Pixel::BLACK);
+        swap_chain.set_sample_count(SampleCount::X4);
+        swap_chain.draw_rasterized(&vertices, &HalfWhiteShader, &HalfWhiteShader);
+        assert!(swap_chain.samples.iter().all(|sample| *sample == expected || *sample == Pixel::BLACK));
+
+        // The samples of pixels (2, 4) through (5, 4) are covered.
+        assert_eq!(swap_chain.samples[(4 * 8 + 2) * 4..(4 * 8 + 6) * 4], [expected; 16]);
+    }
+
+    #[test]
+    fn draw_degenerate_triangles() {
+        let point = Vector2f::new(0.25, -0.5);
+        let vertices = [
+            TriangleVertices::new(point, point, point),
+            TriangleVertices::new(
+                Vector2f::new(-1.0, -1.0),
+                Vector2f::new(0.0, 0.0),
+                Vector2f::new(1.0, 1.0),
+            ),
+            TriangleVertices::new(
+                Vector2f::new(-1.0, 0.0),
+                Vector2f::new(0.5, 0.0),
+                Vector2f::new(0.0, 0.0),
+            ),
+        ];
+
+        for sample_count in [SampleCount::X1, SampleCount::X4] {
+            let mut swap_chain = SwapChain::with_extent(Extent { width: 8, height: 8 });
+            swap_chain.set_sample_count(sample_count);
+            swap_chain.draw_rasterized(&vertices, &HalfWhiteShader, &HalfWhiteShader);
+            swap_chain.resolve();
+            assert!(swap_chain.buffer.iter().all(|pixel| *pixel == Pixel::BLACK), "{sample_count:?}");
+        }
+    }
+
+    /// Creates an image of which every pixel has a different color.
+    fn test_image(extent: Extent) -> Vec<Pixel> {
+        (0..extent.width * extent.height)
+            .map(|index| Pixel::new(index as u8 * 16, 0xFF - index as u8 * 8, 0x40, 0xFF))
+            .collect()
+    }
+
+    #[test]
+    fn blit_copies_one_to_one() {
+        let extent = Extent { width: 3, height: 2 };
+        let image = test_image(extent);
+
+        for filter in [Filter::Nearest, Filter::Bilinear] {
+            let mut swap_chain = SwapChain::with_extent(Extent { width: 6, height: 4 });
+            swap_chain.blit(&image, extent, Rectangle2D::new(Vector2::new(2, 1), Vector2::new(5, 3)), filter);
+
+            for y in 0..4 {
+                for x in 0..6 {
+                    let expected = if (2..5).contains(&x) && (1..3).contains(&y) {
+                        image[(y - 1) * 3 + x - 2]
+                    } else {
+                        Pixel::BLACK
+                    };
+                    assert_eq!(swap_chain.buffer[y * 6 + x], expected, "{filter:?} at ({x}, {y})");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn blit_upscales_twice() {
+        let extent = Extent { width: 2, height: 1 };
+        let image = [Pixel::BLACK, Pixel::WHITE];
+        let rect = Rectangle2D::new(Vector2::new(0, 0), Vector2::new(4, 2));
+
+        let mut swap_chain = SwapChain::with_extent(Extent { width: 4, height: 2 });
+        swap_chain.blit(&image, extent, rect, Filter::Nearest);
+        for row in swap_chain.buffer.chunks(4) {
+            assert_eq!(row, [Pixel::BLACK, Pixel::BLACK, Pixel::WHITE, Pixel::WHITE]);
+        }
+
+        swap_chain.blit(&image, extent, rect, Filter::Bilinear);
+        let gray = |value| Pixel::new(value, value, value, 0xFF);
+        for row in swap_chain.buffer.chunks(4) {
+            assert_eq!(row, [Pixel::BLACK, gray(0x40), gray(0xBF), Pixel::WHITE]);
+        }
+    }
+
+    #[test]
+    fn blit_is_clipped() {
+        let extent = Extent { width: 4, height: 4 };
+        let image = test_image(extent);
+
+        let mut swap_chain = SwapChain::with_extent(Extent { width: 4, height: 4 });
+        swap_chain.set_scissor(Some(Rectangle2D::new(Vector2::new(0, 0), Vector2::new(4, 3))));
+        let rect = Rectangle2D::new(Vector2::new(-2, 1), Vector2::new(2, 5));
+        swap_chain.blit(&image, extent, rect, Filter::Nearest);
+
+        assert_eq!(swap_chain.buffer[4..6], [image[2], image[3]]);
+        assert_eq!(swap_chain.buffer[8..10], [image[6], image[7]]);
+        for (index, pixel) in swap_chain.buffer.iter().enumerate() {
+            if ![4, 5, 8, 9].contains(&index) {
+                assert_eq!(*pixel, Pixel::BLACK, "{index}");
+            }
+        }
+    }
+
+    /// Gets the coordinates of the pixels that aren't black.
+    fn written_pixels(swap_chain: &SwapChain) -> Vec<(usize, usize)> {
+        let width = swap_chain.extent.width;
+        swap_chain.buffer.iter()
+            .enumerate()
+            .filter(|(_, pixel)| **pixel != Pixel::BLACK)
+            .map(|(index, _)| (index % width, index / width))
+            .collect()
+    }
+
+    /// The corners of a quad covering the whole swap chain, as a list of two
+    /// triangles, of which the first one covers the top left half.
+    const FULL_QUAD: [Vector2f; 6] = [
+        Vector2f { x: -1.0, y: -1.0 },
+        Vector2f { x: 1.0, y: -1.0 },
+        Vector2f { x: -1.0, y: 1.0 },
+        Vector2f { x: 1.0, y: -1.0 },
+        Vector2f { x: 1.0, y: 1.0 },
+        Vector2f { x: -1.0, y: 1.0 },
+    ];
+
+    struct VertexColorShader;
+
+    impl FragmentShader for VertexColorShader {
+        fn run(&self, input: &FragmentInput, _uniforms: &()) -> Pixel {
+            let varyings = input.varyings();
+            Pixel::from_normalized(std::array::from_fn(|index| varyings[COLOR_VARYING + index]))
+        }
+    }
+
+    #[test]
+    fn draw_arrays_with_colors() {
+        let mut swap_chain = SwapChain::with_extent(Extent { width: 8, height: 8 });
+        let colors = [Pixel::RED, Pixel::RED, Pixel::RED, Pixel::BLUE, Pixel::BLUE, Pixel::BLUE];
+        swap_chain.draw_arrays(&FULL_QUAD, Some(&colors), None, PrimitiveTopology::TriangleList,
+            &HalfWhiteShader, &VertexColorShader);
+        assert_eq!(swap_chain.get_pixel(Vector2::new(1, 1)), Some(Pixel::RED));
+        assert_eq!(swap_chain.get_pixel(Vector2::new(6, 6)), Some(Pixel::BLUE));
+        assert!(swap_chain.as_slice().iter().all(|pixel| *pixel == Pixel::RED || *pixel == Pixel::BLUE));
+
+        // Without colors, the vertices are white, and a remainder that
+        // doesn't form a triangle is ignored.
+        swap_chain.clear(Pixel::BLACK);
+        swap_chain.draw_arrays(&FULL_QUAD[..5], None, None, PrimitiveTopology::TriangleList, &HalfWhiteShader,
+            &VertexColorShader);
+        assert_eq!(swap_chain.get_pixel(Vector2::new(1, 1)), Some(Pixel::WHITE));
+        assert_eq!(swap_chain.get_pixel(Vector2::new(6, 6)), Some(Pixel::BLACK));
+    }
+
+    #[test]
+    fn strip_and_fan_triangles() {
+        let strip = PrimitiveTopology::TriangleStrip;
+        assert_eq!(strip.triangle_count(4), 2);
+        assert_eq!(strip.triangle_count(1), 0);
+        assert_eq!([strip.triangle(0), strip.triangle(1)], [[0, 1, 2], [2, 1, 3]]);
+
+        // A zigzag strip, of which every triangle goes counter-clockwise.
+        let positions = [Vector2f::new(0.0, 0.0), Vector2f::new(1.0, 0.0), Vector2f::new(0.0, 1.0),
+            Vector2f::new(1.0, 1.0)];
+        for index in 0..strip.triangle_count(positions.len()) {
+            let [a, b, c] = strip.triangle(index).map(|index| positions[index]);
+            let b = Vector2f::new(b.x - a.x, b.y - a.y);
+            let c = Vector2f::new(c.x - a.x, c.y - a.y);
+            assert!(b.cross(c) > 0.0, "triangle {index}");
+        }
+
+        let fan = PrimitiveTopology::TriangleFan;
+        assert_eq!(fan.triangle_count(5), 3);
+        assert_eq!(fan.triangle_count(0), 0);
+        assert_eq!((0..3).map(|index| fan.triangle(index)).collect::<Vec<_>>(),
+            [[0, 1, 2], [0, 2, 3], [0, 3, 4]]);
+    }
+
+    #[test]
+    fn draw_arrays_triangle_strip() {
+        let strip = [FULL_QUAD[0], FULL_QUAD[1], FULL_QUAD[2], FULL_QUAD[4]];
+        let fan = [FULL_QUAD[0], FULL_QUAD[1], FULL_QUAD[4], FULL_QUAD[2]];
+        let topologies = [(strip, PrimitiveTopology::TriangleStrip), (fan, PrimitiveTopology::TriangleFan)];
+        for (positions, topology) in topologies {
+            // Both triangles have the same winding, so culling keeps either
+            // both or neither of them.
+            for (cull_mode, expected) in [(CullMode::Front, Pixel::WHITE), (CullMode::Back, Pixel::BLACK)] {
+                let mut swap_chain = SwapChain::with_extent(Extent { width: 8, height: 8 });
+                swap_chain.set_cull_mode(cull_mode);
+                swap_chain.draw_arrays(&positions, None, None, topology, &HalfWhiteShader, &VertexColorShader);
+                assert!(swap_chain.as_slice().iter().all(|pixel| *pixel == expected),
+                    "{topology:?} {cull_mode:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn draw_arrays_with_uvs() {
+        struct TextureShader(MipmappedTexture);
+
+        impl FragmentShader for TextureShader {
+            fn run(&self, input: &FragmentInput, _uniforms: &()) -> Pixel {
+                let sampler = Sampler { filter: Filter::Nearest, mipmap_filter: MipmapFilter::None };
+                sampler.sample_fragment(&self.0, input, UV_VARYING, UV_VARYING + 1)
+            }
+        }
+
+        let uvs = [
+            Vector2f::new(0.0, 0.0),
+            Vector2f::new(1.0, 0.0),
+            Vector2f::new(0.0, 1.0),
+            Vector2f::new(1.0, 0.0),
+            Vector2f::new(1.0, 1.0),
+            Vector2f::new(0.0, 1.0),
+        ];
+        let extent = Extent { width: 2, height: 2 };
+        let texture = MipmappedTexture::checkerboard(extent, 1, Pixel::RED, Pixel::BLUE);
+        let mut swap_chain = SwapChain::with_extent(Extent { width: 8, height: 8 });
+        swap_chain.draw_arrays(&FULL_QUAD, None, Some(&uvs), PrimitiveTopology::TriangleList, &HalfWhiteShader,
+            &TextureShader(texture));
+
+        let expected = [(1, 1, Pixel::RED), (6, 1, Pixel::BLUE), (1, 6, Pixel::BLUE), (6, 6, Pixel::RED)];
+        for (x, y, color) in expected {
+            assert_eq!(swap_chain.get_pixel(Vector2::new(x, y)), Some(color), "({x}, {y})");
+        }
+    }
+
+    #[test]
+    fn stats_count_one_triangle() {
+        let mut swap_chain = SwapChain::with_extent(Extent { width: 16, height: 16 });
+        let vertices = TriangleVertices::new(
+            Vector2f::new(-1.0, -1.0),
+            Vector2f::new(1.0, -1.0),
+            Vector2f::new(-1.0, 1.0),
+        );
+        swap_chain.fill_triangle(vertices, Pixel::WHITE);
+        assert_eq!(swap_chain.take_stats(), None);
+
+        swap_chain.set_stats(true);
+        swap_chain.fill_triangle(vertices, Pixel::WHITE);
+        let shaded = written_pixels(&swap_chain).len() as u64;
+        let stats = swap_chain.take_stats().unwrap();
+        assert_eq!(stats, RenderStats {
+            triangles_submitted: 1,
+            triangles_culled: 0,
+            pixels_shaded: shaded,
+            fragment_shader_invocations: shaded,
+        });
+        assert!(stats.pixels_shaded > 0);
+        assert_eq!(swap_chain.stats(), Some(RenderStats::default()));
+
+        // Culled triangles are submitted, without shading anything.
+        swap_chain.set_cull_mode(CullMode::Back);
+        let reversed = TriangleVertices::new(vertices.a, vertices.c, vertices.b);
+        swap_chain.draw_rasterized(&[vertices, reversed], &HalfWhiteShader, &HalfWhiteShader);
+        let stats = swap_chain.take_stats().unwrap();
+        assert_eq!((stats.triangles_submitted, stats.triangles_culled), (2, 1));
+        assert_eq!(stats.pixels_shaded, shaded);
+
+        // When multisampling, every shaded pixel is counted once.
+        swap_chain.set_cull_mode(CullMode::None);
+        swap_chain.set_sample_count(SampleCount::X4);
+        swap_chain.fill_triangle(vertices, Pixel::WHITE);
+        let stats = swap_chain.take_stats().unwrap();
+        assert!(stats.pixels_shaded >= shaded, "{stats:?}");
+        assert_eq!(stats.fragment_shader_invocations, stats.pixels_shaded);
+
+        swap_chain.set_stats(false);
+        assert_eq!(swap_chain.stats(), None);
+    }
+
+    #[test]
+    fn draw_with_closure_shaders() {
+        let vertices = [TriangleVertices::new(
+            Vector2f::new(-1.0, -1.0),
+            Vector2f::new(1.0, -1.0),
+            Vector2f::new(-1.0, 1.0),
+        )];
+        let mut expected = SwapChain::with_extent(Extent { width: 16, height: 16 });
+        expected.draw_rasterized(&vertices, &HalfWhiteShader, &WhiteShader);
+
+        // The closures capture their environment, like the offset.
+        let offset = 0.5;
+        let mut swap_chain = SwapChain::with_extent(Extent { width: 16, height: 16 });
+        let vertex_shader = FnVertexShader(|position| Vector2f::new(position.x + offset, position.y));
+        swap_chain.draw_rasterized(&vertices, &vertex_shader, &FnFragmentShader(|| Pixel::WHITE));
+        assert_eq!(swap_chain.get_pixel(Vector2::new(5, 5)), Some(Pixel::WHITE));
+        assert_ne!(swap_chain.as_slice(), expected.as_slice());
+
+        swap_chain.clear(Pixel::BLACK);
+        swap_chain.draw_rasterized(&vertices, &FnVertexShader(|position| position),
+            &FnFragmentShader(|| Pixel::WHITE));
+        assert_eq!(swap_chain.as_slice(), expected.as_slice());
+    }
+
+    #[test]
+    fn fill_triangle_covers_centroid() {
+        let mut swap_chain = SwapChain::with_extent(Extent { width: 16, height: 16 });
+        let vertices = TriangleVertices::new(
+            Vector2f::new(-1.0, -1.0),
+            Vector2f::new(1.0, -1.0),
+            Vector2f::new(-1.0, 1.0),
+        );
+        swap_chain.fill_triangle(vertices, Pixel::WHITE);
+
+        // The centroid lies at a third of the way from the right angle.
+        assert_eq!(swap_chain.get_pixel(Vector2::new(5, 5)), Some(Pixel::WHITE));
+        assert_eq!(swap_chain.get_pixel(Vector2::new(12, 12)), Some(Pixel::BLACK));
+
+        let mut expected = SwapChain::with_extent(Extent { width: 16, height: 16 });
+        expected.draw_rasterized(&[vertices], &HalfWhiteShader, &WhiteShader);
+        assert_eq!(swap_chain.as_slice(), expected.as_slice());
+    }
+
+    #[test]
+    fn flood_fill_enclosed_region() {
+        let mut swap_chain = SwapChain::with_extent(Extent { width: 12, height: 12 });
+        swap_chain.draw_rect(Rectangle2D::new(Vector2::new(2, 2), Vector2::new(9, 8)), Pixel::WHITE);
+        let outline = swap_chain.as_slice().to_vec();
+
+        // The inside is filled, without leaking past the outline.
+        swap_chain.flood_fill(Vector2::new(5, 4), Pixel::RED, 0);
+        for (index, pixel) in swap_chain.as_slice().iter().enumerate() {
+            let (x, y) = (index % 12, index / 12);
+            let inside = (3..8).contains(&x) && (3..7).contains(&y);
+            let expected = if inside { Pixel::RED } else { outline[index] };
+            assert_eq!(*pixel, expected, "({x}, {y})");
+        }
+
+        // Through a gap in the outline of 21 pixels, the outside is filled as
+        // well, but not the outline, nor the inside, which no longer matches.
+        swap_chain.put_pixel(Vector2::new(2, 5), Pixel::BLACK);
+        swap_chain.flood_fill(Vector2::new(0, 0), Pixel::BLUE, 0);
+        let blue = swap_chain.as_slice().iter().filter(|pixel| **pixel == Pixel::BLUE).count();
+        assert_eq!(blue, 144 - 21 - 20);
+        assert_eq!(swap_chain.get_pixel(Vector2::new(2, 5)), Some(Pixel::BLUE));
+        assert_eq!(swap_chain.get_pixel(Vector2::new(2, 4)), Some(Pixel::WHITE));
+        assert_eq!(swap_chain.get_pixel(Vector2::new(3, 5)), Some(Pixel::RED));
+    }
+
+    #[test]
+    fn flood_fill_tolerance_and_scissor() {
+        let mut swap_chain = SwapChain::with_extent(Extent { width: 8, height: 4 });
+        swap_chain.set_blend_mode(BlendMode::AlphaBlend);
+        let (gray, red) = (Pixel::new(4, 4, 4, 0xFF), Pixel::new(9, 0, 0, 0xFF));
+        swap_chain.fill_rect(Rectangle2D::new(Vector2::new(0, 0), Vector2::new(2, 4)), gray);
+        swap_chain.fill_rect(Rectangle2D::new(Vector2::new(4, 0), Vector2::new(5, 4)), red);
+
+        // Only the almost black pixels are replaced, regardless of the blend
+        // mode, and only inside of the scissor rectangle.
+        swap_chain.set_scissor(Some(Rectangle2D::new(Vector2::new(1, 0), Vector2::new(7, 4))));
+        swap_chain.flood_fill(Vector2::new(2, 2), Pixel::GREEN.with_alpha(0x80), 4);
+        let filled: Vec<_> = (0..8).map(|x| swap_chain.get_pixel(Vector2::new(x, 1)).unwrap()).collect();
+        let green = Pixel::GREEN.with_alpha(0x80);
+        assert_eq!(filled, [gray, green, green, green, red, Pixel::BLACK, Pixel::BLACK, Pixel::BLACK]);
+
+        // Seeds outside of the render area are ignored.
+        swap_chain.flood_fill(Vector2::new(7, 0), Pixel::RED, 0xFF);
+        swap_chain.flood_fill(Vector2::new(-1, 0), Pixel::RED, 0xFF);
+        assert!(!swap_chain.as_slice().contains(&Pixel::RED));
+    }
+
+    #[test]
+    fn fill_rect_inside() {
+        let mut swap_chain = SwapChain::with_extent(Extent { width: 6, height: 5 });
+        swap_chain.fill_rect(Rectangle2D::new(Vector2::new(1, 2), Vector2::new(4, 4)), Pixel::RED);
+        assert_eq!(written_pixels(&swap_chain), [(1, 2), (2, 2), (3, 2), (1, 3), (2, 3), (3, 3)]);
+        assert_eq!(swap_chain.buffer[2 * 6 + 1], Pixel::RED);
+    }
+
+    #[test]
+    fn fill_rect_clipped() {
+        let mut swap_chain = SwapChain::with_extent(Extent { width: 4, height: 4 });
+        swap_chain.fill_rect(Rectangle2D::new(Vector2::new(-5, 2), Vector2::new(2, 10)), Pixel::RED);
+        assert_eq!(written_pixels(&swap_chain), [(0, 2), (1, 2), (0, 3), (1, 3)]);
+
+        swap_chain.clear(Pixel::BLACK);
+        swap_chain.set_scissor(Some(Rectangle2D::new(Vector2::new(1, 0), Vector2::new(4, 3))));
+        swap_chain.fill_rect(Rectangle2D::new(Vector2::new(-5, 2), Vector2::new(2, 10)), Pixel::RED);
+        assert_eq!(written_pixels(&swap_chain), [(1, 2)]);
+    }
+
+    #[test]
+    fn fill_rect_zero_size() {
+        let mut swap_chain = SwapChain::with_extent(Extent { width: 4, height: 4 });
+        swap_chain.fill_rect(Rectangle2D::new(Vector2::new(2, 1), Vector2::new(2, 3)), Pixel::RED);
+        swap_chain.fill_rect(Rectangle2D::new(Vector2::new(1, 1), Vector2::new(3, 1)), Pixel::RED);
+        swap_chain.draw_rect(Rectangle2D::new(Vector2::new(1, 1), Vector2::new(1, 1)), Pixel::RED);
+        assert!(written_pixels(&swap_chain).is_empty());
+    }
+
+    #[test]
+    fn fill_circle_covers_pixels_inside_radius() {
+        let mut swap_chain = SwapChain::with_extent(Extent { width: 16, height: 16 });
+        swap_chain.fill_circle(Vector2f::new(8.0, 8.0), 3.0, Pixel::RED);
+
+        let written = written_pixels(&swap_chain);
+        assert!(written.contains(&(8, 8)));
+        assert!(written.contains(&(11, 8)));
+        assert!(written.contains(&(8, 5)));
+
+        // Just outside of the radius, diagonally and straight.
+        assert!(!written.contains(&(11, 9)));
+        assert!(!written.contains(&(8, 12)));
+        assert!(!written.contains(&(10, 11)));
+        assert_eq!(written.len(), 29);
+    }
+
+    #[test]
+    fn fill_circle_is_clipped() {
+        let mut swap_chain = SwapChain::with_extent(Extent { width: 4, height: 4 });
+        swap_chain.fill_circle(Vector2f::new(0.0, 0.0), 1.5, Pixel::RED);
+        assert_eq!(written_pixels(&swap_chain), [(0, 0), (1, 0), (0, 1), (1, 1)]);
+
+        swap_chain.clear(Pixel::BLACK);
+        swap_chain.fill_circle(Vector2f::new(-10.0, 2.0), 5.0, Pixel::RED);
+        swap_chain.fill_circle(Vector2f::new(2.0, 2.0), 0.0, Pixel::RED);
+        swap_chain.fill_circle(Vector2f::new(2.0, 2.0), f32::NAN, Pixel::RED);
+        assert!(written_pixels(&swap_chain).is_empty());
+    }
+
+    #[test]
+    fn fill_circle_antialiased_edges() {
+        let mut swap_chain = SwapChain::with_extent(Extent { width: 16, height: 16 });
+        swap_chain.set_blend_mode(BlendMode::AlphaBlend);
+        swap_chain.fill_circle_antialiased(Vector2f::new(8.0, 8.0), 3.0, Pixel::WHITE);
+
+        // The center is fully covered, and the pixel on the edge halfway.
+        assert_eq!(swap_chain.buffer[8 * 16 + 8], Pixel::WHITE);
+        assert_eq!(swap_chain.buffer[8 * 16 + 11], Pixel::new(0x80, 0x80, 0x80, 0xFF));
+        assert_eq!(swap_chain.buffer[8 * 16 + 12], Pixel::BLACK);
+    }
+
+    #[test]
+    fn collinear_bezier_is_straight_line() {
+        let (from, to) = (Vector2f::new(1.0, 1.0), Vector2f::new(13.0, 7.0));
+        let mut expected = SwapChain::with_extent(Extent { width: 16, height: 16 });
+        expected.set_blend_mode(BlendMode::AlphaBlend);
+        expected.draw_line_aa(from, to, Pixel::WHITE);
+
+        let assert_matches = |swap_chain: &SwapChain| {
+            let pixels = swap_chain.as_slice().iter().zip(expected.as_slice());
+            for (index, (pixel, expected)) in pixels.enumerate() {
+                assert!(pixel.approx_eq(*expected, 1), "pixel {index}: {pixel:?} != {expected:?}");
+            }
+        };
+
+        let mut quadratic = SwapChain::with_extent(Extent { width: 16, height: 16 });
+        quadratic.set_blend_mode(BlendMode::AlphaBlend);
+        quadratic.draw_bezier_quadratic(from, Vector2f::new(3.0, 2.0), to, Pixel::WHITE, 7);
+        assert_matches(&quadratic);
+
+        let mut cubic = SwapChain::with_extent(Extent { width: 16, height: 16 });
+        cubic.set_blend_mode(BlendMode::AlphaBlend);
+        cubic.draw_bezier_cubic(to, Vector2f::new(9.0, 5.0), Vector2f::new(4.0, 2.5), from, Pixel::WHITE, 5);
+        assert_matches(&cubic);
+    }
+
+    #[test]
+    fn draw_line_aa_straddles_line() {
+        let gray = Pixel::new(0x80, 0x80, 0x80, 0xFF);
+        let mut swap_chain = SwapChain::with_extent(Extent { width: 16, height: 16 });
+        swap_chain.set_blend_mode(BlendMode::AlphaBlend);
+
+        // The line passes right between the pixels of every other column,
+        // and through the centers of the others, of which the pixels below
+        // aren't written. The endpoints are half covered.
+        swap_chain.draw_line_aa(Vector2f::new(1.0, 1.0), Vector2f::new(7.0, 4.0), Pixel::WHITE);
+        assert_eq!(written_pixels(&swap_chain),
+            [(1, 1), (2, 1), (2, 2), (3, 2), (4, 2), (4, 3), (5, 3), (6, 3), (6, 4), (7, 4)]);
+        for (x, y) in [(1, 1), (2, 1), (2, 2), (4, 2), (4, 3), (6, 3), (6, 4), (7, 4)] {
+            assert_eq!(swap_chain.get_pixel(Vector2::new(x, y)), Some(gray), "({x}, {y})");
+        }
+        assert_eq!(swap_chain.get_pixel(Vector2::new(3, 2)), Some(Pixel::WHITE));
+
+        // Steep lines walk along Y instead, regardless of their direction.
+        let mut steep = SwapChain::with_extent(Extent { width: 16, height: 16 });
+        steep.set_blend_mode(BlendMode::AlphaBlend);
+        steep.draw_line_aa(Vector2f::new(4.0, 7.0), Vector2f::new(1.0, 1.0), Pixel::WHITE);
+        let transposed: Vec<_> = swap_chain.as_slice().chunks_exact(16).take(8)
+            .map(|row| row[..8].to_vec())
+            .collect();
+        for (y, row) in steep.as_slice().chunks_exact(16).take(8).enumerate() {
+            let expected: Vec<_> = (0..8).map(|x| transposed[x][y]).collect();
+            assert_eq!(row[..8], expected, "row {y}");
+        }
+    }
+
+    #[test]
+    fn fill_convex_polygon_square() {
+        let mut swap_chain = SwapChain::with_extent(Extent { width: 8, height: 8 });
+        let square = [
+            Vector2f::new(2.0, 2.0),
+            Vector2f::new(2.0, 5.0),
+            Vector2f::new(6.0, 5.0),
+            Vector2f::new(6.0, 2.0),
+        ];
+        swap_chain.fill_convex_polygon(&square, Pixel::RED);
+        let expected: Vec<_> = (2..5).flat_map(|y| (2..6).map(move |x| (x, y))).collect();
+        assert_eq!(written_pixels(&swap_chain), expected);
+
+        // The square is clipped, and concave polygons aren't filled.
+        swap_chain.clear(Pixel::BLACK);
+        swap_chain.fill_convex_polygon(&square.map(|point| Vector2f::new(point.x + 4.0, point.y - 4.0)),
+            Pixel::RED);
+        assert_eq!(written_pixels(&swap_chain), [(6, 0), (7, 0)]);
+
+        swap_chain.clear(Pixel::BLACK);
+        let concave = [square[0], Vector2f::new(4.0, 3.0), square[1], square[2], square[3]];
+        swap_chain.fill_convex_polygon(&concave, Pixel::RED);
+        assert!(written_pixels(&swap_chain).is_empty());
+    }
+
+    #[test]
+    fn fill_convex_polygon_pentagon() {
+        let pentagon: Vec<Vector2f> = (0..5)
+            .map(|index| {
+                let angle = index as f32 * std::f32::consts::TAU / 5.0;
+                Vector2f::new(8.0 + 6.5 * angle.sin(), 8.0 - 6.5 * angle.cos())
+            })
+            .collect();
+
+        let mut swap_chain = SwapChain::with_extent(Extent { width: 16, height: 16 });
+        swap_chain.fill_convex_polygon(&pentagon, Pixel::RED);
+
+        // Exactly the pixels of which the center is inside of the pentagon are
+        // filled, since none of them lie on its edges.
+        let inside = |x: f32, y: f32| (0..5).all(|index| {
+            let (from, to) = (pentagon[index], pentagon[(index + 1) % 5]);
+            (to.x - from.x) * (y - from.y) - (to.y - from.y) * (x - from.x) > 0.0
+        });
+        let expected: Vec<_> = (0..16)
+            .flat_map(|y| (0..16).map(move |x| (x, y)))
+            .filter(|&(x, y)| inside(x as f32, y as f32))
+            .collect();
+        assert!(expected.len() > 80);
+        assert_eq!(written_pixels(&swap_chain), expected);
+    }
+
+    #[test]
+    fn draw_rect_outline() {
+        let mut swap_chain = SwapChain::with_extent(Extent { width: 5, height: 5 });
+        swap_chain.set_blend_mode(BlendMode::Additive);
+        let color = Pixel::new(0x80, 0x00, 0x00, 0xFF);
+        swap_chain.draw_rect(Rectangle2D::new(Vector2::new(1, 1), Vector2::new(4, 5)), color);
+
+        let outline = [(1, 1), (2, 1), (3, 1), (1, 2), (3, 2), (1, 3), (3, 3), (1, 4), (2, 4), (3, 4)];
+        assert_eq!(written_pixels(&swap_chain), outline);
+
+        // The corners aren't covered twice.
+        assert!(outline.iter().all(|(x, y)| swap_chain.buffer[y * 5 + x] == color));
+
+        // A rectangle of a single pixel is just that pixel.
+        swap_chain.clear(Pixel::BLACK);
+        swap_chain.draw_rect(Rectangle2D::new(Vector2::new(2, 2), Vector2::new(3, 3)), color);
+        assert_eq!(written_pixels(&swap_chain), [(2, 2)]);
+        assert_eq!(swap_chain.buffer[2 * 5 + 2], color);
+    }
+
+    #[test]
+    fn draw_text() {
+        // The glyphs are three by two pixels, with an 'A' of which only the
+        // top row is set and a 'B' of which only the left column is set.
+        let (set, unset) = (Pixel::WHITE, Pixel::TRANSPARENT);
+        let atlas = [
+            set, set, set, set, unset, unset,
+            unset, unset, unset, set, unset, unset,
+        ];
+        let glyph_extent = Extent { width: 3, height: 2 };
+        let font = BitmapFont::new(&atlas, Extent { width: 6, height: 2 }, glyph_extent, 'A');
+
+        let mut swap_chain = SwapChain::with_extent(Extent { width: 8, height: 6 });
+        swap_chain.draw_text(&font, "AB\n?B", Vector2::new(1, 1), Pixel::RED);
+        assert_eq!(written_pixels(&swap_chain), [(1, 1), (2, 1), (3, 1), (4, 1), (4, 2), (4, 3), (4, 4)]);
+        assert_eq!(swap_chain.buffer[8 + 1], Pixel::RED);
+
+        // The text is clipped to the swap chain.
+        swap_chain.clear(Pixel::BLACK);
+        swap_chain.draw_text(&font, "BA", Vector2::new(-1, 5), Pixel::RED);
+        assert_eq!(written_pixels(&swap_chain), [(2, 5), (3, 5), (4, 5)]);
+    }
+
+    /// Fills the triangle by hit testing every pixel of the swap chain. A
+    /// pixel exactly on an edge is only covered when it's a top or left edge.
+    fn reference_fill(extent: Extent, triangle: &Triangle2D<i32>) -> Vec<bool> {
+        let bounds = triangle.encapsulating_rectangle();
+        let edges = EdgeFunction::for_triangle(triangle);
+        let mut coverage = Vec::new();
+        for y in 0..extent.height as i32 {
+            for x in 0..extent.width as i32 {
+                let point = Vector2::new(x, y);
+                let on_excluded_edge = edges.iter()
+                    .any(|edge| edge.evaluate(point) == 0 && !edge.is_top_left());
+                coverage.push(bounds.contains(point) && triangle.hit_test(point) && !on_excluded_edge);
+            }
+        }
+        coverage
+    }
+
+    #[test]
+    fn default_state() {
+        let swap_chain = SwapChain::with_extent(Extent { width: 4, height: 4 });
+        assert_eq!(swap_chain.state(), &RasterizerState::default());
+        assert_eq!(swap_chain.blend_mode(), BlendMode::Opaque);
+        assert_eq!(swap_chain.scissor(), None);
+        assert_eq!(swap_chain.depth_test(), None);
+        assert!(swap_chain.depth_write() && swap_chain.color_write());
+
+        // Setting a single field changes the state.
+        let mut swap_chain = SwapChain::with_extent(Extent { width: 4, height: 4 });
+        swap_chain.set_shading_mode(ShadingMode::Flat(ProvokingVertex::Last));
+        assert_eq!(swap_chain.state().shading_mode, ShadingMode::Flat(ProvokingVertex::Last));
+    }
+
+    #[test]
+    fn set_state_changes_output() {
+        let quad = [
+            TriangleVertices::new(
+                Vector2f::new(-1.0, -1.0),
+                Vector2f::new(1.0, -1.0),
+                Vector2f::new(1.0, 1.0),
+            ),
+            TriangleVertices::new(
+                Vector2f::new(-1.0, -1.0),
+                Vector2f::new(1.0, 1.0),
+                Vector2f::new(-1.0, 1.0),
+            ),
+        ];
+
+        let mut swap_chain = SwapChain::with_extent(Extent { width: 4, height: 4 });
+        swap_chain.set_state(RasterizerState::default());
+        swap_chain.draw_rasterized(&quad, &HalfWhiteShader, &HalfWhiteShader);
+        assert_eq!(swap_chain.buffer[0], Pixel::new(0xFF, 0xFF, 0xFF, 0x80));
+
+        let state = RasterizerState {
+            blend_mode: BlendMode::AlphaBlend,
+            scissor: Some(Rectangle2D::new(Vector2::new(0, 0), Vector2::new(2, 4))),
+            ..Default::default()
+        };
+        swap_chain.clear(Pixel::BLACK);
+        swap_chain.set_state(state);
+        swap_chain.draw_rasterized(&quad, &HalfWhiteShader, &HalfWhiteShader);
+        assert_eq!(swap_chain.blend_mode(), BlendMode::AlphaBlend);
+        assert_eq!(swap_chain.buffer[0], Pixel::new(0x80, 0x80, 0x80, 0xFF));
+        assert_eq!(swap_chain.buffer[3], Pixel::BLACK);
+
+        // The depth buffer is created for the depth test of the state.
+        swap_chain.set_state(RasterizerState { depth_test: Some(CompareFunction::Less), ..state });
+        assert!(swap_chain.depth.is_some());
+    }
+
+    #[test]
+    fn viewport_cull_skips_off_screen_triangles() {
+        use std::cell::Cell;
+
+        struct Counting {
+            vertices: Cell<usize>,
+            fragments: Cell<usize>,
+        }
+
+        impl VertexShader2D for Counting {
+            fn run(&self, position: Vector2f) -> Vector2f {
+                self.vertices.set(self.vertices.get() + 1);
+                position
+            }
+        }
+
+        impl FragmentShader2D for Counting {
+            fn run(&self) -> Pixel {
+                self.fragments.set(self.fragments.get() + 1);
+                Pixel::WHITE
+            }
+        }
+
+        let on_screen = TriangleVertices::new(
+            Vector2f::new(-1.0, -1.0),
+            Vector2f::new(0.0, -1.0),
+            Vector2f::new(-1.0, 0.0),
+        );
+        let off_screen = [
+            TriangleVertices::new(Vector2f::new(1.5, -1.0), Vector2f::new(3.0, -1.0), Vector2f::new(1.5, 1.0)),
+            TriangleVertices::new(Vector2f::new(-1.0, -4.0), Vector2f::new(1.0, -4.0),
+                Vector2f::new(0.0, -2.0)),
+        ];
+        let vertices = [off_screen[0], on_screen, off_screen[1]];
+
+        let mut expected = SwapChain::with_extent(Extent { width: 8, height: 8 });
+        let shader = Counting { vertices: Cell::new(0), fragments: Cell::new(0) };
+        expected.draw_rasterized(&vertices, &shader, &shader);
+        assert_eq!(shader.vertices.get(), 9);
+
+        let mut swap_chain = SwapChain::with_extent(Extent { width: 8, height: 8 });
+        swap_chain.set_viewport_cull(ViewportCull::BeforeVertexShader);
+        let culled = Counting { vertices: Cell::new(0), fragments: Cell::new(0) };
+        swap_chain.draw_rasterized(&vertices, &culled, &culled);
+        assert_eq!(culled.vertices.get(), 3);
+        assert_eq!(culled.fragments.get(), shader.fragments.get());
+        assert_eq!(swap_chain.as_slice(), expected.as_slice());
+
+        // Only the off-screen triangles are drawn, so no fragment is shaded.
+        let culled = Counting { vertices: Cell::new(0), fragments: Cell::new(0) };
+        swap_chain.draw_rasterized(&off_screen, &culled, &culled);
+        assert_eq!((culled.vertices.get(), culled.fragments.get()), (0, 0));
+    }
+
+    #[test]
+    fn vertex_rounding() {
+        // The right edge ends at 3.6 pixels, which is snapped to either 3 or
+        // 4, covering the pixels up to it, exclusive.
+        let vertices = [
+            TriangleVertices::new(Vector2f::new(0.0, 0.0), Vector2f::new(3.6, 0.0), Vector2f::new(3.6, 3.0)),
+        ];
+        let draw = |rounding| {
+            let mut swap_chain = SwapChain::with_extent(Extent { width: 6, height: 4 });
+            swap_chain.set_vertex_rounding(rounding);
+            swap_chain.draw_rasterized_px(&vertices, &HalfWhiteShader, &WhiteShader);
+            written_pixels(&swap_chain).iter().map(|(x, _)| *x).max()
+        };
+
+        assert_eq!(draw(Rounding::Nearest), Some(3));
+        assert_eq!(draw(Rounding::Ceil), Some(3));
+        assert_eq!(draw(Rounding::Floor), Some(2));
+        assert_eq!(draw(Rounding::Truncate), Some(2));
+    }
+
+    #[test]
+    fn cull_by_winding_order() {
+        // The first triangle goes clockwise on screen, the second one
+        // counter-clockwise.
+        let vertices = [
+            TriangleVertices::new(Vector2f::new(0.0, 0.0), Vector2f::new(4.0, 0.0), Vector2f::new(0.0, 4.0)),
+            TriangleVertices::new(Vector2f::new(4.0, 4.0), Vector2f::new(4.0, 8.0), Vector2f::new(8.0, 4.0)),
+        ];
+        let draw = |cull_mode, front_face| {
+            let mut swap_chain = SwapChain::with_extent(Extent { width: 8, height: 8 });
+            swap_chain.set_cull_mode(cull_mode);
+            swap_chain.set_front_face(front_face);
+            swap_chain.draw_rasterized_px(&vertices, &HalfWhiteShader, &WhiteShader);
+            let pixel = |x, y| swap_chain.get_pixel(Vector2::new(x, y)) == Some(Pixel::WHITE);
+            (pixel(1, 1), pixel(5, 5))
+        };
+
+        assert_eq!(draw(CullMode::None, FrontFace::CounterClockwise), (true, true));
+        assert_eq!(draw(CullMode::Back, FrontFace::CounterClockwise), (false, true));
+        assert_eq!(draw(CullMode::Front, FrontFace::CounterClockwise), (true, false));
+        assert_eq!(draw(CullMode::Back, FrontFace::Clockwise), (true, false));
+        assert_eq!(draw(CullMode::Front, FrontFace::Clockwise), (false, true));
+    }
+
+    #[test]
+    fn conservative_raster_covers_touched_pixels() {
+        // A thin sliver, of which the long edge grazes the pixels above it.
+        let sliver = [
+            TriangleVertices::new(
+                Vector2f::new(1.0, 1.0),
+                Vector2f::new(13.0, 2.0),
+                Vector2f::new(1.0, 2.0),
+            ),
+        ];
+
+        for sample_count in [SampleCount::X1, SampleCount::X4] {
+            let draw = |conservative_raster| {
+                let mut swap_chain = SwapChain::with_extent(Extent { width: 16, height: 4 });
+                swap_chain.set_sample_count(sample_count);
+                swap_chain.set_conservative_raster(conservative_raster);
+                swap_chain.draw_rasterized_px(&sliver, &HalfWhiteShader, &WhiteShader);
+                swap_chain.resolve();
+                written_pixels(&swap_chain)
+            };
+
+            let standard = draw(ConservativeRaster::Disabled);
+            let conservative = draw(ConservativeRaster::Overestimate);
+            assert!(standard.len() < conservative.len(), "{sample_count:?}");
+            assert!(standard.iter().all(|pixel| conservative.contains(pixel)), "{sample_count:?}");
+
+            // The center of the pixel lies above the sliver, of which the
+            // edge only passes through the bottom of the pixel.
+            assert!(!standard.contains(&(7, 1)) && conservative.contains(&(7, 1)), "{sample_count:?}");
+        }
+    }
+
+    #[test]
+    fn y_axis_orientation() {
+        let mut swap_chain = SwapChain::with_extent(Extent { width: 8, height: 6 });
+        let top = Vector2f::new(0.0, 1.0);
+        assert_eq!(swap_chain.vertex_to_pixel_position(top), Vector2f::new(4.0, 6.0));
+
+        swap_chain.set_y_axis(YAxis::Up);
+        assert_eq!(swap_chain.vertex_to_pixel_position(top), Vector2f::new(4.0, 0.0));
+        assert_eq!(swap_chain.vertex_to_pixel_position(Vector2f::new(-1.0, -1.0)), Vector2f::new(0.0, 6.0));
+
+        // The triangle covers the top half of the swap chain, with the winding
+        // flipped on screen.
+        let vertices = [
+            TriangleVertices::new(
+                Vector2f::new(-1.0, 1.0),
+                Vector2f::new(1.0, 1.0),
+                Vector2f::new(-1.0, 0.0),
+            ),
+        ];
+        swap_chain.draw_rasterized(&vertices, &HalfWhiteShader, &WhiteShader);
+        assert_eq!(swap_chain.get_pixel(Vector2::new(1, 1)), Some(Pixel::WHITE));
+        assert_eq!(swap_chain.get_pixel(Vector2::new(1, 4)), Some(Pixel::BLACK));
+    }
+
+    #[test]
+    fn draw_in_pixel_space() {
+        let extent = Extent { width: 8, height: 6 };
+        let triangle = Triangle2D(Vector2::new(1, 1), Vector2::new(7, 2), Vector2::new(2, 5));
+        let vertices = [
+            TriangleVertices::new(
+                Vector2f::new(1.0, 1.0),
+                Vector2f::new(7.0, 2.0),
+                Vector2f::new(2.0, 5.0),
+            ),
+        ];
+
+        let mut swap_chain = SwapChain::with_extent(Extent { width: 8, height: 6 });
+        swap_chain.draw_rasterized_px(&vertices, &HalfWhiteShader, &WhiteShader);
+        assert_eq!(swap_chain.coordinate_space(), CoordinateSpace::NormalizedDevice);
+
+        let coverage: Vec<bool> = swap_chain.buffer.iter().map(|pixel| *pixel == Pixel::WHITE).collect();
+        assert_eq!(coverage, reference_fill(extent, &triangle));
+
+        let mut in_pixels = SwapChain::with_extent(Extent { width: 8, height: 6 });
+        in_pixels.set_coordinate_space(CoordinateSpace::Pixels);
+        in_pixels.draw_rasterized(&vertices, &HalfWhiteShader, &WhiteShader);
+        assert_eq!(in_pixels.buffer, swap_chain.buffer);
+    }
+
+    #[test]
+    fn rasterize_triangle_matches_reference_fill() {
+        let mut swap_chain = SwapChain::with_extent(Extent { width: 32, height: 24 });
+
+        // A simple linear congruential generator, so the test is deterministic.
+        let mut state = 0x2545F491u32;
+        let mut random = |bound: i32| {
+            state = state.wrapping_mul(1664525).wrapping_add(1013904223);
+            (state >> 8) as i32 % bound - 4
+        };
+
+        for _ in 0..200 {
+            let triangle = Triangle2D(
+                Vector2::new(random(40), random(32)),
+                Vector2::new(random(40), random(32)),
+                Vector2::new(random(40), random(32)),
+            );
+
+            swap_chain.clear(Pixel::BLACK);
+            let varyings = [0.0; MAX_VARYINGS];
+            let interpolator = Interpolator::new([Vector2f::default(); 3], [0.0; 3], [&varyings; 3]);
+            let assembled = AssembledTriangle { triangle, interpolator, index: 0 };
+            swap_chain.rasterize_triangle(&assembled, &HalfWhiteShader, &());
+
+            let coverage: Vec<bool> = swap_chain.buffer.iter().map(|pixel| *pixel != Pixel::BLACK).collect();
+            assert_eq!(coverage, reference_fill(swap_chain.extent, &triangle), "{triangle:?}");
+        }
+    }
+
+    #[test]
+    fn scanline_matches_half_space() {
+        let mut half_space = SwapChain::with_extent(Extent { width: 32, height: 24 });
+        let mut scanline = SwapChain::with_extent(Extent { width: 32, height: 24 });
+        scanline.set_fill_algorithm(FillAlgorithm::Scanline);
+
+        let mut state = 0x1B873593u32;
+        let mut random = |bound: i32| {
+            state = state.wrapping_mul(1664525).wrapping_add(1013904223);
+            (state >> 8) as i32 % bound - 4
+        };
+
+        for conservative_raster in [ConservativeRaster::Disabled, ConservativeRaster::Overestimate] {
+            half_space.set_conservative_raster(conservative_raster);
+            scanline.set_conservative_raster(conservative_raster);
+
+            for _ in 0..200 {
+                let triangle = Triangle2D(
+                    Vector2::new(random(40), random(32)),
+                    Vector2::new(random(40), random(32)),
+                    Vector2::new(random(40), random(32)),
+                );
+                let varyings = [0.0; MAX_VARYINGS];
+                let interpolator = Interpolator::new([Vector2f::default(); 3], [0.0; 3], [&varyings; 3]);
+                let assembled = AssembledTriangle { triangle, interpolator, index: 0 };
+
+                for swap_chain in [&mut half_space, &mut scanline] {
+                    swap_chain.clear(Pixel::BLACK);
+                    swap_chain.rasterize_triangle(&assembled, &HalfWhiteShader, &());
+                }
+                assert_eq!(scanline.buffer, half_space.buffer, "{triangle:?} {conservative_raster:?}");
+            }
+        }
+
+        // Whole draws match as well, also when multisampling.
+        let vertices = [
+            TriangleVertices::new(Vector2f::new(-0.9, -0.8), Vector2f::new(0.7, -0.6),
+                Vector2f::new(-0.2, 0.9)),
+            TriangleVertices::new(Vector2f::new(0.9, 0.9), Vector2f::new(-0.7, 0.3),
+                Vector2f::new(0.0, -1.5)),
+        ];
+        for sample_count in [SampleCount::X1, SampleCount::X4] {
+            for swap_chain in [&mut half_space, &mut scanline] {
+                swap_chain.set_sample_count(sample_count);
+                swap_chain.set_blend_mode(BlendMode::AlphaBlend);
+                swap_chain.clear(Pixel::BLACK);
+                swap_chain.draw_rasterized(&vertices, &HalfWhiteShader, &HalfWhiteShader);
+                swap_chain.resolve();
+            }
+            assert_eq!(scanline.buffer, half_space.buffer, "{sample_count:?}");
+        }
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn parallel_matches_serial() {
+        let vertices = [
+            TriangleVertices::new(Vector2f::new(-0.9, -0.8), Vector2f::new(0.7, -0.6),
+                Vector2f::new(-0.2, 0.9)),
+            TriangleVertices::new(Vector2f::new(-1.5, 0.2), Vector2f::new(1.2, 0.4),
+                Vector2f::new(0.3, 1.8)),
+        ];
+
+        for sample_count in [SampleCount::X1, SampleCount::X4] {
+            let mut serial = SwapChain::with_extent(Extent { width: 97, height: 61 });
+            serial.set_blend_mode(BlendMode::AlphaBlend);
+            serial.set_sample_count(sample_count);
+            serial.draw_rasterized(&vertices, &HalfWhiteShader, &HalfWhiteShader);
+
+            let mut parallel = SwapChain::with_extent(Extent { width: 97, height: 61 });
+            parallel.set_blend_mode(BlendMode::AlphaBlend);
+            parallel.set_sample_count(sample_count);
+            parallel.draw_rasterized_parallel(&vertices, &HalfWhiteShader, &HalfWhiteShader);
+
+            assert!(serial.buffer.iter().chain(&serial.samples).any(|pixel| *pixel != Pixel::BLACK));
+            assert_eq!(serial.buffer, parallel.buffer);
+            assert_eq!(serial.samples, parallel.samples);
+        }
+    }
+
+    #[test]
+    fn draw_multisampled() {
+        let mut swap_chain = SwapChain::with_extent(Extent { width: 4, height: 4 });
+        swap_chain.set_sample_count(SampleCount::X4);
+        swap_chain.clear(Pixel::BLACK);
+
+        let vertices = [
+            TriangleVertices::new(
+                Vector2f::new(-1.0, -1.0),
+                Vector2f::new(1.0, -1.0),
+                Vector2f::new(-1.0, 1.0),
+            )
+        ];
+        swap_chain.draw_rasterized(&vertices, &HalfWhiteShader, &WhiteShader);
+        swap_chain.resolve();
+
+        // The edge runs through the pixels where x + y = 4, which have two
+        // of their four samples covered.
+        assert_eq!(swap_chain.buffer[4 + 1], Pixel::WHITE);
+        assert_eq!(swap_chain.buffer[2 * 4 + 2], Pixel::new(0x80, 0x80, 0x80, 0xFF));
+        assert_eq!(swap_chain.buffer[3 * 4 + 1], Pixel::new(0x80, 0x80, 0x80, 0xFF));
+        assert_eq!(swap_chain.buffer[3 * 4 + 3], Pixel::BLACK);
+
+        swap_chain.set_sample_count(SampleCount::X1);
+        assert_eq!(swap_chain.buffer[2 * 4 + 2], Pixel::new(0x80, 0x80, 0x80, 0xFF));
+        assert!(swap_chain.samples.is_empty());
+    }
+
+    #[test]
+    fn multisampled_clear_and_resize() {
+        let mut swap_chain = SwapChain::with_extent(Extent { width: 3, height: 2 });
+        swap_chain.clear(Pixel::RED);
+        swap_chain.set_sample_count(SampleCount::X2);
+        assert_eq!(swap_chain.samples, vec![Pixel::RED; 12]);
+
+        swap_chain.clear(Pixel::BLUE);
+        assert_eq!(swap_chain.samples, vec![Pixel::BLUE; 12]);
+
+        swap_chain.resize(Extent { width: 5, height: 4 }, Pixel::GREEN);
+        assert_eq!(swap_chain.samples, vec![Pixel::GREEN; 40]);
+        swap_chain.resolve();
+        assert_eq!(swap_chain.buffer, vec![Pixel::GREEN; 20]);
+    }
+
+    #[test]
+    fn draw_in_rgb565() {
+        let extent = Extent { width: 4, height: 4 };
+        let mut swap_chain = SwapChain::<Rgb565>::try_with_format_extent(extent).unwrap();
+        swap_chain.clear(Pixel::new(0x00, 0x00, 0xFF, 0xFF));
+        swap_chain.set_blend_mode(BlendMode::AlphaBlend);
+
+        let vertices = [
+            TriangleVertices::new(
+                Vector2f::new(-1.0, -1.0),
+                Vector2f::new(1.0, -1.0),
+                Vector2f::new(-1.0, 1.0),
+            )
+        ];
+        swap_chain.draw_rasterized(&vertices, &HalfWhiteShader, &HalfWhiteShader);
+
+        assert_eq!(swap_chain.buffer[4 + 1], Rgb565::from_pixel(Pixel::new(0x80, 0x80, 0xFF, 0xFF)));
+        assert_eq!(swap_chain.buffer[3 * 4 + 3], Rgb565(0x001F));
+    }
+
+    #[test]
+    fn present_to_memory_surface() {
+        let mut swap_chain = SwapChain::with_extent(Extent { width: 3, height: 2 });
+        swap_chain.set_alpha_mode(AlphaMode::Premultiplied);
+        swap_chain.clear(Pixel::new(0xFF, 0x00, 0x00, 0x80));
+
+        let mut surface = MemorySurface::new();
+        swap_chain.present(&mut surface).unwrap();
+        assert_eq!(surface.extent(), Extent { width: 3, height: 2 });
+        assert_eq!(surface.pixels(), vec![Pixel::new(0xFF, 0x00, 0x00, 0x80); 6]);
+    }
+
+    #[test]
+    fn present_keeps_channel_order() {
+        let mut swap_chain = SwapChain::with_extent(Extent { width: 2, height: 1 });
+        swap_chain.put_pixel(Vector2::new(0, 0), Pixel::RED);
+        swap_chain.put_pixel(Vector2::new(1, 0), Pixel::BLUE);
+
+        let mut surface = MemorySurface::new();
+        swap_chain.present(&mut surface).unwrap();
+        assert_eq!(surface.pixels(), [Pixel::RED, Pixel::BLUE]);
+        assert_eq!(surface.pixels()[0].red(), 0xFF);
+        assert_eq!(surface.pixels()[0].to_bgra(), [0x00, 0x00, 0xFF, 0xFF]);
+
+        // The bytes handed to the surface are red, green, blue and alpha.
+        let bytes: [[u8; 4]; 2] = unsafe { std::mem::transmute([surface.pixels()[0], surface.pixels()[1]]) };
+        assert_eq!(bytes, [[0xFF, 0x00, 0x00, 0xFF], [0x00, 0x00, 0xFF, 0xFF]]);
+    }
+
+    /// Places the vertices on a plane at `z = -2`, as seen through a camera
+    /// with a field of view of 90 degrees.
+    struct PerspectiveShader;
+
+    impl VertexShader for PerspectiveShader {
+        fn run(&self, position: Vector2f, _uniforms: &()) -> VertexOutput {
+            let projection = Matrix4::perspective(std::f32::consts::FRAC_PI_2, 1.0, 0.5, 10.0);
+            VertexOutput::new(projection * Vector4::new(position.x, position.y, -2.0, 1.0))
+        }
+    }
+
+    #[test]
+    fn draw_with_perspective_projection() {
+        let square = [
+            TriangleVertices::new(
+                Vector2f::new(-1.0, -1.0),
+                Vector2f::new(1.0, -1.0),
+                Vector2f::new(-1.0, 1.0),
+            ),
+            TriangleVertices::new(
+                Vector2f::new(1.0, -1.0),
+                Vector2f::new(1.0, 1.0),
+                Vector2f::new(-1.0, 1.0),
+            ),
+        ];
+        let mut projected = SwapChain::with_extent(Extent { width: 8, height: 8 });
+        projected.draw_rasterized(&square, &PerspectiveShader, &WhiteShader);
+
+        // At twice the focal length, the square appears half as large.
+        let half_square = [
+            TriangleVertices::new(
+                Vector2f::new(-0.5, -0.5),
+                Vector2f::new(0.5, -0.5),
+                Vector2f::new(-0.5, 0.5),
+            ),
+            TriangleVertices::new(
+                Vector2f::new(0.5, -0.5),
+                Vector2f::new(0.5, 0.5),
+                Vector2f::new(-0.5, 0.5),
+            ),
+        ];
+        let mut flat = SwapChain::with_extent(Extent { width: 8, height: 8 });
+        flat.draw_rasterized(&half_square, &HalfWhiteShader, &WhiteShader);
+
+        assert!(projected.buffer.contains(&Pixel::WHITE));
+        assert_eq!(projected.buffer, flat.buffer);
+    }
+
+    #[test]
+    fn draw_triangle_behind_camera() {
+        struct BehindShader;
+
+        impl VertexShader for BehindShader {
+            fn run(&self, position: Vector2f, _uniforms: &()) -> VertexOutput {
+                let projection = Matrix4::perspective(std::f32::consts::FRAC_PI_2, 1.0, 0.5, 10.0);
+                VertexOutput::new(projection * Vector4::new(position.x, position.y, 2.0, 1.0))
+            }
+        }
+
+        let vertices = [
+            TriangleVertices::new(
+                Vector2f::new(-1.0, -1.0),
+                Vector2f::new(3.0, -1.0),
+                Vector2f::new(-1.0, 3.0),
+            ),
+        ];
+        let mut swap_chain = SwapChain::with_extent(Extent { width: 8, height: 8 });
+        swap_chain.draw_rasterized(&vertices, &BehindShader, &WhiteShader);
+        assert!(swap_chain.buffer.iter().all(|pixel| *pixel == Pixel::BLACK));
+    }
+
+    #[test]
+    fn draw_with_varyings() {
+        /// Passes the X coordinate in pixels of an 8 pixels wide swap chain.
+        struct GradientShader;
+
+        impl VertexShader for GradientShader {
+            fn run(&self, position: Vector2f, _uniforms: &()) -> VertexOutput {
+                let mut varyings = [0.0; MAX_VARYINGS];
+                varyings[1] = (position.x + 1.0) * 4.0;
+                VertexOutput::with_varyings(Vector4::new(position.x, position.y, 0.0, 1.0), varyings)
+            }
+        }
+
+        impl FragmentShader for GradientShader {
+            fn run(&self, input: &FragmentInput, _uniforms: &()) -> Pixel {
+                assert_eq!(input.varying(1), input.position().x);
+                assert_eq!(input.varyings()[0], 0.0);
+                Pixel::new((input.varying(1) * 32.0) as u8, 0x00, 0x00, 0xFF)
+            }
+        }
+
+        let vertices = [
+            TriangleVertices::new(
+                Vector2f::new(-1.0, -1.0),
+                Vector2f::new(3.0, -1.0),
+                Vector2f::new(-1.0, 3.0),
+            ),
+        ];
+        let mut swap_chain = SwapChain::with_extent(Extent { width: 8, height: 8 });
+        swap_chain.draw_rasterized(&vertices, &GradientShader, &GradientShader);
+
+        for (x, pixel) in swap_chain.buffer[..8].iter().enumerate() {
+            assert_eq!(*pixel, Pixel::new(x as u8 * 32, 0x00, 0x00, 0xFF));
+        }
+    }
+
+    #[test]
+    fn draw_flat_shaded() {
+        /// Gives every vertex a different color, depending on its position.
+        struct ColorShader;
+
+        impl VertexShader for ColorShader {
+            fn run(&self, position: Vector2f, _uniforms: &()) -> VertexOutput {
+                let mut varyings = [0.0; MAX_VARYINGS];
+                varyings[0] = (position.x + 1.0) / 2.0;
+                varyings[1] = (position.y + 1.0) / 2.0;
+                VertexOutput::with_varyings(Vector4::new(position.x, position.y, 0.0, 1.0), varyings)
+            }
+        }
+
+        impl FragmentShader for ColorShader {
+            fn run(&self, input: &FragmentInput, _uniforms: &()) -> Pixel {
+                Pixel::new((input.varying(0) * 255.0) as u8, (input.varying(1) * 255.0) as u8, 0xFF, 0xFF)
+            }
+        }
+
+        let vertices = [
+            TriangleVertices::new(
+                Vector2f::new(-1.0, -1.0),
+                Vector2f::new(1.0, -1.0),
+                Vector2f::new(-1.0, 1.0),
+            ),
+        ];
+
+        let draw = |shading_mode| {
+            let mut swap_chain = SwapChain::with_extent(Extent { width: 16, height: 16 });
+            swap_chain.set_shading_mode(shading_mode);
+            swap_chain.draw_rasterized(&vertices, &ColorShader, &ColorShader);
+            swap_chain.buffer.into_iter().filter(|pixel| *pixel != Pixel::BLACK).collect::<Vec<_>>()
+        };
+
+        let smooth = draw(ShadingMode::Smooth);
+        assert!(smooth.iter().any(|pixel| *pixel != smooth[0]));
+
+        let first = draw(ShadingMode::Flat(ProvokingVertex::First));
+        assert_eq!(first.len(), smooth.len());
+        assert!(first.iter().all(|pixel| *pixel == Pixel::new(0x00, 0x00, 0xFF, 0xFF)));
+
+        let last = draw(ShadingMode::Flat(ProvokingVertex::Last));
+        assert_eq!(last.len(), smooth.len());
+        assert!(last.iter().all(|pixel| *pixel == Pixel::new(0x00, 0xFF, 0xFF, 0xFF)));
+    }
+
+    #[test]
+    fn pick_front_triangle() {
+        let triangles = [
+            TriangleVertices::new(
+                Vector2f::new(-1.0, -1.0),
+                Vector2f::new(0.5, -1.0),
+                Vector2f::new(-1.0, 0.5),
+            ),
+            TriangleVertices::new(
+                Vector2f::new(-0.5, -0.5),
+                Vector2f::new(1.0, -0.5),
+                Vector2f::new(-0.5, 1.0),
+            ),
+        ];
+
+        for sample_count in [SampleCount::X1, SampleCount::X4] {
+            let mut swap_chain = SwapChain::with_extent(Extent { width: 16, height: 16 });
+            swap_chain.set_sample_count(sample_count);
+            assert!(!swap_chain.picking());
+            swap_chain.draw_rasterized(&triangles, &HalfWhiteShader, &WhiteShader);
+            assert_eq!(swap_chain.pick(Vector2::new(5, 5)), None);
+
+            swap_chain.set_picking(true);
+            swap_chain.draw_rasterized(&triangles, &HalfWhiteShader, &WhiteShader);
+            assert_eq!(swap_chain.pick(Vector2::new(1, 1)), Some(0), "{sample_count:?}");
+            assert_eq!(swap_chain.pick(Vector2::new(5, 5)), Some(1), "{sample_count:?}");
+            assert_eq!(swap_chain.pick(Vector2::new(9, 6)), Some(1), "{sample_count:?}");
+            assert_eq!(swap_chain.pick(Vector2::new(15, 15)), None, "{sample_count:?}");
+            assert_eq!(swap_chain.pick(Vector2::new(-1, 3)), None);
+            assert_eq!(swap_chain.pick(Vector2::new(3, 16)), None);
+
+            // With a depth test, the front triangle is picked regardless of
+            // the order in which they are drawn.
+            swap_chain.clear(Pixel::BLACK);
+            assert_eq!(swap_chain.pick(Vector2::new(5, 5)), None);
+            swap_chain.set_depth_test(Some(CompareFunction::Less));
+            swap_chain.draw_rasterized(&triangles[..1], &DepthShader(-0.5), &WhiteShader);
+            swap_chain.draw_rasterized(&triangles, &DepthShader(0.5), &WhiteShader);
+            assert_eq!(swap_chain.pick(Vector2::new(5, 5)), Some(0), "{sample_count:?}");
+            assert_eq!(swap_chain.pick(Vector2::new(9, 6)), Some(1), "{sample_count:?}");
+        }
+    }
+
+    #[test]
+    fn read_back_pixels() {
+        let mut swap_chain = SwapChain::with_extent(Extent { width: 4, height: 3 });
+        assert_eq!(swap_chain.extent(), Extent { width: 4, height: 3 });
+        swap_chain.fill_rect(Rectangle2D::new(Vector2::new(1, 1), Vector2::new(3, 3)), Pixel::RED);
+
+        assert_eq!(swap_chain.get_pixel(Vector2::new(0, 0)), Some(Pixel::BLACK));
+        assert_eq!(swap_chain.get_pixel(Vector2::new(1, 1)), Some(Pixel::RED));
+        assert_eq!(swap_chain.get_pixel(Vector2::new(2, 2)), Some(Pixel::RED));
+        assert_eq!(swap_chain.get_pixel(Vector2::new(3, 2)), Some(Pixel::BLACK));
+        for point in [(-1, 0), (0, -1), (4, 0), (0, 3)] {
+            assert_eq!(swap_chain.get_pixel(Vector2::from(point)), None, "{point:?}");
+        }
+
+        let red_pixels = swap_chain.as_slice().iter().filter(|pixel| **pixel == Pixel::RED).count();
+        assert_eq!(red_pixels, 4);
+        assert_eq!(swap_chain.as_slice()[4 + 1], Pixel::RED);
+
+        // Pixels are read back with straight alpha, and resolved when
+        // multisampling.
+        swap_chain.set_alpha_mode(AlphaMode::Premultiplied);
+        swap_chain.set_sample_count(SampleCount::X4);
+        let color = Pixel::new(0xFF, 0x00, 0x00, 0x80);
+        swap_chain.fill_rect(Rectangle2D::new(Vector2::new(0, 0), Vector2::new(1, 1)), color);
+        assert_eq!(swap_chain.get_pixel(Vector2::new(0, 0)), Some(color));
+    }
+
+    #[test]
+    fn put_pixel() {
+        let mut swap_chain = SwapChain::with_extent(Extent { width: 3, height: 2 });
+        assert!(swap_chain.put_pixel(Vector2::new(2, 1), Pixel::RED));
+        assert!(swap_chain.put_pixel(Vector2::new(0, 0), Pixel::GREEN));
+        assert_eq!(swap_chain.buffer[3 + 2], Pixel::RED);
+        assert_eq!(swap_chain.buffer[0], Pixel::GREEN);
+
+        for point in [(-1, 0), (0, -1), (3, 0), (0, 2), (i32::MIN, i32::MAX)] {
+            assert!(!swap_chain.put_pixel(Vector2::from(point), Pixel::WHITE), "{point:?}");
+        }
+        assert_eq!(swap_chain.buffer.iter().filter(|pixel| **pixel == Pixel::WHITE).count(), 0);
+
+        swap_chain.set_scissor(Some(Rectangle2D::new(Vector2::new(1, 0), Vector2::new(2, 2))));
+        assert!(!swap_chain.put_pixel(Vector2::new(0, 1), Pixel::WHITE));
+        assert!(swap_chain.put_pixel(Vector2::new(1, 1), Pixel::WHITE));
+        assert_eq!(swap_chain.buffer[3], Pixel::BLACK);
+        assert_eq!(swap_chain.buffer[3 + 1], Pixel::WHITE);
+    }
+
+    #[test]
+    fn writes_outside_render_area_are_clipped() {
+        let mut swap_chain = SwapChain::with_extent(Extent { width: 8, height: 4 });
+        swap_chain.set_scissor(Some(Rectangle2D::new(Vector2::new(2, 1), Vector2::new(6, 3))));
+        let extremes = [(-1, 1), (2, -1), (6, 1), (2, 3), (i32::MIN, i32::MIN), (i32::MAX, i32::MAX),
+            (i32::MIN, 1), (-8, 1), (2, i32::MAX)];
+
+        // Negative points would wrap around when cast to an index, and points
+        // right of a row would end up in the next row.
+        for (x, y) in extremes {
+            let point = Vector2::new(x, y);
+            assert!(!swap_chain.put_pixel(point, Pixel::WHITE), "{point:?}");
+            swap_chain.flood_fill(point, Pixel::WHITE, 0xFF);
+            let end = Vector2::new(x.saturating_add(1), y.saturating_add(1));
+            swap_chain.fill_rect(Rectangle2D::new(point, end), Pixel::WHITE);
+            swap_chain.fill_circle(Vector2f::new(x as f32, y as f32), 0.7, Pixel::WHITE);
+            assert!(written_pixels(&swap_chain).is_empty(), "{point:?}");
+        }
+
+        swap_chain.draw_line_aa(Vector2f::new(-100.0, -5.0), Vector2f::new(100.0, -5.0), Pixel::WHITE);
+        swap_chain.draw_line_aa(Vector2f::new(7.0, -100.0), Vector2f::new(7.0, 100.0), Pixel::WHITE);
+        assert!(written_pixels(&swap_chain).is_empty());
+
+        // Spans that straddle the scissor rectangle only write inside of it.
+        let span = Rectangle2D::new(Vector2::new(i32::MIN, 1), Vector2::new(i32::MAX, 2));
+        swap_chain.fill_rect(span, Pixel::WHITE);
+        swap_chain.draw_line_aa(Vector2f::new(-100.0, 2.0), Vector2f::new(100.0, 2.0), Pixel::WHITE);
+        swap_chain.flood_fill(Vector2::new(3, 1), Pixel::RED, 0);
+        assert_eq!(written_pixels(&swap_chain),
+            [(2, 1), (3, 1), (4, 1), (5, 1), (2, 2), (3, 2), (4, 2), (5, 2)]);
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "pixel (8, 0) is outside of the extent")]
+    fn write_pixel_past_row_panics() {
+        let mut swap_chain = SwapChain::with_extent(Extent { width: 8, height: 4 });
+        let state = swap_chain.output_state();
+        swap_chain.write_pixel(&state, 8, 0, Pixel::WHITE);
+    }
+
+    #[test]
+    fn pixels_visit_every_pixel_once() {
+        let mut swap_chain = SwapChain::with_extent(Extent { width: 5, height: 3 });
+        swap_chain.put_pixel(Vector2::new(3, 1), Pixel::RED);
+
+        let pixels: Vec<_> = swap_chain.pixels().collect();
+        assert_eq!(pixels.len(), 15);
+        // Every column appears on 3 rows, and every row in 5 columns.
+        assert_eq!(pixels.iter().map(|(position, _)| position.x).sum::<i32>(), 3 * 10);
+        assert_eq!(pixels.iter().map(|(position, _)| position.y).sum::<i32>(), 5 * 3);
+        let indices: Vec<_> = pixels.iter().map(|(position, _)| position.y * 5 + position.x).collect();
+        assert_eq!(indices, (0..15).collect::<Vec<_>>());
+        assert_eq!(pixels[..2], [(Vector2::new(0, 0), Pixel::BLACK), (Vector2::new(1, 0), Pixel::BLACK)]);
+        assert_eq!(pixels[8], (Vector2::new(3, 1), Pixel::RED));
+        for (position, color) in &pixels {
+            assert_eq!(swap_chain.get_pixel(*position), Some(*color));
+        }
+
+        swap_chain.reset_dirty();
+        for (position, value) in swap_chain.pixels_mut().unwrap() {
+            if position.x == position.y {
+                *value = Pixel::BLUE;
+            }
+        }
+        assert_eq!(swap_chain.dirty_rect(), Some(Rectangle2D::new(Vector2::new(0, 0), Vector2::new(5, 3))));
+        let blue: Vec<_> = swap_chain.pixels().filter(|(_, color)| *color == Pixel::BLUE).collect();
+        assert_eq!(blue.iter().map(|(position, _)| (position.x, position.y)).collect::<Vec<_>>(),
+            [(0, 0), (1, 1), (2, 2)]);
+
+        // The buffer can't be changed on its own when it is derived from
+        // the samples or the accumulated colors.
+        swap_chain.reset_dirty();
+        swap_chain.set_float_accumulation(true);
+        assert!(swap_chain.pixels_mut().is_none());
+        swap_chain.set_float_accumulation(false);
+        swap_chain.set_sample_count(SampleCount::X4);
+        assert!(swap_chain.pixels_mut().is_none());
+        assert_eq!(swap_chain.dirty_rect(), None);
+        swap_chain.set_sample_count(SampleCount::X1);
+        assert!(swap_chain.pixels_mut().is_some());
+    }
+
+    #[test]
+    fn dither_distributes_error() {
+        let mut swap_chain = SwapChain::with_extent(Extent { width: 64, height: 4 });
+        swap_chain.post_process(|position, _| {
+            let value = position.x as u8 * 4;
+            Pixel::new(value, value, value, 0x80)
+        });
+        let original: Vec<_> = swap_chain.pixels().map(|(_, pixel)| pixel).collect();
+
+        swap_chain.dither(2);
+        let dithered: Vec<_> = swap_chain.pixels().map(|(_, pixel)| pixel).collect();
+        assert!(dithered.iter().all(|pixel| [0x00, 0x55, 0xAA, 0xFF].contains(&pixel.red())));
+        assert!(dithered.iter().all(|pixel| pixel.green() == pixel.red() && pixel.alpha() == 0x80));
+
+        // Every block of the matrix averages to about the original gradient,
+        // while rounding every pixel would be off by up to half a level, and
+        // most blocks mix two levels instead of forming a band.
+        let mut mixed_blocks = 0;
+        for block in 0..16 {
+            let red = |pixels: &[Pixel]| -> Vec<i32> {
+                (0..16).map(|index| pixels[index / 4 * 64 + block * 4 + index % 4].red() as i32).collect()
+            };
+            let (before, after) = (red(&original), red(&dithered));
+            let error = after.iter().sum::<i32>() - before.iter().sum::<i32>();
+            assert!(error.abs() <= 16 * 12, "block {block} is off by {error}");
+            if after.iter().any(|value| *value != after[0]) {
+                mixed_blocks += 1;
+            }
+        }
+        assert!(mixed_blocks >= 12, "only {mixed_blocks} blocks are dithered");
+
+        // The pattern depends on nothing but the position.
+        swap_chain.dither(2);
+        assert_eq!(swap_chain.pixels().map(|(_, pixel)| pixel).collect::<Vec<_>>(), dithered);
+        swap_chain.dither(8);
+        assert_eq!(swap_chain.pixels().map(|(_, pixel)| pixel).collect::<Vec<_>>(), dithered);
+    }
+
+    #[test]
+    fn adjust_colors() {
+        let mut swap_chain = SwapChain::with_extent(Extent { width: 256, height: 1 });
+        swap_chain.post_process(|position, _| {
+            let value = position.x as u8;
+            Pixel::new(value, 0xFF - value, value / 2, value)
+        });
+        let original = swap_chain.buffer.clone();
+        swap_chain.adjust(0.0, 1.0, 1.0);
+        assert_eq!(swap_chain.buffer, original);
+
+        // A gamma of 2 squares the linear value of a midtone, which keeps
+        // black and white as they are.
+        let mut swap_chain = SwapChain::with_extent(Extent { width: 3, height: 1 });
+        let gray = Pixel::from_linear([0.5, 0.5, 0.5, 1.0]);
+        swap_chain.buffer = vec![Pixel::BLACK, gray, Pixel::WHITE];
+        swap_chain.adjust(0.0, 1.0, 2.0);
+        assert_eq!((swap_chain.buffer[0], swap_chain.buffer[2]), (Pixel::BLACK, Pixel::WHITE));
+        assert!(swap_chain.buffer[1].approx_eq(Pixel::from_linear([0.25, 0.25, 0.25, 1.0]), 1));
+        assert!(swap_chain.buffer[1].red() < gray.red());
+
+        swap_chain.buffer = vec![Pixel::BLACK, gray, Pixel::WHITE];
+        swap_chain.adjust(0.25, 1.0, 1.0);
+        assert_eq!(swap_chain.buffer[0], Pixel::from_linear([0.25, 0.25, 0.25, 1.0]));
+        assert_eq!(swap_chain.buffer[2], Pixel::WHITE);
+
+        swap_chain.buffer = vec![Pixel::BLACK, gray, Pixel::WHITE];
+        swap_chain.adjust(0.0, 0.0, 1.0);
+        assert_eq!(swap_chain.buffer, vec![gray; 3]);
+    }
+
+    #[test]
+    fn post_process_inverts_pixels() {
+        let invert = |pixel: Pixel| {
+            Pixel::new(0xFF - pixel.red(), 0xFF - pixel.green(), 0xFF - pixel.blue(), pixel.alpha())
+        };
+
+        let mut swap_chain = SwapChain::with_extent(Extent { width: 3, height: 2 });
+        swap_chain.buffer = test_image(Extent { width: 3, height: 2 });
+        let expected: Vec<Pixel> = swap_chain.buffer.iter().copied().map(invert).collect();
+        swap_chain.post_process(|_, pixel| invert(pixel));
+        assert_eq!(swap_chain.buffer, expected);
+
+        swap_chain.post_process(|position, _| Pixel::new(position.x as u8, position.y as u8, 0x00, 0xFF));
+        assert_eq!(swap_chain.buffer[2], Pixel::new(2, 0, 0x00, 0xFF));
+        assert_eq!(swap_chain.buffer[3 + 1], Pixel::new(1, 1, 0x00, 0xFF));
+
+        // Premultiplied and multisampled buffers are processed like straight
+        // ones.
+        let mut swap_chain = SwapChain::with_extent(Extent { width: 2, height: 2 });
+        swap_chain.set_alpha_mode(AlphaMode::Premultiplied);
+        swap_chain.set_sample_count(SampleCount::X4);
+        swap_chain.clear(Pixel::new(0xFF, 0x00, 0x00, 0x80));
+        swap_chain.post_process(|_, pixel| invert(pixel));
+
+        let mut surface = MemorySurface::new();
+        swap_chain.present(&mut surface).unwrap();
+        assert_eq!(surface.pixels(), vec![Pixel::new(0x00, 0xFF, 0xFF, 0x80); 4]);
+    }
+
+    #[test]
+    fn draw_instanced() {
+        let triangle = TriangleVertices::new(
+            Vector2f::new(-0.25, -0.25),
+            Vector2f::new(0.25, -0.25),
+            Vector2f::new(-0.25, 0.25),
+        );
+        let offsets = [(-0.5, -0.5), (0.5, 0.25)];
+        let transforms = offsets.map(|(x, y)| Matrix3::translation(x, y));
+
+        let mut swap_chain = SwapChain::with_extent(Extent { width: 16, height: 16 });
+        swap_chain.draw_instanced(&[triangle], &transforms, &HalfWhiteShader, &WhiteShader);
+
+        let mut expected = SwapChain::with_extent(Extent { width: 16, height: 16 });
+        let translated = offsets.map(|(x, y)| TriangleVertices::new(
+            Vector2f::new(triangle.a.x + x, triangle.a.y + y),
+            Vector2f::new(triangle.b.x + x, triangle.b.y + y),
+            Vector2f::new(triangle.c.x + x, triangle.c.y + y),
+        ));
+        expected.draw_rasterized(&translated, &HalfWhiteShader, &WhiteShader);
+
+        assert_eq!(swap_chain.buffer, expected.buffer);
+        assert_eq!(swap_chain.buffer[2 * 16 + 2], Pixel::WHITE);
+        assert_eq!(swap_chain.buffer[10 * 16 + 10], Pixel::WHITE);
+        assert_eq!(swap_chain.buffer[8 * 16 + 8], Pixel::BLACK);
+    }
+
+    #[test]
+    fn present_with_overlay() {
+        let mut swap_chain = SwapChain::with_extent(Extent { width: 4, height: 3 });
+        swap_chain.clear(Pixel::BLUE);
+
+        // The overlay hangs over the right edge, and its transparent pixel
+        // keeps the frame as is.
+        let overlay = [Pixel::RED, Pixel::TRANSPARENT, Pixel::new(0xFF, 0xFF, 0xFF, 0x80), Pixel::RED];
+        let mut surface = MemorySurface::new();
+        swap_chain.present_with_overlay(&mut surface, &overlay, Extent { width: 2, height: 2 },
+            Vector2::new(3, 1)).unwrap();
+
+        let mut expected = vec![Pixel::BLUE; 12];
+        expected[7] = Pixel::RED;
+        expected[11] = Pixel::new(0x80, 0x80, 0xFF, 0xFF);
+        assert_eq!(surface.pixels(), expected);
+        assert_eq!(swap_chain.as_slice(), [Pixel::BLUE; 12]);
+
+        swap_chain.present(&mut surface).unwrap();
+        assert_eq!(surface.pixels(), [Pixel::BLUE; 12]);
+    }
+
+    #[test]
+    fn dirty_rect_covers_writes() {
+        let mut swap_chain = SwapChain::with_extent(Extent { width: 8, height: 6 });
+        assert_eq!(swap_chain.dirty_rect(), None);
+        swap_chain.clear(Pixel::BLUE);
+        assert_eq!(swap_chain.dirty_rect(), Some(Rectangle2D::new(Vector2::new(0, 0), Vector2::new(8, 6))));
+
+        let mut surface = MemorySurface::new();
+        swap_chain.present(&mut surface).unwrap();
+        swap_chain.reset_dirty();
+        swap_chain.present_dirty(&mut surface).unwrap();
+
+        swap_chain.fill_rect(Rectangle2D::new(Vector2::new(2, 1), Vector2::new(4, 3)), Pixel::RED);
+        assert_eq!(swap_chain.dirty_rect(), Some(Rectangle2D::new(Vector2::new(2, 1), Vector2::new(4, 3))));
+        swap_chain.put_pixel(Vector2::new(6, 4), Pixel::RED);
+        assert_eq!(swap_chain.dirty_rect(), Some(Rectangle2D::new(Vector2::new(2, 1), Vector2::new(7, 5))));
+
+        // Pixels outside of the dirty rectangle aren't presented.
+        swap_chain.buffer[0] = Pixel::WHITE;
+        swap_chain.present_dirty(&mut surface).unwrap();
+        let mut expected = swap_chain.as_slice().to_vec();
+        expected[0] = Pixel::BLUE;
+        assert_eq!(surface.pixels(), expected);
+
+        // Triangles mark the pixels they may cover.
+        swap_chain.reset_dirty();
+        let before = swap_chain.as_slice().to_vec();
+        let vertices = [
+            TriangleVertices::new(Vector2f::new(1.0, 1.0), Vector2f::new(3.0, 1.0), Vector2f::new(1.0, 4.0)),
+        ];
+        swap_chain.draw_rasterized_px(&vertices, &HalfWhiteShader, &WhiteShader);
+        let dirty = swap_chain.dirty_rect().unwrap();
+        for (index, (before, after)) in before.iter().zip(swap_chain.as_slice()).enumerate() {
+            let point = Vector2::new((index % 8) as i32, (index / 8) as i32);
+            assert!(before == after || dirty.contains(point), "{point}");
+        }
+        assert!(dirty.contains(Vector2::new(1, 1)) && !dirty.contains(Vector2::new(5, 5)));
+    }
+
+    #[test]
+    fn draw_masked_by_stencil() {
+        let mask = [
+            TriangleVertices::new(
+                Vector2f::new(-1.0, -1.0),
+                Vector2f::new(1.0, -1.0),
+                Vector2f::new(-1.0, 1.0),
+            ),
+        ];
+        let quad = [
+            TriangleVertices::new(
+                Vector2f::new(-1.0, -1.0),
+                Vector2f::new(1.0, -1.0),
+                Vector2f::new(1.0, 1.0),
+            ),
+            TriangleVertices::new(
+                Vector2f::new(-1.0, -1.0),
+                Vector2f::new(1.0, 1.0),
+                Vector2f::new(-1.0, 1.0),
+            ),
+        ];
+
+        for sample_count in [SampleCount::X1, SampleCount::X4] {
+            let mut expected = SwapChain::with_extent(Extent { width: 8, height: 8 });
+            expected.set_sample_count(sample_count);
+            expected.draw_rasterized(&mask, &HalfWhiteShader, &WhiteShader);
+            expected.resolve();
+
+            // The mask only writes the stencil buffer, since the color of the
+            // draw is fully transparent.
+            let mut swap_chain = SwapChain::with_extent(Extent { width: 8, height: 8 });
+            swap_chain.set_sample_count(sample_count);
+            swap_chain.set_blend_mode(BlendMode::AlphaBlend);
+            swap_chain.clear_stencil(0);
+            swap_chain.set_stencil_test(Some(StencilTest::write(1)));
+            swap_chain.draw_rasterized(&mask, &HalfWhiteShader, &TransparentShader);
+
+            swap_chain.set_blend_mode(BlendMode::Opaque);
+            swap_chain.set_stencil_test(Some(StencilTest::equal(1)));
+            swap_chain.draw_rasterized(&quad, &HalfWhiteShader, &WhiteShader);
+            swap_chain.resolve();
+
+            assert!(swap_chain.buffer.contains(&Pixel::BLACK));
+            assert_eq!(swap_chain.buffer, expected.buffer, "{sample_count:?}");
+
+            // Rectangles are masked as well.
+            swap_chain.set_stencil_test(Some(StencilTest::equal(0)));
+            swap_chain.fill_rect(Rectangle2D::new(Vector2::new(0, 0), Vector2::new(8, 8)), Pixel::WHITE);
+            swap_chain.resolve();
+            assert!(swap_chain.buffer.iter().all(|pixel| *pixel == Pixel::WHITE), "{sample_count:?}");
+        }
+    }
+
+    /// Places the vertices at the given depth.
+    struct DepthShader(f32);
+
+    impl VertexShader for DepthShader {
+        fn run(&self, position: Vector2f, _uniforms: &()) -> VertexOutput {
+            VertexOutput::new(Vector4::new(position.x, position.y, self.0, 1.0))
+        }
+    }
+
+    #[test]
+    fn draw_sorted_back_to_front() {
+        /// Places the vertices of the near, red layer at a depth of -0.5. The
+        /// vertices of the far, blue layer are given four to the right, which
+        /// this undoes, placing them at a depth of 0.5.
+        struct LayerShader;
+
+        impl VertexShader for LayerShader {
+            fn run(&self, position: Vector2f, _uniforms: &()) -> VertexOutput {
+                let far = position.x > 2.0;
+                let x = if far { position.x - 4.0 } else { position.x };
+                let mut varyings = [0.0; MAX_VARYINGS];
+                varyings[0] = if far { 1.0 } else { 0.0 };
+                VertexOutput::with_varyings(Vector4::new(x, position.y, if far { 0.5 } else { -0.5 }, 1.0),
+                    varyings)
+            }
+        }
+
+        impl FragmentShader for LayerShader {
+            fn run(&self, input: &FragmentInput, _uniforms: &()) -> Pixel {
+                if input.varying(0) > 0.5 { BLUE } else { RED }
+            }
+        }
+
+        const RED: Pixel = Pixel::new(0xFF, 0x00, 0x00, 0x80);
+        const BLUE: Pixel = Pixel::new(0x00, 0x00, 0xFF, 0x80);
+        let vertices = [
+            TriangleVertices::new(
+                Vector2f::new(-1.0, -1.0),
+                Vector2f::new(0.5, -1.0),
+                Vector2f::new(-1.0, 1.0),
+            ),
+            TriangleVertices::new(
+                Vector2f::new(3.5, -1.0),
+                Vector2f::new(5.0, -1.0),
+                Vector2f::new(5.0, 1.0),
+            ),
+        ];
+
+        let over = |source, destination| BlendMode::AlphaBlend.blend(source, destination);
+        let pixel = 8 + 3;
+
+        let mut swap_chain = SwapChain::with_extent(Extent { width: 8, height: 8 });
+        swap_chain.set_blend_mode(BlendMode::AlphaBlend);
+        swap_chain.draw_rasterized(&vertices, &LayerShader, &LayerShader);
+        let unsorted = swap_chain.buffer[pixel];
+        assert_eq!(unsorted, over(BLUE, over(RED, Pixel::BLACK)));
+
+        let mut swap_chain = SwapChain::with_extent(Extent { width: 8, height: 8 });
+        swap_chain.set_blend_mode(BlendMode::AlphaBlend);
+        swap_chain.draw_sorted(&vertices, &LayerShader, &LayerShader);
+        let sorted = swap_chain.buffer[pixel];
+        assert_eq!(sorted, over(RED, over(BLUE, Pixel::BLACK)));
+        assert_ne!(sorted, unsorted);
+    }
+
+    #[test]
+    fn depth_test_discards_hidden_fragments() {
+        let lower_left = [
+            TriangleVertices::new(
+                Vector2f::new(-1.0, -1.0),
+                Vector2f::new(1.0, -1.0),
+                Vector2f::new(-1.0, 1.0),
+            ),
+        ];
+        let full_screen = [
+            TriangleVertices::new(
+                Vector2f::new(-1.0, -1.0),
+                Vector2f::new(3.0, -1.0),
+                Vector2f::new(-1.0, 3.0),
+            ),
+        ];
+
+        for sample_count in [SampleCount::X1, SampleCount::X4] {
+            let mut swap_chain = SwapChain::with_extent(Extent { width: 4, height: 4 });
+            swap_chain.set_sample_count(sample_count);
+            swap_chain.set_depth_test(Some(CompareFunction::Less));
+            swap_chain.draw_rasterized(&lower_left, &DepthShader(-0.5), &HalfWhiteShader);
+            swap_chain.draw_rasterized(&full_screen, &DepthShader(0.5), &WhiteShader);
+            swap_chain.resolve();
+
+            assert_eq!(swap_chain.buffer[4 + 1], Pixel::new(0xFF, 0xFF, 0xFF, 0x80), "{sample_count:?}");
+            assert_eq!(swap_chain.buffer[3 * 4 + 3], Pixel::WHITE, "{sample_count:?}");
+        }
+    }
+
+    #[test]
+    fn draw_perspective_correct() {
+        /// Draws a plane that recedes to the right, where it's three times as
+        /// far away as on the left, with a varying going from 0 on the left
+        /// to 1 on the right.
+        struct FloorShader;
+
+        impl VertexShader for FloorShader {
+            fn run(&self, position: Vector2f, _uniforms: &()) -> VertexOutput {
+                let w = 2.0 + position.x;
+                let mut varyings = [0.0; MAX_VARYINGS];
+                varyings[0] = (position.x + 1.0) / 2.0;
+                VertexOutput::with_varyings(Vector4::new(position.x * w, position.y * w, 0.0, w), varyings)
+            }
+        }
+
+        impl FragmentShader for FloorShader {
+            fn run(&self, input: &FragmentInput, _uniforms: &()) -> Pixel {
+                Pixel::new((input.varying(0) * 255.0).round() as u8, 0x00, 0x00, 0xFF)
+            }
+        }
+
+        let quad = [
+            TriangleVertices::new(
+                Vector2f::new(-1.0, -1.0),
+                Vector2f::new(1.0, -1.0),
+                Vector2f::new(1.0, 1.0),
+            ),
+            TriangleVertices::new(
+                Vector2f::new(-1.0, -1.0),
+                Vector2f::new(1.0, 1.0),
+                Vector2f::new(-1.0, 1.0),
+            ),
+        ];
+
+        // In the middle of the screen, the plane is only twice as far away
+        // as on the left, which is a quarter of the way in clip space.
+        let mut swap_chain = SwapChain::with_extent(Extent { width: 4, height: 4 });
+        assert_eq!(swap_chain.interpolation(), Interpolation::PerspectiveCorrect);
+        swap_chain.draw_rasterized(&quad, &FloorShader, &FloorShader);
+        assert_eq!(swap_chain.buffer[4 + 2], Pixel::new(0x40, 0x00, 0x00, 0xFF));
+
+        swap_chain.set_interpolation(Interpolation::Affine);
+        swap_chain.draw_rasterized(&quad, &FloorShader, &FloorShader);
+        assert_eq!(swap_chain.buffer[4 + 2], Pixel::new(0x80, 0x00, 0x00, 0xFF));
+    }
+
+    #[test]
+    fn alpha_test_discards_fragments() {
+        struct Checkerboard;
+
+        impl FragmentShader for Checkerboard {
+            fn run(&self, input: &FragmentInput, _uniforms: &()) -> Pixel {
+                let position = input.position();
+                match (position.x + position.y) as i32 % 2 {
+                    0 => Pixel::new(0xFF, 0xFF, 0xFF, 0x10),
+                    _ => Pixel::RED,
+                }
+            }
+        }
+
+        let quad = [
+            TriangleVertices::new(
+                Vector2f::new(-1.0, -1.0),
+                Vector2f::new(1.0, -1.0),
+                Vector2f::new(1.0, 1.0),
+            ),
+            TriangleVertices::new(
+                Vector2f::new(-1.0, -1.0),
+                Vector2f::new(1.0, 1.0),
+                Vector2f::new(-1.0, 1.0),
+            ),
+        ];
+
+        for sample_count in [SampleCount::X1, SampleCount::X4] {
+            let mut swap_chain = SwapChain::with_extent(Extent { width: 4, height: 4 });
+            swap_chain.set_sample_count(sample_count);
+            swap_chain.set_depth_test(Some(CompareFunction::Less));
+            swap_chain.set_alpha_test(Some(0x80));
+            swap_chain.clear(Pixel::BLUE);
+            swap_chain.clear_depth(1.0);
+            swap_chain.draw_rasterized(&quad, &HalfWhiteShader, &Checkerboard);
+            swap_chain.resolve();
+
+            // When multisampling, some samples of the pixels in the first row
+            // and column lie outside of the buffer.
+            for (x, y) in (1..4).flat_map(|y| (1..4).map(move |x| (x, y))) {
+                let expected = if (x + y) % 2 == 0 { Pixel::BLUE } else { Pixel::RED };
+                assert_eq!(swap_chain.buffer[y * 4 + x], expected, "{sample_count:?} at ({x}, {y})");
+            }
+
+            // The depth of discarded fragments isn't written either.
+            let count = sample_count.count();
+            let depths = swap_chain.depth.as_ref().unwrap();
+            assert!(depths[5 * count..6 * count].iter().all(|depth| *depth == 1.0));
+            assert!(depths[6 * count..7 * count].iter().all(|depth| *depth == 0.0));
+        }
+    }
+
+    #[test]
+    fn depth_only_pass() {
+        let full_screen = [
+            TriangleVertices::new(
+                Vector2f::new(-1.0, -1.0),
+                Vector2f::new(3.0, -1.0),
+                Vector2f::new(-1.0, 3.0),
+            ),
+        ];
+
+        let mut swap_chain = SwapChain::with_extent(Extent { width: 4, height: 4 });
+        swap_chain.clear(Pixel::RED);
+        swap_chain.set_depth_test(Some(CompareFunction::Less));
+        swap_chain.set_color_write(false);
+        swap_chain.draw_rasterized(&full_screen, &DepthShader(0.5), &WhiteShader);
+        swap_chain.fill_rect(Rectangle2D::new(Vector2::new(0, 0), Vector2::new(4, 4)), Pixel::WHITE);
+
+        assert_eq!(swap_chain.buffer, vec![Pixel::RED; 16]);
+        assert_eq!(swap_chain.depth, Some(vec![0.5; 16]));
+
+        // The shaded pass only writes the fragments at the depth of the
+        // depth-only pass.
+        swap_chain.set_color_write(true);
+        swap_chain.set_depth_write(false);
+        swap_chain.set_depth_test(Some(CompareFunction::Equal));
+        swap_chain.draw_rasterized(&full_screen, &DepthShader(0.75), &HalfWhiteShader);
+        assert_eq!(swap_chain.buffer, vec![Pixel::RED; 16]);
+
+        swap_chain.draw_rasterized(&full_screen, &DepthShader(0.5), &WhiteShader);
+        assert_eq!(swap_chain.buffer, vec![Pixel::WHITE; 16]);
+        assert_eq!(swap_chain.depth, Some(vec![0.5; 16]));
+
+        swap_chain.clear_depth(1.0);
+        assert_eq!(swap_chain.depth, Some(vec![1.0; 16]));
+    }
+
+    #[test]
+    fn draw_with_uniforms() {
+        struct Uniforms {
+            time: f32,
+        }
+
+        /// Fades from black to red over a second.
+        struct FadeShader;
+
+        impl FragmentShader<Uniforms> for FadeShader {
+            fn run(&self, _input: &FragmentInput, uniforms: &Uniforms) -> Pixel {
+                Pixel::new((uniforms.time.clamp(0.0, 1.0) * 255.0) as u8, 0x00, 0x00, 0xFF)
+            }
+        }
+
+        let vertices = [
+            TriangleVertices::new(
+                Vector2f::new(-1.0, -1.0),
+                Vector2f::new(3.0, -1.0),
+                Vector2f::new(-1.0, 3.0),
+            ),
+        ];
+        let mut swap_chain = SwapChain::with_extent(Extent { width: 4, height: 4 });
+
+        let uniforms = Uniforms { time: 0.0 };
+        swap_chain.draw_rasterized_with_uniforms(&vertices, &HalfWhiteShader, &FadeShader, &uniforms);
+        assert!(swap_chain.buffer.iter().all(|pixel| *pixel == Pixel::BLACK));
+
+        let uniforms = Uniforms { time: 1.0 };
+        swap_chain.draw_rasterized_with_uniforms(&vertices, &HalfWhiteShader, &FadeShader, &uniforms);
+        assert!(swap_chain.buffer.iter().all(|pixel| *pixel == Pixel::RED));
+    }
+
+    #[test]
+    fn draw_with_linear_blending() {
+        let linear = ColorSpace::Linear;
+        assert_eq!(draw_half_white(BlendMode::AlphaBlend, linear), Pixel::new(0xBC, 0xBC, 0xBC, 0xFF));
+    }
 }
@@ -0,0 +1,331 @@
+// Copyright (C) 2023 Tristan Gerritsen <tristan@thewoosh.org>
+// All Rights Reserved.
+
+//! Textures with a chain of mipmaps, i.e. ever smaller copies of the image,
+//! and the samplers that pick between them, so textures that are drawn
+//! smaller than their size don't alias.
+
+use crate::{filter::Filter, math::Vector2f, shader::FragmentInput, swap_chain::Extent, Pixel};
+
+/// A single image in the mip chain of a texture.
+#[derive(Clone, Debug, PartialEq)]
+struct MipLevel {
+    pixels: Vec<Pixel>,
+    extent: Extent,
+}
+
+/// A texture together with its mip chain, of which every level is half the
+/// width and height of the previous one, rounded down, until both are a
+/// single pixel.
+#[derive(Clone, Debug, PartialEq)]
+pub struct MipmappedTexture {
+    levels: Vec<MipLevel>,
+}
+
+impl MipmappedTexture {
+
+    /// Creates the texture from the image, specified with straight alpha,
+    /// generating the other levels of the mip chain with a box filter, which
+    /// averages the premultiplied colors of every two by two pixels.
+    ///
+    /// # Panics
+    /// Panics when the length of the image doesn't match its extent, or when
+    /// the image is empty.
+    pub fn new(pixels: &[Pixel], extent: Extent) -> Self {
+        assert_eq!(pixels.len(), extent.width * extent.height, "image doesn't match its extent");
+        assert!(!extent.is_empty(), "textures must have at least one pixel");
+
+        let mut levels = vec![MipLevel { pixels: pixels.to_vec(), extent }];
+        while let Some(level) = levels.last().and_then(MipLevel::downsampled) {
+            levels.push(level);
+        }
+        Self { levels }
+    }
+
+    /// Creates a texture of square tiles of the given size in texels, which
+    /// alternate between the colors, specified with straight alpha, starting
+    /// with the first color at the top left corner. This is useful for
+    /// checking texture coordinates and filtering.
+    ///
+    /// # Panics
+    /// Panics when the tile size is zero, or when the extent is empty.
+    pub fn checkerboard(extent: Extent, tile: usize, color_a: Pixel, color_b: Pixel) -> Self {
+        assert_ne!(tile, 0, "tiles must have at least one texel");
+        let pixels: Vec<_> = (0..extent.height)
+            .flat_map(|y| (0..extent.width).map(move |x| (x, y)))
+            .map(|(x, y)| match (x / tile + y / tile) % 2 {
+                0 => color_a,
+                _ => color_b,
+            })
+            .collect();
+        Self::new(&pixels, extent)
+    }
+
+    /// Creates an opaque texture of which the red channel is the horizontal
+    /// texture coordinate, and the green channel the vertical one, at the
+    /// center of every texel. This shows how texture coordinates are mapped
+    /// onto a triangle.
+    ///
+    /// # Panics
+    /// Panics when the extent is empty.
+    pub fn uv_gradient(extent: Extent) -> Self {
+        let coordinate = |texel: usize, size: usize| {
+            ((texel as f32 + 0.5) / size as f32 * 255.0).round() as u8
+        };
+        let pixels: Vec<_> = (0..extent.height)
+            .flat_map(|y| (0..extent.width).map(move |x| (x, y)))
+            .map(|(x, y)| Pixel::new(coordinate(x, extent.width), coordinate(y, extent.height), 0x00, 0xFF))
+            .collect();
+        Self::new(&pixels, extent)
+    }
+
+    /// Gets the extent of the full-size image.
+    pub fn extent(&self) -> Extent {
+        self.levels[0].extent
+    }
+
+    /// Gets the pixels and the extent of the given level of the mip chain,
+    /// where level 0 is the full-size image, or `None` when the chain doesn't
+    /// have that level.
+    pub fn level(&self, level: usize) -> Option<(&[Pixel], Extent)> {
+        self.levels.get(level).map(|level| (level.pixels.as_slice(), level.extent))
+    }
+
+    /// Gets the amount of levels in the mip chain, including the full-size
+    /// image.
+    pub fn level_count(&self) -> usize {
+        self.levels.len()
+    }
+
+}
+
+impl MipLevel {
+
+    /// Creates the next level of the mip chain, or `None` when this level is
+    /// a single pixel.
+    fn downsampled(&self) -> Option<MipLevel> {
+        if self.extent.width == 1 && self.extent.height == 1 {
+            return None;
+        }
+
+        let extent = Extent {
+            width: (self.extent.width / 2).max(1),
+            height: (self.extent.height / 2).max(1),
+        };
+        let pixel = |x: usize, y: usize| {
+            let x = x.min(self.extent.width - 1);
+            let y = y.min(self.extent.height - 1);
+            self.pixels[y * self.extent.width + x].premultiplied()
+        };
+
+        let mut pixels = Vec::with_capacity(extent.width * extent.height);
+        for y in 0..extent.height {
+            for x in 0..extent.width {
+                let box_pixels = [
+                    pixel(x * 2, y * 2),
+                    pixel(x * 2 + 1, y * 2),
+                    pixel(x * 2, y * 2 + 1),
+                    pixel(x * 2 + 1, y * 2 + 1),
+                ];
+                let average = |channel: fn(&Pixel) -> u8| {
+                    ((box_pixels.iter().map(|pixel| channel(pixel) as u32).sum::<u32>() + 2) / 4) as u8
+                };
+                pixels.push(Pixel::new(average(Pixel::red), average(Pixel::green), average(Pixel::blue),
+                    average(Pixel::alpha)).unpremultiplied());
+            }
+        }
+        Some(MipLevel { pixels, extent })
+    }
+
+}
+
+/// The way a sampler combines the levels of the mip chain.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub enum MipmapFilter {
+
+    /// Only sample the full-size image, ignoring the mip chain.
+    None,
+
+    /// Sample the level closest to the level of detail.
+    Nearest,
+
+    /// Interpolate linearly between the samples of the two levels around the
+    /// level of detail. Together with [`Filter::Bilinear`], this is also
+    /// known as trilinear filtering.
+    #[default]
+    Linear,
+
+}
+
+/// The way a texture is sampled, both within a level of its mip chain and
+/// across the levels.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Sampler {
+
+    /// The filter within a level of the mip chain.
+    pub filter: Filter,
+
+    /// The filter across the levels of the mip chain.
+    pub mipmap_filter: MipmapFilter,
+
+}
+
+impl Sampler {
+
+    /// Creates the sampler that uses trilinear filtering.
+    pub fn trilinear() -> Self {
+        Self {
+            filter: Filter::Bilinear,
+            mipmap_filter: MipmapFilter::Linear,
+        }
+    }
+
+    /// Computes the level of detail, i.e. the level of the mip chain of which
+    /// a texel is about the size of a pixel, given how much the texture
+    /// coordinates change per pixel in either direction. The level of detail
+    /// is fractional, and clamped to the levels of the chain.
+    pub fn level_of_detail(texture: &MipmappedTexture, uv_dx: Vector2f, uv_dy: Vector2f) -> f32 {
+        let extent = texture.extent();
+        let texels = |uv: Vector2f| {
+            let texels = Vector2f::new(uv.x * extent.width as f32, uv.y * extent.height as f32);
+            texels.dot(texels).sqrt()
+        };
+
+        let footprint = texels(uv_dx).max(texels(uv_dy));
+        if footprint <= 1.0 {
+            return 0.0;
+        }
+        footprint.log2().min((texture.level_count() - 1) as f32)
+    }
+
+    /// Samples the texture at the texture coordinates, from `(0, 0)` at the
+    /// top left to `(1, 1)` at the bottom right corner, given how much they
+    /// change per pixel in either direction, which selects the levels of the
+    /// mip chain. Coordinates outside of the texture are clamped to its
+    /// edges.
+    pub fn sample(&self, texture: &MipmappedTexture, uv: Vector2f, uv_dx: Vector2f, uv_dy: Vector2f) -> Pixel {
+        let level_of_detail = Self::level_of_detail(texture, uv_dx, uv_dy);
+        match self.mipmap_filter {
+            MipmapFilter::None => self.sample_level(texture, 0, uv),
+            MipmapFilter::Nearest => self.sample_level(texture, level_of_detail.round() as usize, uv),
+            MipmapFilter::Linear => {
+                let level = level_of_detail.floor();
+                let finer = self.sample_level(texture, level as usize, uv);
+                let t = level_of_detail - level;
+                if t == 0.0 {
+                    return finer;
+                }
+
+                let coarser = self.sample_level(texture, level as usize + 1, uv);
+                Pixel::lerp(finer.premultiplied(), coarser.premultiplied(), t).unpremultiplied()
+            }
+        }
+    }
+
+    /// Samples the texture in a fragment shader, at the texture coordinates
+    /// in the varyings with the given indices, of which the change per pixel
+    /// is estimated from the neighbouring pixels in the triangle.
+    ///
+    /// # Panics
+    /// Panics when either index isn't below
+    /// [`MAX_VARYINGS`](crate::shader::MAX_VARYINGS).
+    pub fn sample_fragment(&self, texture: &MipmappedTexture, input: &FragmentInput, u_index: usize,
+            v_index: usize) -> Pixel {
+        let (u_dx, u_dy) = input.varying_derivatives(u_index);
+        let (v_dx, v_dy) = input.varying_derivatives(v_index);
+        let uv = Vector2f::new(input.varying(u_index), input.varying(v_index));
+        self.sample(texture, uv, Vector2f::new(u_dx, v_dx), Vector2f::new(u_dy, v_dy))
+    }
+
+    fn sample_level(&self, texture: &MipmappedTexture, level: usize, uv: Vector2f) -> Pixel {
+        let level = &texture.levels[level];
+        let position = Vector2f::new(
+            uv.x * level.extent.width as f32 - 0.5,
+            uv.y * level.extent.height as f32 - 0.5,
+        );
+        self.filter.sample(&level.pixels, level.extent, position)
+    }
+
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn checkerboard(extent: Extent) -> MipmappedTexture {
+        MipmappedTexture::checkerboard(extent, 1, Pixel::WHITE, Pixel::BLACK)
+    }
+
+    #[test]
+    fn checkerboard_alternates_at_tile_boundaries() {
+        let extent = Extent { width: 8, height: 7 };
+        let texture = MipmappedTexture::checkerboard(extent, 3, Pixel::RED, Pixel::BLUE);
+        let (pixels, _) = texture.level(0).unwrap();
+        let texel = |x: usize, y: usize| pixels[y * extent.width + x];
+
+        assert_eq!([texel(0, 0), texel(2, 0), texel(3, 0), texel(5, 0), texel(6, 0), texel(7, 0)],
+            [Pixel::RED, Pixel::RED, Pixel::BLUE, Pixel::BLUE, Pixel::RED, Pixel::RED]);
+        assert_eq!([texel(0, 2), texel(0, 3), texel(3, 3), texel(3, 6), texel(7, 6)],
+            [Pixel::RED, Pixel::BLUE, Pixel::RED, Pixel::BLUE, Pixel::RED]);
+    }
+
+    #[test]
+    fn uv_gradient_at_texel_centers() {
+        let extent = Extent { width: 4, height: 2 };
+        let texture = MipmappedTexture::uv_gradient(extent);
+        let (pixels, _) = texture.level(0).unwrap();
+        assert_eq!(pixels[0], Pixel::new(0x20, 0x40, 0x00, 0xFF));
+        assert_eq!(pixels[3], Pixel::new(0xDF, 0x40, 0x00, 0xFF));
+        assert_eq!(pixels[7], Pixel::new(0xDF, 0xBF, 0x00, 0xFF));
+    }
+
+    #[test]
+    fn mip_chain_of_checkerboard() {
+        let extent = Extent { width: 8, height: 4 };
+        let texture = checkerboard(extent);
+        assert_eq!(texture.level_count(), 4);
+        assert_eq!(texture.level(3).map(|(_, extent)| extent), Some(Extent { width: 1, height: 1 }));
+        assert_eq!(texture.level(4), None);
+
+        let gray = Pixel::new(0x80, 0x80, 0x80, 0xFF);
+        for level in 1..texture.level_count() {
+            let (pixels, _) = texture.level(level).unwrap();
+            assert!(pixels.iter().all(|pixel| *pixel == gray), "level {level}");
+        }
+    }
+
+    #[test]
+    fn sample_selects_level_by_footprint() {
+        let extent = Extent { width: 8, height: 8 };
+        let texture = checkerboard(extent);
+        let sampler = Sampler { filter: Filter::Nearest, mipmap_filter: MipmapFilter::Nearest };
+        let uv = Vector2f::new(0.5 / 8.0, 0.5 / 8.0);
+
+        // A texel per pixel samples the full-size image.
+        let one_texel = Vector2f::new(1.0 / 8.0, 0.0);
+        assert_eq!(Sampler::level_of_detail(&texture, one_texel, Vector2f::new(0.0, 1.0 / 8.0)), 0.0);
+        assert_eq!(sampler.sample(&texture, uv, one_texel, Vector2f::default()), Pixel::WHITE);
+
+        // Four texels per pixel sample the gray level of two by two pixels.
+        let four_texels = Vector2f::new(0.5, 0.0);
+        assert_eq!(Sampler::level_of_detail(&texture, four_texels, Vector2f::default()), 2.0);
+        assert_eq!(sampler.sample(&texture, uv, four_texels, Vector2f::default()),
+            Pixel::new(0x80, 0x80, 0x80, 0xFF));
+
+        let unfiltered = Sampler { mipmap_filter: MipmapFilter::None, ..sampler };
+        assert_eq!(unfiltered.sample(&texture, uv, four_texels, Vector2f::default()), Pixel::WHITE);
+    }
+
+    #[test]
+    fn trilinear_interpolates_between_levels() {
+        let extent = Extent { width: 8, height: 8 };
+        let texture = checkerboard(extent);
+        let uv = Vector2f::new(0.5 / 8.0, 0.5 / 8.0);
+
+        // Halfway between the first two levels, at 2^0.5 texels per pixel.
+        let footprint = Vector2f::new(std::f32::consts::SQRT_2 / 8.0, 0.0);
+        let sampler = Sampler { filter: Filter::Nearest, ..Sampler::trilinear() };
+        let pixel = sampler.sample(&texture, uv, footprint, Vector2f::default());
+        assert!(pixel.approx_eq(Pixel::new(0xC0, 0xC0, 0xC0, 0xFF), 1), "{pixel:?}");
+    }
+}
@@ -0,0 +1,243 @@
+// Copyright (C) 2023 Tristan Gerritsen <tristan@thewoosh.org>
+// All Rights Reserved.
+
+//! A rasterizer that divides the render area into square tiles, and draws the
+//! triangles tile by tile. Every tile only touches a small part of the buffer,
+//! which keeps it in the cache while the triangles overlapping it are drawn.
+
+use crate::{
+    format::PixelFormat,
+    math::{Rectangle2D, Vector2},
+    shader::{FragmentShader, VertexShader},
+    swap_chain::{AssembledTriangle, SwapChain},
+    TriangleVertices,
+};
+
+/// The width and height of a tile in pixels, when not specified otherwise.
+pub const DEFAULT_TILE_SIZE: u32 = 32;
+
+/// Draws triangles by binning them into tiles first. The output is identical
+/// to that of [`SwapChain::draw_rasterized`], since the triangles are drawn
+/// in the order they are submitted within every tile.
+///
+/// The rasterizer keeps its bins around between draws, so reusing it avoids
+/// allocating them every frame.
+#[derive(Clone, Debug)]
+pub struct TiledRasterizer {
+    tile_size: i32,
+    triangles: Vec<AssembledTriangle>,
+    bins: Vec<Vec<usize>>,
+}
+
+impl TiledRasterizer {
+
+    /// Creates a rasterizer with tiles of [`DEFAULT_TILE_SIZE`] pixels.
+    pub fn new() -> Self {
+        Self::with_tile_size(DEFAULT_TILE_SIZE)
+    }
+
+    /// Creates a rasterizer with tiles of the given width and height.
+    ///
+    /// # Panics
+    /// Panics when the tile size is zero or doesn't fit in an `i32`.
+    pub fn with_tile_size(tile_size: u32) -> Self {
+        assert!(tile_size != 0, "tile size must not be zero");
+        let tile_size = i32::try_from(tile_size).expect("tile size is too large");
+
+        Self {
+            tile_size,
+            triangles: Vec::new(),
+            bins: Vec::new(),
+        }
+    }
+
+    /// Gets the width and height of a tile in pixels.
+    pub fn tile_size(&self) -> u32 {
+        self.tile_size as u32
+    }
+
+    /// Draws the triangles to the swap chain, using the same state and
+    /// producing the same pixels as [`SwapChain::draw_rasterized`].
+    pub fn draw<P: PixelFormat>(&mut self, swap_chain: &mut SwapChain<P>, vertices: &[TriangleVertices],
+            vertex_shader: &dyn VertexShader, fragment_shader: &dyn FragmentShader) {
+        self.draw_with_uniforms(swap_chain, vertices, vertex_shader, fragment_shader, &());
+    }
+
+    /// Draws the triangles just like [`TiledRasterizer::draw`], passing the
+    /// uniforms to every invocation of the shaders.
+    pub fn draw_with_uniforms<P: PixelFormat, U: ?Sized>(&mut self, swap_chain: &mut SwapChain<P>,
+            vertices: &[TriangleVertices], vertex_shader: &dyn VertexShader<U>,
+            fragment_shader: &dyn FragmentShader<U>, uniforms: &U) {
+        let Some(render_area) = swap_chain.render_area() else {
+            return;
+        };
+        let Some(clip_rectangle) = swap_chain.clip_rectangle() else {
+            return;
+        };
+
+        self.triangles.clear();
+        for (index, vertex_triple) in vertices.iter().enumerate() {
+            let triangles = swap_chain.assemble_triangles(vertex_triple, index, vertex_shader, uniforms,
+                &clip_rectangle);
+            self.triangles.extend(triangles);
+        }
+
+        let columns = (render_area.width() + self.tile_size - 1) / self.tile_size;
+        let rows = (render_area.height() + self.tile_size - 1) / self.tile_size;
+        self.bin_triangles(swap_chain, &render_area, columns as usize, rows as usize);
+
+        for row in 0..rows {
+            for column in 0..columns {
+                let min = Vector2::new(
+                    render_area.x() + column * self.tile_size,
+                    render_area.y() + row * self.tile_size,
+                );
+                let max = Vector2::new(
+                    (min.x + self.tile_size).min(render_area.x_range().end),
+                    (min.y + self.tile_size).min(render_area.y_range().end),
+                );
+                let tile = Rectangle2D::new(min, max);
+
+                for &index in &self.bins[(row * columns + column) as usize] {
+                    let triangle = &self.triangles[index];
+                    let bounds = swap_chain.rasterization_bounds(&triangle.triangle);
+                    if let Some(bounds) = bounds.and_then(|bounds| bounds.intersect(&tile)) {
+                        swap_chain.rasterize_triangle_bounded(triangle, &bounds, fragment_shader, uniforms);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Puts the index of every triangle in the bins of the tiles its bounding
+    /// box overlaps, in the order of the triangles.
+    fn bin_triangles<P: PixelFormat>(&mut self, swap_chain: &SwapChain<P>, render_area: &Rectangle2D<i32>,
+            columns: usize, rows: usize) {
+        self.bins.resize_with(columns * rows, Vec::new);
+        for bin in &mut self.bins {
+            bin.clear();
+        }
+
+        for (index, triangle) in self.triangles.iter().enumerate() {
+            let Some(bounds) = swap_chain.rasterization_bounds(&triangle.triangle) else {
+                continue;
+            };
+
+            let first_column = (bounds.x() - render_area.x()) / self.tile_size;
+            let last_column = (bounds.x_range().end - 1 - render_area.x()) / self.tile_size;
+            let first_row = (bounds.y() - render_area.y()) / self.tile_size;
+            let last_row = (bounds.y_range().end - 1 - render_area.y()) / self.tile_size;
+
+            for row in first_row..=last_row {
+                for column in first_column..=last_column {
+                    self.bins[row as usize * columns + column as usize].push(index);
+                }
+            }
+        }
+    }
+
+}
+
+impl Default for TiledRasterizer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        blend::BlendMode,
+        math::Vector2f,
+        multisample::SampleCount,
+        shader::{FragmentShader2D, VertexShader2D},
+        swap_chain::Extent,
+        Pixel,
+    };
+
+    struct HalfWhiteShader;
+
+    impl VertexShader2D for HalfWhiteShader {
+        fn run(&self, position: Vector2f) -> Vector2f {
+            position
+        }
+    }
+
+    impl FragmentShader2D for HalfWhiteShader {
+        fn run(&self) -> Pixel {
+            Pixel::new(0xFF, 0xFF, 0xFF, 0x80)
+        }
+    }
+
+    /// Generates small, overlapping triangles all over the swap chain, and
+    /// somewhat beyond it.
+    fn small_triangles(count: usize) -> Vec<TriangleVertices> {
+        // A simple linear congruential generator, so the test is deterministic.
+        let mut state = 0x6C078965u32;
+        let mut random = || {
+            state = state.wrapping_mul(1664525).wrapping_add(1013904223);
+            (state >> 8) as f32 / (1 << 24) as f32 * 2.4 - 1.2
+        };
+
+        (0..count)
+            .map(|_| {
+                let center = Vector2f::new(random(), random());
+                let mut corner = || Vector2f::new(center.x + random() * 0.1, center.y + random() * 0.1);
+                TriangleVertices::new(corner(), corner(), corner())
+            })
+            .collect()
+    }
+
+    #[test]
+    fn tiled_matches_draw_rasterized() {
+        let vertices = small_triangles(500);
+
+        for sample_count in [SampleCount::X1, SampleCount::X4] {
+            for tile_size in [1, 7, DEFAULT_TILE_SIZE, 1000] {
+                let mut expected = SwapChain::with_extent(Extent { width: 150, height: 90 });
+                expected.set_blend_mode(BlendMode::AlphaBlend);
+                expected.set_sample_count(sample_count);
+                expected.draw_rasterized(&vertices, &HalfWhiteShader, &HalfWhiteShader);
+                expected.resolve();
+
+                let mut tiled = SwapChain::with_extent(Extent { width: 150, height: 90 });
+                tiled.set_blend_mode(BlendMode::AlphaBlend);
+                tiled.set_sample_count(sample_count);
+                TiledRasterizer::with_tile_size(tile_size).draw(&mut tiled, &vertices, &HalfWhiteShader,
+                    &HalfWhiteShader);
+                tiled.resolve();
+
+                assert!(expected.as_slice().iter().any(|pixel| *pixel != Pixel::BLACK));
+                assert_eq!(expected.as_slice(), tiled.as_slice(), "{sample_count:?}, tile size {tile_size}");
+            }
+        }
+    }
+
+    #[test]
+    fn tiled_respects_scissor() {
+        let vertices = small_triangles(200);
+        let scissor = Some(Rectangle2D::new(Vector2::new(13, 9), Vector2::new(71, 40)));
+
+        let mut expected = SwapChain::with_extent(Extent { width: 80, height: 50 });
+        expected.set_scissor(scissor);
+        expected.draw_rasterized(&vertices, &HalfWhiteShader, &HalfWhiteShader);
+
+        let mut tiled = SwapChain::with_extent(Extent { width: 80, height: 50 });
+        tiled.set_scissor(scissor);
+        let mut rasterizer = TiledRasterizer::with_tile_size(16);
+
+        // Drawing twice makes sure the bins are reset between draws.
+        rasterizer.draw(&mut tiled, &vertices, &HalfWhiteShader, &HalfWhiteShader);
+        tiled.clear(Pixel::BLACK);
+        rasterizer.draw(&mut tiled, &vertices, &HalfWhiteShader, &HalfWhiteShader);
+
+        assert_eq!(expected.as_slice(), tiled.as_slice());
+    }
+
+    #[test]
+    #[should_panic]
+    fn zero_tile_size_panics() {
+        TiledRasterizer::with_tile_size(0);
+    }
+}
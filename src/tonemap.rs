@@ -0,0 +1,76 @@
+// Copyright (C) 2023 Tristan Gerritsen <tristan@thewoosh.org>
+// All Rights Reserved.
+
+//! Tone mapping, which maps colors in linear light that may exceed the
+//! displayable range, such as the result of additive blending, to 8-bit sRGB
+//! pixels for presentation.
+
+use crate::Pixel;
+
+/// The way colors in linear light are brought into the displayable range.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Tonemap {
+
+    /// Clamp every channel to `[0, 1]`, which loses the detail in overexposed
+    /// areas.
+    #[default]
+    Clamp,
+
+    /// Map every channel `c` to `c / (1 + c)`, which compresses bright
+    /// values instead of clipping them, so they never reach full intensity.
+    Reinhard,
+
+}
+
+impl Tonemap {
+
+    /// Maps the normalized linear `[red, green, blue, alpha]` color to an
+    /// sRGB pixel. The alpha channel is clamped, since it isn't a light
+    /// intensity.
+    pub fn map(&self, color: [f32; 4]) -> Pixel {
+        let [red, green, blue, alpha] = color;
+        let channel = |value: f32| match self {
+            Tonemap::Clamp => value,
+            Tonemap::Reinhard => {
+                let value = value.max(0.0);
+                value / (1.0 + value)
+            }
+        };
+        Pixel::from_linear([channel(red), channel(green), channel(blue), alpha])
+    }
+
+    /// Maps every color of the buffer, see [`Tonemap::map`].
+    pub fn map_buffer(&self, buffer: &[[f32; 4]]) -> Vec<Pixel> {
+        buffer.iter().map(|color| self.map(*color)).collect()
+    }
+
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn overexposed_colors() {
+        let overexposed = [4.0, 1.0, 0.0, 2.0];
+        assert_eq!(Tonemap::Clamp.map(overexposed), Pixel::new(0xFF, 0xFF, 0x00, 0xFF));
+
+        let mapped = Tonemap::Reinhard.map(overexposed);
+        assert!(mapped.red() < 0xFF);
+        assert!(mapped.green() < mapped.red());
+        assert_eq!(mapped.blue(), 0x00);
+        assert_eq!(mapped.alpha(), 0xFF);
+    }
+
+    #[test]
+    fn displayable_colors() {
+        let gray = Pixel::new(0x80, 0x80, 0x80, 0x80);
+        assert_eq!(Tonemap::Clamp.map(gray.to_linear()), gray);
+        assert_eq!(Tonemap::Clamp.map([-1.0, 0.0, 0.0, -1.0]), Pixel::TRANSPARENT);
+
+        // Reinhard maps a value of one to half of the intensity.
+        let half = Pixel::from_linear([0.5, 0.5, 0.5, 1.0]);
+        assert_eq!(Tonemap::Reinhard.map([1.0, 1.0, 1.0, 1.0]), half);
+        assert_eq!(Tonemap::Reinhard.map_buffer(&[[0.0; 4], [1.0; 4]]), vec![Pixel::TRANSPARENT, half]);
+    }
+}